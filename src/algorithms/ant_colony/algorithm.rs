@@ -1,10 +1,11 @@
-use rand::thread_rng;
 
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
 use super::ant::Ant;
 use super::types::{City, Matrix, PheromoneMatrix};
+use crate::algorithms::helpers::make_rng;
 use crate::problems::travelling_salesman::helpers::calculate_distance;
 
-use random_choice::random_choice;
 
 #[derive(Debug)]
 pub struct AntColonyAlgorithm {
@@ -19,6 +20,7 @@ pub struct AntColonyAlgorithm {
 }
 
 impl AntColonyAlgorithm {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         iters_count: usize,
         solutions_count: usize,
@@ -41,11 +43,11 @@ impl AntColonyAlgorithm {
         }
     }
 
-    fn run(&self) -> Result<Vec<Ant>, &str> {
+    pub fn run(&self) -> Result<Vec<Ant>, &'static str> {
         let cities_count = self.cities_count();
         let mut pheromone_matrix: PheromoneMatrix = Self::generate_pheromone_matrix(cities_count);
         let mut solutions: Vec<Ant> = Vec::new();
-        let mut rng = thread_rng();
+        let mut rng = make_rng();
         let mut colony: Vec<Ant> = (0..self.actors_count).map(|_| Ant::new(cities_count, &mut rng)).collect();
 
         for _ in 1..=self.iters_count {
@@ -53,11 +55,11 @@ impl AntColonyAlgorithm {
 
             for ant in colony.iter_mut() {
                 for _ in 0..cities_count - 1 {
-                    let probabilities = self.get_probabilities_list(&ant, &mut pheromone_matrix)?;
-                    let city = self.select_city(probabilities)?;
+                    let probabilities = self.get_probabilities_list(ant, &mut pheromone_matrix)?;
+                    let city = self.select_city(probabilities, &mut rng)?;
                     ant.go_to(city);
 
-                    ant.distance = self.get_ant_distance(&ant);
+                    ant.distance = self.get_ant_distance(ant);
                     if ant.distance > 0. {
                         iter_pheromone_matrix[ant.previous_city()][city] += self.q / ant.distance
                     }
@@ -79,7 +81,7 @@ impl AntColonyAlgorithm {
     }
 
     pub fn cities_count(&self) -> usize {
-        self.matrix.len() as usize
+        self.matrix.len()
     }
 
     pub fn cities_list(&self) -> Vec<City> {
@@ -87,7 +89,7 @@ impl AntColonyAlgorithm {
     }
 
     fn get_ant_visibility(&self, ant: &Ant, city: &City) -> f64 {
-        if ant.path.contains(&city) {
+        if ant.path.contains(city) {
             return 0.;
         }
 
@@ -109,7 +111,7 @@ impl AntColonyAlgorithm {
                 return 0.;
             }
 
-            let visibility = self.get_ant_visibility(&ant, &city);
+            let visibility = self.get_ant_visibility(ant, &city);
             let pheromone = pheromone_matrix[ant.current_city()][city];
 
             visibility.powf(self.alpha) * pheromone.powf(self.beta)
@@ -121,7 +123,7 @@ impl AntColonyAlgorithm {
             .collect()
     }
 
-    pub fn get_probabilities_list(&self, ant: &Ant, pheromone_matrix: &mut PheromoneMatrix) -> Result<Vec<f64>, &str> {
+    pub fn get_probabilities_list(&self, ant: &Ant, pheromone_matrix: &mut PheromoneMatrix) -> Result<Vec<f64>, &'static str> {
         let cities_preferences = self.get_ant_preferences(ant, pheromone_matrix);
         let cities_preferences_sum: f64 = cities_preferences.iter().sum();
 
@@ -141,13 +143,10 @@ impl AntColonyAlgorithm {
         }
     }
 
-    pub fn select_city(&self, probabilities: Vec<f64>) -> Result<City, &str> {
-        let cities_list: Vec<City> = self.cities_list();
-        let selected_cities = random_choice().random_choice_f64(&cities_list, &probabilities, 1);
-        if selected_cities.len() == 0 {
-            Err("Ant haven't found a path")
-        } else {
-            Ok(*selected_cities[0])
+    pub fn select_city(&self, probabilities: Vec<f64>, rng: &mut StdRng) -> Result<City, &'static str> {
+        match WeightedIndex::new(&probabilities) {
+            Ok(dist) => Ok(self.cities_list()[dist.sample(rng)]),
+            Err(_) => Err("Ant haven't found a path"),
         }
     }
 
@@ -1,5 +1,5 @@
 use super::types::City;
-use rand::rngs::ThreadRng;
+use rand::rngs::StdRng;
 use rand::Rng;
 
 #[derive(Debug, Clone)]
@@ -10,7 +10,7 @@ pub struct Ant {
 }
 
 impl Ant {
-    pub fn new(cities_count: usize, rng: &mut ThreadRng) -> Self {
+    pub fn new(cities_count: usize, rng: &mut StdRng) -> Self {
         let first_city: City = rng.gen_range(0..cities_count);
         let mut path: Vec<City> = Vec::with_capacity(cities_count);
         path.push(first_city);
@@ -1,7 +1,7 @@
 use std::fmt::Debug;
-use rand::thread_rng;
-use random_choice::random_choice;
-use crate::algorithms::types::{FitnessFuncs, Population, Purpose};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use crate::algorithms::types::{Fitness, FitnessFuncs, Population, Purpose};
 use super::types::GenerateFuncRaw;
 use crate::algorithms::helpers;
 use crate::algorithms::individual::Individual as Bee;
@@ -16,47 +16,71 @@ pub struct BeeColonyAlgorithm<T> {
     pub fitness_funcs: FitnessFuncs<T>,
     pub research_func: ResearchFunction<T>,
     pub generate_func: GenerateFuncRaw<T>,
+    pub initial_values: Vec<Vec<T>>,
 }
 
 impl<T: Clone + Debug> BeeColonyAlgorithm<T> {
-    pub fn run(&self) -> Result<Vec<Bee<T>>, &str> {
+    pub fn run(&self) -> Result<Vec<Bee<T>>, &'static str> {
+        self.run_with(Vec::new())
+    }
+
+    // Given values and then own initial values are used as the first food sources of the workers
+    pub fn run_with(&self, initial_values: Vec<Vec<T>>) -> Result<Vec<Bee<T>>, &'static str> {
+        self.run_observed(initial_values, &mut |_, _| true)
+    }
+
+    // The observer gets the iteration number and the best food source after every iteration
+    pub fn run_observed(
+        &self,
+        initial_values: Vec<Vec<T>>,
+        observer: &mut dyn FnMut(usize, &Bee<T>) -> bool,
+    ) -> Result<Vec<Bee<T>>, &'static str> {
         let workers_count = (self.workers_part * self.actors_count as f32).round() as usize;
         let onlookers_count = self.actors_count - workers_count;
-        let mut workers: Population<T> = (0..workers_count).map(|_| self.generate_bee()).collect();
-        let mut rng = thread_rng();
+        let mut workers: Population<T> = initial_values
+            .into_iter()
+            .chain(self.initial_values.iter().cloned())
+            .take(workers_count)
+            .map(|value| Bee::with_fitnesses(value, &self.fitness_funcs))
+            .collect();
+        while workers.len() < workers_count {
+            workers.push(self.generate_bee());
+        }
+        let mut rng = helpers::make_rng();
 
         helpers::calculate_fitnesses(&mut workers, &self.fitness_funcs);
 
-        for _ in 0..self.iters_count {
-            let mut onlookers: Population<T> = (0..onlookers_count).map(|_| { self.generate_bee() }).collect();
-            helpers::calculate_fitnesses(&mut onlookers, &self.fitness_funcs);
-            onlookers.shrink_to_fit();
+        for iter in 0..self.iters_count {
+            let onlookers: Population<T> = (0..onlookers_count).map(|_| { self.generate_bee() }).collect();
+            let researched: Population<T> = workers
+                .iter()
+                .map(|worker| Bee::with_fitnesses((self.research_func.0)(&worker.value, &mut rng), &self.fitness_funcs))
+                .collect();
+
+            // Workers, researched sources and onlookers are compared on one scale
+            let workers_count = workers.len();
+            let mut sources: Population<T> = workers.into_iter().chain(researched).chain(onlookers).collect();
+            helpers::calculate_fitnesses(&mut sources, &self.fitness_funcs);
+            let onlookers = sources.split_off(2 * workers_count);
+            let researched = sources.split_off(workers_count);
+            workers = sources;
 
             let probabilities = self.get_source_probabilities(&onlookers);
-            for worker in workers.iter_mut() {
-                let selected_source: &Bee<T> = self.select_onlooker_by_probabilities(&onlookers, &probabilities);
-
-                let researched_source = (self.research_func.0)(&worker.value, &mut rng);
-                let researched_fitness = worker.fitness;
-
-                if let Purpose::Min = self.purpose {
-                    if researched_fitness < selected_source.fitness && (worker.fitness.is_some() && researched_fitness < worker.fitness || worker.fitness.is_none()) {
-                        worker.value = researched_source;
-                        worker.fitness = researched_fitness;
-                    } else if selected_source.fitness.is_some() && (worker.fitness.is_some() && selected_source.fitness < worker.fitness || worker.fitness.is_none()) {
-                        worker.value = selected_source.value.clone();
-                        worker.fitness = selected_source.fitness;
-                    }
-                } else {
-                    if researched_fitness.is_some() && researched_fitness > selected_source.fitness && researched_fitness > worker.fitness {
-                        worker.value = researched_source;
-                        worker.fitness = researched_fitness;
-                    } else if selected_source.fitness.is_some() && selected_source.fitness > worker.fitness {
-                        worker.value = selected_source.value.clone();
-                        worker.fitness = selected_source.fitness;
-                    }
+            for (worker, researched_source) in workers.iter_mut().zip(researched) {
+                let selected_source: &Bee<T> = self.select_onlooker_by_probabilities(&onlookers, &probabilities, &mut rng);
+
+                if self.is_better(researched_source.fitness, selected_source.fitness)
+                    && self.is_better(researched_source.fitness, worker.fitness)
+                {
+                    *worker = researched_source;
+                } else if self.is_better(selected_source.fitness, worker.fitness) {
+                    *worker = selected_source.clone();
                 }
             }
+
+            if helpers::best_by_energy(&workers, &self.purpose).is_some_and(|best| !observer(iter, best)) {
+                break;
+            }
         }
 
         helpers::calculate_fitnesses(&mut workers, &self.fitness_funcs);
@@ -72,7 +96,7 @@ impl<T: Clone + Debug> BeeColonyAlgorithm<T> {
                 None => return true
             };
 
-            return fitness_a.total_cmp(&fitness_b).is_eq()
+            fitness_a.total_cmp(&fitness_b).is_eq()
         });
         workers.sort_by(helpers::compare_by_fitness(&self.purpose));
         workers.truncate(self.solutions_count );
@@ -84,35 +108,64 @@ impl<T: Clone + Debug> BeeColonyAlgorithm<T> {
         Bee::with_fitnesses(source, &self.fitness_funcs)
     }
 
-    fn get_source_probabilities(&self, onlookers: &Vec<Bee<T>>) -> Vec<f32> {
-        let fitness_sum: f32 = onlookers.iter().filter_map(|bee| bee.fitness).sum();
-        let get_probability: fn(Option<f32>, f32) -> f32 = match self.purpose {
-            Purpose::Min => |fit: Option<f32>, sum: f32| {
-                if let Some(fitness) = fit {
-                    1. - fitness / sum
-                } else {
-                    0.
-                }
+    // A source without a fitness, e.g. an excluded tour, is never better than another one
+    fn is_better(&self, fitness: Fitness, other: Fitness) -> bool {
+        match (fitness, other) {
+            (Some(fitness), Some(other)) => match self.purpose {
+                Purpose::Min => fitness < other,
+                Purpose::Max => fitness > other,
             },
-            Purpose::Max => |fit: Option<f32>, sum: f32| {
-                if let Some(fitness) = fit {
-                    fitness / sum
-                } else {
-                    0.
-                }
-            }
-        };
-        return onlookers.iter().map(|bee| {
-            get_probability(bee.fitness, fitness_sum)
-        }).collect();
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+
+    fn get_source_probabilities(&self, onlookers: &[Bee<T>]) -> Vec<f32> {
+        helpers::windowed_weights(onlookers, &self.purpose)
     }
 
-    fn select_onlooker_by_probabilities<'a>(&self, onlookers: &'a Vec<Bee<T>>, probabilities: &Vec<f32>) -> &'a Bee<T> {
-        let selected_bees = random_choice().random_choice_f32(&onlookers, &probabilities, 1);
-        if selected_bees.is_empty() {
-            panic!("Unable to select bee: {:?}", onlookers)
-        } else {
-            selected_bees[0]
+    fn select_onlooker_by_probabilities<'a>(&self, onlookers: &'a [Bee<T>], probabilities: &[f32], rng: &mut StdRng) -> &'a Bee<T> {
+        match WeightedIndex::new(probabilities) {
+            Ok(dist) => &onlookers[dist.sample(rng)],
+            Err(_) => panic!("Unable to select bee: {:?}", onlookers),
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::bee_colony::types::ResearchFunction;
+
+    // One worker starting at 50 and one onlooker at 100, the fitness is the value itself
+    fn algorithm(research_func: ResearchFunction<i64>) -> BeeColonyAlgorithm<i64> {
+        BeeColonyAlgorithm {
+            actors_count: 2,
+            iters_count: 5,
+            solutions_count: 1,
+            workers_part: 0.5,
+            purpose: Purpose::Min,
+            fitness_funcs: vec![Box::new(|value: &Vec<i64>| if value[0] >= 0 { Some(value[0] as f64) } else { None })],
+            research_func,
+            generate_func: Box::new(|| vec![100]),
+            initial_values: vec![vec![50]],
+        }
+    }
+
+    #[test]
+    fn better_research_replaces_the_source() {
+        let algorithm = algorithm(ResearchFunction(Box::new(|value, _| vec![value[0] - 1])));
+
+        let workers = algorithm.run().unwrap();
+
+        assert_eq!(workers[0].value, vec![45]);
+    }
+
+    #[test]
+    fn research_without_a_fitness_is_dropped() {
+        let algorithm = algorithm(ResearchFunction(Box::new(|_, _| vec![-1])));
+
+        let workers = algorithm.run().unwrap();
+
+        assert_eq!(workers[0].value, vec![50]);
+    }
+}
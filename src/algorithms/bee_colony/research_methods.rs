@@ -1,23 +1,27 @@
-use rand::rngs::ThreadRng;
 use crate::algorithms::bee_colony::types::ResearchFunction;
-use crate::algorithms::helpers;
+use crate::algorithms::operators::Operator;
 
 
-pub fn swap_indexes<T: Clone>(offset: Option<usize>) -> ResearchFunction<T> {
-    ResearchFunction(Box::new(move |value, rng: &mut ThreadRng| {
-        let (left, right) = helpers::generate_two_points(offset, value.len(), rng);
-        let mut source = value.to_vec();
-        source.swap(left, right);
-        source
-    }))
+pub fn swap_indexes<T: Clone + 'static>(offset: Option<usize>) -> ResearchFunction<T> {
+    Operator::swap_indexes(offset).into()
 }
 
-pub fn reverse_elements<T: Clone>(offset: Option<usize>) -> ResearchFunction<T> {
-    ResearchFunction(Box::new(move |value, rng: &mut ThreadRng| {
-        let (left, right) = helpers::generate_two_points(offset, value.len(), rng);
-        let mut value_new = value.to_vec();
+pub fn reverse_elements<T: Clone + 'static>(offset: Option<usize>) -> ResearchFunction<T> {
+    Operator::reverse_elements(offset).into()
+}
+
+pub fn insertion<T: Clone + 'static>(offset: Option<usize>) -> ResearchFunction<T> {
+    Operator::insertion(offset).into()
+}
+
+pub fn scramble<T: Clone + 'static>(offset: Option<usize>) -> ResearchFunction<T> {
+    Operator::scramble(offset).into()
+}
+
+pub fn displacement<T: Clone + 'static>(offset: Option<usize>) -> ResearchFunction<T> {
+    Operator::displacement(offset).into()
+}
 
-        value_new[left..right].reverse();
-        value_new
-    }))
+pub fn two_opt<T: Clone + 'static>(cost_func: impl Fn(&T, &T) -> f64 + 'static) -> ResearchFunction<T> {
+    Operator::two_opt(cost_func).into()
 }
\ No newline at end of file
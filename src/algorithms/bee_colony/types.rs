@@ -1,7 +1,7 @@
-use rand::rngs::ThreadRng;
+use rand::rngs::StdRng;
 
 pub type FoodSource<T> = Vec<T>;
 
 pub type GenerateFuncRaw<T> = Box<dyn Fn() -> FoodSource<T>>;
-pub struct ResearchFunction<T>(pub(crate) Box<dyn Fn(&Vec<T>, &mut ThreadRng) -> Vec<T>>);
-pub type ResearchFuncRaw<T> = Box<dyn Fn(&FoodSource<T>, &mut ThreadRng) -> FoodSource<T>>;
\ No newline at end of file
+pub type ResearchFuncRaw<T> = Box<dyn Fn(&FoodSource<T>, &mut StdRng) -> FoodSource<T>>;
+pub struct ResearchFunction<T>(pub(crate) ResearchFuncRaw<T>);
\ No newline at end of file
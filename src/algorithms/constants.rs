@@ -1,3 +1,5 @@
 pub const ITERS_COUNT: usize = 50;
 pub const ACTORS_COUNT: usize = 100;
-pub const SOLUTIONS_COUNT: usize = 3;
\ No newline at end of file
+pub const SOLUTIONS_COUNT: usize = 3;
+pub const MOVES_COUNT: usize = 100;
+pub const RUNS_COUNT: usize = 30;
\ No newline at end of file
@@ -1,11 +1,16 @@
 use crate::algorithms::{
     individual::Individual,
-    genetic::types::{CrossoverFunc, GenerateFunc, MutateFunc, Population, SelectFunc},
+    genetic::types::{
+        CrossoverFunc, DistanceFunc, EquivalenceFunc, FitnessSharing, GenerateFunc, MateFunc,
+        MutateFunc, Population, ReplaceFunc, SelectFunc,
+    },
     types::{FitnessFuncs, Purpose},
     helpers
 };
-use rand::{Rng, thread_rng};
+use rand::Rng;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
+use std::cmp::Ordering;
 
 
 pub struct GeneticAlgorithm<T> {
@@ -17,68 +22,339 @@ pub struct GeneticAlgorithm<T> {
     pub crossover_func: CrossoverFunc<T>,
     pub mutate_func: MutateFunc<T>,
     pub select_func: SelectFunc<T>,
+    pub mate_func: MateFunc<T>,
+    pub replace_func: ReplaceFunc<T>,
     pub generate_func: GenerateFunc<T>,
     pub purpose: Purpose,
+    pub duplicates_func: Option<EquivalenceFunc<T>>,
+    pub sharing: Option<FitnessSharing<T>>,
+    pub crowding_func: Option<DistanceFunc<T>>,
+    pub initial_values: Vec<Vec<T>>,
 }
 
-impl<T: std::fmt::Debug + Clone + Send + Sync> GeneticAlgorithm<T> {
-    pub fn run(&self) -> Result<Population<T>, &str> {
-        let mut rng = thread_rng();
-        let mut population: Population<T> = Vec::with_capacity(self.actors_count );
+impl<T: std::fmt::Debug + Clone + PartialEq + Send + Sync> GeneticAlgorithm<T> {
+    pub fn run(&self) -> Result<Population<T>, &'static str> {
+        self.run_with(Vec::new())
+    }
+
+    // Given values and then own initial values take the first places in the population, the rest is generated
+    pub fn run_with(&self, initial_values: Vec<Vec<T>>) -> Result<Population<T>, &'static str> {
+        self.run_observed(initial_values, &mut |_, _| true)
+    }
 
-        for _ in 0..self.actors_count {
-            let value = self.generate_func.0(&mut rng);
-            population.push(Individual::with_fitnesses(value, &self.fitness_funcs));
+    // The observer gets the iteration number and the best individual after every iteration,
+    // returning false stops the run
+    pub fn run_observed(
+        &self,
+        initial_values: Vec<Vec<T>>,
+        observer: &mut dyn FnMut(usize, &Individual<T>) -> bool,
+    ) -> Result<Population<T>, &'static str> {
+        let mut rng = helpers::make_rng();
+        if let Some(reset) = &self.select_func.1 {
+            reset();
         }
 
-        helpers::calculate_fitnesses(&mut population, &self.fitness_funcs);
+        let mut population: Population<T> = initial_values
+            .into_iter()
+            .chain(self.initial_values.iter().cloned())
+            .take(self.actors_count)
+            .map(|value| Individual::with_fitnesses(value, &self.fitness_funcs))
+            .collect();
+
+        while population.len() < self.actors_count {
+            population.push(self.generate_individual(&mut rng));
+        }
 
-        for _ in 0..self.iters_count {
-            // SELECTION
+        self.evaluate(&mut population);
+
+        for iter in 0..self.iters_count {
             if population.iter().all(|individual: &Individual<T>| {
                 individual.fitness.unwrap_or(0.) == 0.
             }) {
                 break
             };
-            population = self.select_func.0(population, &self.purpose, &mut rng);
-
-            // CROSSOVER
-            let new_population: Population<T> = population.iter().flat_map(|individual: &Individual<T>| {
-                // let panmixia = |ind: &Individual<T>| levenshtein(&format!("{:?}", &individual.value), &format!("{:?}", ind.value));
-                // let partner = population.iter().max_by_key(|ind| panmixia(*ind)).unwrap();
-                let partner = population.choose(&mut rng).unwrap();
 
-                let (child_1, child_2) = self.crossover_func.0(individual, partner, &mut rng);
+            population.iter_mut().for_each(|individual| individual.age += 1);
 
-                // MUTATION
-                let child_1_value = if rng.gen::<f32>() < self.p_mutation {
-                    self.mutate_func.0(child_1, &mut rng)
-                } else {
-                    child_1
-                };
-
-                let child_2_value = if rng.gen::<f32>() < self.p_mutation {
-                    self.mutate_func.0(child_2, &mut rng)
-                } else {
-                    child_2
-                };
-
-                vec![
-                    Individual::with_fitnesses(child_1_value, &self.fitness_funcs),
-                    Individual::with_fitnesses(child_2_value, &self.fitness_funcs)
-                ]
-            }).collect();
+            population = match &self.crowding_func {
+                Some(distance_func) => self.crowding(population, distance_func, &mut rng),
+                None => {
+                    let offspring = self.reproduce(&population, &mut rng);
+                    self.replace(population, offspring, &mut rng)
+                }
+            };
 
-            population.extend(new_population);
+            if let Some(duplicates_func) = &self.duplicates_func {
+                population = Self::remove_duplicates(population, &duplicates_func.0);
+                while population.len() < self.actors_count {
+                    population.push(self.generate_individual(&mut rng));
+                }
+            }
 
-            helpers::calculate_fitnesses(&mut population, &self.fitness_funcs);
+            self.evaluate(&mut population);
             population.sort_by(helpers::compare_by_fitness(&self.purpose));
             population.truncate(self.actors_count );
+
+            if population.first().is_some_and(|best| !observer(iter, best)) {
+                break;
+            }
         }
 
-        population.dedup_by(|a, b| a.fitness == b.fitness);
-        population.sort_unstable_by(helpers::compare_by_fitness(&self.purpose));
+        // Shared fitness is only a search device, solutions are reported with the raw one
+        helpers::calculate_fitnesses(&mut population, &self.fitness_funcs);
+        population.sort_by(helpers::compare_by_fitness(&self.purpose));
+        population = match &self.duplicates_func {
+            Some(duplicates_func) => Self::remove_duplicates(population, &duplicates_func.0),
+            None => Self::remove_duplicates(population, |a: &[T], b: &[T]| a == b),
+        };
         population.truncate(self.solutions_count );
         Ok(population)
     }
+
+    fn generate_individual(&self, rng: &mut StdRng) -> Individual<T> {
+        let value = self.generate_func.0(rng);
+        Individual::with_fitnesses(value, &self.fitness_funcs)
+    }
+
+    fn breed(&self, a: &Individual<T>, b: &Individual<T>, rng: &mut StdRng) -> (Vec<T>, Vec<T>) {
+        // CROSSOVER
+        let (child_1, child_2) = self.crossover_func.0(a, b, rng);
+
+        // MUTATION
+        let child_1_value = if rng.gen::<f32>() < self.p_mutation {
+            self.mutate_func.0(child_1, rng)
+        } else {
+            child_1
+        };
+
+        let child_2_value = if rng.gen::<f32>() < self.p_mutation {
+            self.mutate_func.0(child_2, rng)
+        } else {
+            child_2
+        };
+
+        (child_1_value, child_2_value)
+    }
+
+    fn reproduce(&self, population: &Population<T>, rng: &mut StdRng) -> Population<T> {
+        // SELECTION
+        let population = self.select_func.0(population.clone(), &self.purpose, rng);
+
+        population.iter().flat_map(|individual: &Individual<T>| {
+            let partner = match self.mate_func.0(individual, &population, &self.purpose, rng) {
+                Some(partner) => partner,
+                None => return vec![],
+            };
+            let (child_1, child_2) = self.breed(individual, partner, rng);
+
+            vec![
+                Individual::with_fitnesses(child_1, &self.fitness_funcs),
+                Individual::with_fitnesses(child_2, &self.fitness_funcs)
+            ]
+        }).collect()
+    }
+
+    fn replace(&self, mut population: Population<T>, offspring: Population<T>, rng: &mut StdRng) -> Population<T> {
+        // Parents and offspring are evaluated together, so their fitnesses are comparable
+        let parents_count = population.len();
+        population.extend(offspring);
+        self.evaluate(&mut population);
+        let offspring = population.split_off(parents_count);
+
+        self.replace_func.0(population, offspring, self.actors_count, &self.purpose, rng)
+    }
+
+    // Deterministic crowding: parents are paired at random, every child competes
+    // with the parent it is closest to and only the winner survives
+    fn crowding(&self, mut population: Population<T>, distance_func: &DistanceFunc<T>, rng: &mut StdRng) -> Population<T> {
+        population.shuffle(rng);
+
+        let mut contest: Population<T> = Vec::with_capacity(population.len() * 2);
+        let mut rest: Population<T> = Vec::new();
+        let mut parents = population.into_iter();
+
+        while let Some(parent_1) = parents.next() {
+            let parent_2 = match parents.next() {
+                Some(parent) => parent,
+                None => {
+                    rest.push(parent_1);
+                    break
+                }
+            };
+
+            let (value_1, value_2) = self.breed(&parent_1, &parent_2, rng);
+            let distance = &distance_func.0;
+            let straight = distance(&parent_1.value, &value_1) + distance(&parent_2.value, &value_2);
+            let crossed = distance(&parent_1.value, &value_2) + distance(&parent_2.value, &value_1);
+            let (value_1, value_2) = if straight <= crossed {
+                (value_1, value_2)
+            } else {
+                (value_2, value_1)
+            };
+
+            contest.push(parent_1);
+            contest.push(Individual::with_fitnesses(value_1, &self.fitness_funcs));
+            contest.push(parent_2);
+            contest.push(Individual::with_fitnesses(value_2, &self.fitness_funcs));
+        }
+
+        self.evaluate(&mut contest);
+        let compare = helpers::compare_by_fitness(&self.purpose);
+
+        let mut winners: Population<T> = contest.chunks(2).map(|pair| {
+            let (parent, child) = (&pair[0], &pair[1]);
+            if compare(child, parent) == Ordering::Less {
+                child.clone()
+            } else {
+                parent.clone()
+            }
+        }).collect();
+
+        winners.extend(rest);
+        winners
+    }
+
+    fn evaluate(&self, population: &mut Population<T>) {
+        helpers::calculate_fitnesses(population, &self.fitness_funcs);
+
+        if let Some(sharing) = &self.sharing {
+            Self::share_fitnesses(population, sharing, &self.purpose);
+        }
+    }
+
+    fn share_fitnesses(population: &mut Population<T>, sharing: &FitnessSharing<T>, purpose: &Purpose) {
+        let niche_counts: Vec<f32> = population.iter().map(|a| {
+            population.iter().map(|b| {
+                let distance = (sharing.distance_func.0)(&a.value, &b.value);
+                if distance < sharing.sigma {
+                    1. - (distance / sharing.sigma).powf(sharing.alpha)
+                } else {
+                    0.
+                }
+            }).sum::<f64>() as f32
+        }).collect();
+
+        for (individual, niche_count) in population.iter_mut().zip(niche_counts) {
+            individual.fitness = individual.fitness.map(|fitness| match purpose {
+                // Normalized fitness of the best individual is 0, shift it so crowded optima are penalized too
+                Purpose::Min => (fitness + 1.) * niche_count - 1.,
+                Purpose::Max => fitness / niche_count,
+            });
+        }
+    }
+
+    fn remove_duplicates(population: Population<T>, is_same: impl Fn(&[T], &[T]) -> bool) -> Population<T> {
+        let mut unique: Population<T> = Vec::with_capacity(population.len());
+
+        for individual in population {
+            if !unique.iter().any(|ind| is_same(&ind.value, &individual.value)) {
+                unique.push(individual);
+            }
+        }
+
+        unique
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::genetic::methods::{Crossover, Distance, Equivalence, Mate, Mutate, Replace, Select};
+    use crate::algorithms::types::FitnessFuncRaw;
+
+    fn individual(value: Vec<usize>, fitness: f32) -> Individual<usize> {
+        Individual { value, fitnesses: Vec::new(), fitness: Some(fitness), age: 0 }
+    }
+
+    // Sum of |value - index|, the identity is the only optimum
+    fn displacement() -> FitnessFuncs<usize> {
+        vec![Box::new(|value: &Vec<usize>| {
+            Some(value.iter().enumerate().map(|(idx, el)| idx.abs_diff(*el) as f64).sum())
+        }) as FitnessFuncRaw<usize>]
+    }
+
+    fn algorithm(sharing: Option<FitnessSharing<usize>>, crowding: bool) -> GeneticAlgorithm<usize> {
+        GeneticAlgorithm {
+            fitness_funcs: displacement(),
+            actors_count: 20,
+            iters_count: 30,
+            solutions_count: 5,
+            p_mutation: 0.3,
+            crossover_func: Crossover::ordered(),
+            mutate_func: Mutate::swap_indexes(None),
+            select_func: Select::tournament(3, None),
+            mate_func: Mate::random(),
+            replace_func: Replace::plus(),
+            generate_func: GenerateFunc(Box::new(|rng: &mut StdRng| {
+                let mut value: Vec<usize> = (0..6).collect();
+                value.shuffle(rng);
+                value
+            })),
+            purpose: Purpose::Min,
+            duplicates_func: Some(Equivalence::exact()),
+            sharing,
+            crowding_func: if crowding { Some(Distance::hamming()) } else { None },
+            initial_values: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn remove_duplicates_keeps_first_of_equivalent_tours() {
+        let population = vec![
+            individual(vec![0, 1, 2], 0.),
+            individual(vec![1, 2, 0], 1.),
+            individual(vec![0, 2, 1], 2.),
+        ];
+
+        let unique = GeneticAlgorithm::remove_duplicates(population, helpers::is_same_tour);
+
+        // All three are the same triangle
+        assert_eq!(unique.len(), 1);
+        assert_eq!(unique[0].fitness, Some(0.));
+    }
+
+    #[test]
+    fn sharing_penalizes_crowded_niches() {
+        let sharing = FitnessSharing { distance_func: Distance::hamming(), sigma: 0.5, alpha: 1. };
+        let mut population = vec![
+            individual(vec![0, 1, 2, 3], 0.5),
+            individual(vec![0, 1, 2, 3], 0.5),
+            individual(vec![3, 2, 1, 0], 0.5),
+        ];
+
+        GeneticAlgorithm::share_fitnesses(&mut population, &sharing, &Purpose::Min);
+
+        // Two equal individuals share the niche, the lone one keeps its fitness
+        assert_eq!(population[0].fitness, Some(2.));
+        assert_eq!(population[1].fitness, Some(2.));
+        assert_eq!(population[2].fitness, Some(0.5));
+    }
+
+    #[test]
+    fn run_returns_distinct_solutions() {
+        for (sharing, crowding) in [(None, false), (None, true)] {
+            let solutions = algorithm(sharing, crowding).run().unwrap();
+
+            assert!(!solutions.is_empty() && solutions.len() <= 5);
+            for (idx, solution) in solutions.iter().enumerate() {
+                assert!(solutions[..idx].iter().all(|other| other.value != solution.value));
+            }
+        }
+
+        let sharing = FitnessSharing { distance_func: Distance::hamming(), sigma: 0.5, alpha: 1. };
+        assert!(!algorithm(Some(sharing), false).run().unwrap().is_empty());
+    }
+
+    #[test]
+    fn every_run_resets_the_selection() {
+        let resets = std::rc::Rc::new(std::cell::Cell::new(0));
+        let counter = std::rc::Rc::clone(&resets);
+        let mut algorithm = algorithm(None, false);
+        algorithm.select_func.1 = Some(Box::new(move || counter.set(counter.get() + 1)));
+
+        algorithm.run().unwrap();
+        algorithm.run().unwrap();
+
+        assert_eq!(resets.get(), 2);
+    }
 }
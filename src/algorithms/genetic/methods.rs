@@ -1,23 +1,36 @@
-use super::types::{CrossoverFunc, MutateFunc, SelectFunc};
+use super::types::{
+    CrossoverFunc, DistanceFunc, EquivalenceFunc, MateFunc, MutateFunc, ReplaceFunc, SelectFunc,
+    SelectFuncRaw,
+};
 use crate::algorithms::genetic::types::Population;
 use crate::algorithms::helpers;
 use crate::algorithms::individual::Individual;
-use crate::algorithms::types::Purpose;
-use rand::prelude::ThreadRng;
+use crate::algorithms::operators::Operator;
+use crate::algorithms::types::{Purpose, Scaling};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
 use rand::{seq::IteratorRandom, Rng};
+use std::cell::Cell;
 use std::fmt::Debug;
+use std::hash::Hash;
+use std::rc::Rc;
 
 pub struct Crossover;
 pub struct Select;
 pub struct Mutate;
+pub struct Distance;
+pub struct Equivalence;
+pub struct Mate;
+pub struct Replace;
 
 impl Crossover {
     pub fn one_point<T: Clone>(point_idx: Option<usize>) -> CrossoverFunc<T> {
         CrossoverFunc(Box::new(
-            move |a: &Individual<T>, b: &Individual<T>, rng: &mut ThreadRng| {
+            move |a: &Individual<T>, b: &Individual<T>, rng: &mut StdRng| {
                 let idx = if let Some(val) = point_idx {
                     val
-                } else if a.value.len() < 1 {
+                } else if a.value.is_empty() {
                     0
                 } else {
                     rng.gen_range(0..a.value.len() - 1)
@@ -38,7 +51,7 @@ impl Crossover {
 
     pub fn two_points<T: Copy>(points_range: (Option<usize>, Option<usize>)) -> CrossoverFunc<T> {
         CrossoverFunc(Box::new(
-            move |a: &Individual<T>, b: &Individual<T>, rng: &mut ThreadRng| {
+            move |a: &Individual<T>, b: &Individual<T>, rng: &mut StdRng| {
                 let (point_left, point_right) =
                     helpers::process_two_points_or_generate(a.value.len(), points_range, rng);
                 let mut values_left: Vec<T> = Vec::with_capacity(a.value.len());
@@ -58,7 +71,7 @@ impl Crossover {
 
     pub fn ordered<T: Clone + PartialEq>() -> CrossoverFunc<T> {
         CrossoverFunc(Box::new(
-            move |a: &Individual<T>, b: &Individual<T>, rng: &mut ThreadRng| {
+            move |a: &Individual<T>, b: &Individual<T>, rng: &mut StdRng| {
                 let (point_left, point_right) =
                     helpers::process_two_points_or_generate(a.value.len(), (None, None), rng);
                 let value_length = a.value.len();
@@ -95,11 +108,11 @@ impl Crossover {
                 (
                     child_a_value
                         .into_iter()
-                        .filter_map(|i| Some(i).unwrap())
+                        .flatten()
                         .collect(),
                     child_b_value
                         .into_iter()
-                        .filter_map(|i| Some(i.unwrap()))
+                        .map(|i| i.unwrap())
                         .collect(),
                 )
             },
@@ -108,57 +121,163 @@ impl Crossover {
 }
 
 impl Mutate {
-    pub fn swap_indexes<T: Clone>(offset: Option<usize>) -> MutateFunc<T> {
-        MutateFunc(Box::new(move |mut value, rng: &mut ThreadRng| {
-            let (left, right) = helpers::generate_two_points(offset, value.len(), rng);
-            value.swap(left, right);
-            value
-        }))
+    pub fn swap_indexes<T: 'static>(offset: Option<usize>) -> MutateFunc<T> {
+        Operator::swap_indexes(offset).into()
     }
 
-    pub fn reverse_elements<T: Clone>(offset: Option<usize>) -> MutateFunc<T> {
-        MutateFunc(Box::new(move |mut value, rng: &mut ThreadRng| {
-            let (left, right) = helpers::generate_two_points(offset, value.len(), rng);
-            value[left..right].reverse();
-            value
-        }))
+    pub fn reverse_elements<T: 'static>(offset: Option<usize>) -> MutateFunc<T> {
+        Operator::reverse_elements(offset).into()
+    }
+
+    pub fn insertion<T: 'static>(offset: Option<usize>) -> MutateFunc<T> {
+        Operator::insertion(offset).into()
+    }
+
+    pub fn scramble<T: 'static>(offset: Option<usize>) -> MutateFunc<T> {
+        Operator::scramble(offset).into()
+    }
+
+    pub fn displacement<T: 'static>(offset: Option<usize>) -> MutateFunc<T> {
+        Operator::displacement(offset).into()
+    }
+
+    pub fn two_opt<T: 'static>(cost_func: impl Fn(&T, &T) -> f64 + 'static) -> MutateFunc<T> {
+        Operator::two_opt(cost_func).into()
     }
 }
 
 const RATE_DEFAULT: f32 = 0.7;
 impl Select {
     pub fn roulette<T: Clone + Debug>(rate: Option<f32>) -> SelectFunc<T> {
-        SelectFunc(Box::new(
-            move |population: Population<T>, purpose: &Purpose, rng: &mut ThreadRng| {
+        Self::roulette_scaled(Scaling::Windowing, rate)
+    }
+
+    pub fn roulette_scaled<T: Clone + Debug>(scaling: Scaling, rate: Option<f32>) -> SelectFunc<T> {
+        SelectFunc::new(Box::new(
+            move |population: Population<T>, purpose: &Purpose, rng: &mut StdRng| {
                 let count =
                     helpers::get_count_by_rate::<T>(population.len(), rate.unwrap_or(RATE_DEFAULT));
-                let fitness_sum: f32 = population.iter().filter_map(|ind| ind.fitness).sum();
-                let probabilities: Vec<f32> = population
+                let weights = helpers::scaled_weights(&population, purpose, &scaling);
+
+                helpers::weighted_random_sampling(&population, weights, count, rng).unwrap_or_default()
+            },
+        ))
+    }
+
+    pub fn sus<T: Clone>(scaling: Scaling, rate: Option<f32>) -> SelectFunc<T> {
+        SelectFunc::new(Box::new(
+            move |population: Population<T>, purpose: &Purpose, rng: &mut StdRng| {
+                let count =
+                    helpers::get_count_by_rate::<T>(population.len(), rate.unwrap_or(RATE_DEFAULT));
+                let weights = helpers::scaled_weights(&population, purpose, &scaling);
+
+                helpers::stochastic_universal_sampling(&population, &weights, count, rng).unwrap_or_default()
+            },
+        ))
+    }
+
+    // Selective pressure 1 <= pressure <= 2, the best individual gets `pressure` times the average share
+    pub fn linear_ranking<T: Clone + Debug>(pressure: f32, rate: Option<f32>) -> SelectFunc<T> {
+        Self::ranking(rate, move |rank: usize, len: usize| {
+            if len <= 1 {
+                return 1.;
+            }
+
+            (2. - pressure) / len as f32
+                + 2. * (len - 1 - rank) as f32 * (pressure - 1.) / (len * (len - 1)) as f32
+        })
+    }
+
+    // Weight of the individual decreases as base^rank, 0 < base < 1
+    pub fn exponential_ranking<T: Clone + Debug>(base: f32, rate: Option<f32>) -> SelectFunc<T> {
+        Self::ranking(rate, move |rank: usize, _: usize| base.powi(rank as i32))
+    }
+
+    // Temperature is lowered after every selection: t = max(t_min, t * cooling), every run starts hot again
+    pub fn boltzmann<T: Clone + Debug>(temperature: f32, cooling: f32, temperature_min: f32, rate: Option<f32>) -> SelectFunc<T> {
+        let current_temperature = Rc::new(Cell::new(temperature));
+        let reset_temperature = Rc::clone(&current_temperature);
+
+        let select: SelectFuncRaw<T> = Box::new(
+            move |population: Population<T>, purpose: &Purpose, rng: &mut StdRng| {
+                let count =
+                    helpers::get_count_by_rate::<T>(population.len(), rate.unwrap_or(RATE_DEFAULT));
+                let t = current_temperature.get();
+                current_temperature.set((t * cooling).max(temperature_min));
+
+                let best = population
                     .iter()
-                    .map(|ind| {
-                        if let Some(fitness) = ind.fitness {
-                            return if let Purpose::Min = purpose {
-                                1. - fitness / fitness_sum
-                            } else {
-                                fitness / fitness_sum
-                            };
-                        } else {
-                            0.
-                        }
+                    .filter_map(|ind| ind.fitness)
+                    .reduce(|a, b| match purpose {
+                        Purpose::Min => a.min(b),
+                        Purpose::Max => a.max(b),
+                    })
+                    .unwrap_or(0.);
+                let weights: Vec<f32> = population
+                    .iter()
+                    .map(|ind| match ind.fitness {
+                        // Shifted by the best fitness so the exponent never overflows
+                        Some(fitness) => match purpose {
+                            Purpose::Min => (-(fitness - best) / t).exp(),
+                            Purpose::Max => ((fitness - best) / t).exp(),
+                        },
+                        None => 0.,
                     })
                     .collect();
 
-                match helpers::weighted_random_sampling(&population, probabilities, count, rng) {
-                    Ok(population) => population,
-                    _ => Vec::new(),
+                helpers::weighted_random_sampling(&population, weights, count, rng).unwrap_or_default()
+            },
+        );
+
+        SelectFunc(select, Some(Box::new(move || reset_temperature.set(temperature))))
+    }
+
+    // The `elitism` best individuals are always selected, the rest are drawn
+    // uniformly from the best `threshold` part of the population
+    pub fn truncation<T: Clone>(threshold: f32, elitism: usize, rate: Option<f32>) -> SelectFunc<T> {
+        SelectFunc::new(Box::new(
+            move |mut population: Population<T>, purpose: &Purpose, rng: &mut StdRng| {
+                let count =
+                    helpers::get_count_by_rate::<T>(population.len(), rate.unwrap_or(RATE_DEFAULT));
+                population.sort_by(helpers::compare_by_fitness(purpose));
+
+                let elite_count = elitism.min(count).min(population.len());
+                let pool_count = helpers::get_count_by_rate::<T>(population.len(), threshold).max(1).min(population.len());
+                let mut population_new: Population<T> = population[..elite_count].to_vec();
+
+                if !population.is_empty() {
+                    population_new.extend(
+                        (elite_count..count).map(|_| population[..pool_count].choose(rng).unwrap().clone()),
+                    );
                 }
+
+                population_new
+            },
+        ))
+    }
+
+    fn ranking<T: Clone + Debug>(rate: Option<f32>, weight: impl Fn(usize, usize) -> f32 + 'static) -> SelectFunc<T> {
+        SelectFunc::new(Box::new(
+            move |mut population: Population<T>, purpose: &Purpose, rng: &mut StdRng| {
+                let count =
+                    helpers::get_count_by_rate::<T>(population.len(), rate.unwrap_or(RATE_DEFAULT));
+                population.sort_by(helpers::compare_by_fitness(purpose));
+
+                let len = population.len();
+                let weights: Vec<f32> = population
+                    .iter()
+                    .enumerate()
+                    .map(|(rank, ind)| if ind.fitness.is_some() { weight(rank, len) } else { 0. })
+                    .collect();
+
+                helpers::weighted_random_sampling(&population, weights, count, rng).unwrap_or_default()
             },
         ))
     }
 
     pub fn stochastic<T: Clone>(rate: Option<f32>) -> SelectFunc<T> {
-        SelectFunc(Box::new(
-            move |population: Population<T>, _: &Purpose, rng: &mut ThreadRng| {
+        SelectFunc::new(Box::new(
+            move |population: Population<T>, _: &Purpose, rng: &mut StdRng| {
                 let count =
                     helpers::get_count_by_rate::<T>(population.len(), rate.unwrap_or(RATE_DEFAULT));
                 population.into_iter().choose_multiple(rng, count)
@@ -167,8 +286,8 @@ impl Select {
     }
 
     pub fn tournament<T: Clone>(size: usize, rate: Option<f32>) -> SelectFunc<T> {
-        SelectFunc(Box::new(
-            move |population: Population<T>, purpose: &Purpose, rng: &mut ThreadRng| {
+        SelectFunc::new(Box::new(
+            move |population: Population<T>, purpose: &Purpose, rng: &mut StdRng| {
                 let count =
                     helpers::get_count_by_rate::<T>(population.len(), rate.unwrap_or(RATE_DEFAULT));
                 let mut population_new: Population<T> = Vec::with_capacity(population.len());
@@ -200,8 +319,8 @@ impl Select {
     }
 
     pub fn best_n<T: Clone>(rate: Option<f32>) -> SelectFunc<T> {
-        SelectFunc(Box::new(
-            move |mut population: Population<T>, purpose: &Purpose, _: &mut ThreadRng| {
+        SelectFunc::new(Box::new(
+            move |mut population: Population<T>, purpose: &Purpose, _: &mut StdRng| {
                 let count =
                     helpers::get_count_by_rate::<T>(population.len(), rate.unwrap_or(RATE_DEFAULT));
                 population.sort_by(helpers::compare_by_fitness(purpose));
@@ -211,3 +330,332 @@ impl Select {
         ))
     }
 }
+
+
+impl Distance {
+    pub fn shared_edges<T: Eq + Hash>() -> DistanceFunc<T> {
+        DistanceFunc(Box::new(|a: &[T], b: &[T]| helpers::shared_edges_distance(a, b)))
+    }
+
+    pub fn hamming<T: PartialEq>() -> DistanceFunc<T> {
+        DistanceFunc(Box::new(|a: &[T], b: &[T]| {
+            let len = a.len().max(b.len());
+            if len == 0 {
+                return 0.;
+            }
+
+            let equal = a.iter().zip(b.iter()).filter(|(x, y)| x == y).count();
+            (len - equal) as f64 / len as f64
+        }))
+    }
+}
+
+impl Equivalence {
+    pub fn exact<T: PartialEq>() -> EquivalenceFunc<T> {
+        EquivalenceFunc(Box::new(|a: &[T], b: &[T]| a == b))
+    }
+
+    pub fn tour<T: PartialEq>() -> EquivalenceFunc<T> {
+        EquivalenceFunc(Box::new(|a: &[T], b: &[T]| helpers::is_same_tour(a, b)))
+    }
+}
+
+
+impl Mate {
+    pub fn random<T>() -> MateFunc<T> {
+        MateFunc(Box::new(|_, population, _, rng| population.choose(rng)))
+    }
+
+    pub fn assortative<T: 'static>(distance_func: DistanceFunc<T>, candidates: usize) -> MateFunc<T> {
+        Self::by_distance(distance_func, candidates, true)
+    }
+
+    pub fn disassortative<T: 'static>(distance_func: DistanceFunc<T>, candidates: usize) -> MateFunc<T> {
+        Self::by_distance(distance_func, candidates, false)
+    }
+
+    // CHC-like incest prevention: a partner is accepted only if it is far enough from the individual,
+    // otherwise the individual does not mate in this generation
+    pub fn incest_prevention<T: 'static>(distance_func: DistanceFunc<T>, threshold: f64, attempts: usize) -> MateFunc<T> {
+        MateFunc(Box::new(move |individual, population, _, rng| {
+            population
+                .iter()
+                .filter(|ind| !std::ptr::eq(*ind, individual))
+                .choose_multiple(rng, attempts)
+                .into_iter()
+                .find(|ind| distance_func.0(&individual.value, &ind.value) >= threshold)
+        }))
+    }
+
+    pub fn fitness_proportional<T>() -> MateFunc<T> {
+        MateFunc(Box::new(|_, population, purpose, rng| {
+            let weights = helpers::windowed_weights(population, purpose);
+            let dist = WeightedIndex::new(weights).ok()?;
+            population.get(dist.sample(rng))
+        }))
+    }
+
+    fn by_distance<T: 'static>(distance_func: DistanceFunc<T>, candidates: usize, nearest: bool) -> MateFunc<T> {
+        MateFunc(Box::new(move |individual, population, _, rng| {
+            let distance = |ind: &Individual<T>| distance_func.0(&individual.value, &ind.value);
+            let candidates = population
+                .iter()
+                .filter(|ind| !std::ptr::eq(*ind, individual))
+                .choose_multiple(rng, candidates)
+                .into_iter();
+
+            if nearest {
+                candidates.min_by(|a, b| distance(a).total_cmp(&distance(b)))
+            } else {
+                candidates.max_by(|a, b| distance(a).total_cmp(&distance(b)))
+            }
+        }))
+    }
+}
+
+
+impl Replace {
+    pub fn plus<T>() -> ReplaceFunc<T> {
+        ReplaceFunc(Box::new(
+            |mut parents: Population<T>, offspring: Population<T>, count: usize, purpose: &Purpose, _: &mut StdRng| {
+                parents.extend(offspring);
+                parents.sort_by(helpers::compare_by_fitness(purpose));
+                parents.truncate(count);
+                parents
+            },
+        ))
+    }
+
+    pub fn comma<T>() -> ReplaceFunc<T> {
+        ReplaceFunc(Box::new(
+            |mut parents: Population<T>, mut offspring: Population<T>, count: usize, purpose: &Purpose, _: &mut StdRng| {
+                offspring.sort_by(helpers::compare_by_fitness(purpose));
+                offspring.truncate(count);
+
+                // Not enough offspring to fill the population, the best parents take the empty places
+                if offspring.len() < count {
+                    parents.sort_by(helpers::compare_by_fitness(purpose));
+                    parents.truncate(count - offspring.len());
+                    offspring.extend(parents);
+                }
+
+                offspring
+            },
+        ))
+    }
+
+    pub fn generational<T>(elitism: usize) -> ReplaceFunc<T> {
+        ReplaceFunc(Box::new(
+            move |mut parents: Population<T>, mut offspring: Population<T>, count: usize, purpose: &Purpose, rng: &mut StdRng| {
+                parents.sort_by(helpers::compare_by_fitness(purpose));
+                let elite_count = elitism.min(count);
+                let mut elite: Population<T> = parents.drain(..elite_count.min(parents.len())).collect();
+
+                offspring.shuffle(rng);
+                offspring.truncate(count - elite.len());
+                elite.extend(offspring);
+
+                if elite.len() < count {
+                    elite.extend(parents.into_iter().take(count - elite.len()));
+                }
+
+                elite
+            },
+        ))
+    }
+
+    pub fn replace_worst<T>(count: usize) -> ReplaceFunc<T> {
+        Self::steady_state(count, |parents: &mut Population<T>, purpose: &Purpose, _: &mut StdRng| {
+            parents.sort_by(helpers::compare_by_fitness(purpose));
+        })
+    }
+
+    pub fn replace_oldest<T>(count: usize) -> ReplaceFunc<T> {
+        Self::steady_state(count, |parents: &mut Population<T>, _: &Purpose, _: &mut StdRng| {
+            parents.sort_by_key(|ind| ind.age);
+        })
+    }
+
+    pub fn replace_random<T>(count: usize) -> ReplaceFunc<T> {
+        Self::steady_state(count, |parents: &mut Population<T>, _: &Purpose, rng: &mut StdRng| {
+            parents.shuffle(rng);
+        })
+    }
+
+    // Only the best `replaced` offspring enter the population, the parents ordered
+    // by `order` are removed from the tail
+    fn steady_state<T>(
+        replaced: usize,
+        order: impl Fn(&mut Population<T>, &Purpose, &mut StdRng) + 'static,
+    ) -> ReplaceFunc<T> {
+        ReplaceFunc(Box::new(
+            move |mut parents: Population<T>, mut offspring: Population<T>, count: usize, purpose: &Purpose, rng: &mut StdRng| {
+                offspring.sort_by(helpers::compare_by_fitness(purpose));
+                offspring.truncate(replaced.min(count));
+
+                order(&mut parents, purpose, rng);
+                parents.truncate(count.saturating_sub(offspring.len()));
+                parents.extend(offspring);
+                parents
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn individual(value: Vec<usize>, fitness: f32) -> Individual<usize> {
+        Individual { value, fitnesses: Vec::new(), fitness: Some(fitness), age: 0 }
+    }
+
+    fn rng() -> StdRng {
+        StdRng::seed_from_u64(1)
+    }
+
+    fn population() -> Population<usize> {
+        vec![
+            individual(vec![0, 1, 2, 3], 0.),
+            individual(vec![0, 1, 3, 2], 1.),
+            individual(vec![3, 2, 0, 1], 2.),
+        ]
+    }
+
+    #[test]
+    fn assortative_mate_is_the_nearest_candidate() {
+        let population = population();
+        let mate = Mate::assortative(Distance::hamming(), population.len());
+
+        let partner = mate.0(&population[0], &population, &Purpose::Min, &mut rng()).unwrap();
+
+        assert_eq!(partner.value, vec![0, 1, 3, 2]);
+    }
+
+    #[test]
+    fn disassortative_mate_is_the_farthest_candidate() {
+        let population = population();
+        let mate = Mate::disassortative(Distance::hamming(), population.len());
+
+        let partner = mate.0(&population[0], &population, &Purpose::Min, &mut rng()).unwrap();
+
+        assert_eq!(partner.value, vec![3, 2, 0, 1]);
+    }
+
+    #[test]
+    fn incest_prevention_refuses_close_partners() {
+        let population = population();
+        let mate = Mate::incest_prevention(Distance::hamming(), 0.9, population.len());
+
+        let partner = mate.0(&population[0], &population, &Purpose::Min, &mut rng()).unwrap();
+        assert_eq!(partner.value, vec![3, 2, 0, 1]);
+
+        let mate = Mate::incest_prevention(Distance::hamming(), 1.1, population.len());
+        assert!(mate.0(&population[0], &population, &Purpose::Min, &mut rng()).is_none());
+    }
+
+    #[test]
+    fn mate_is_never_the_individual_itself() {
+        let population = population();
+        let mate = Mate::assortative(Distance::hamming(), population.len());
+
+        for individual in population.iter() {
+            let partner = mate.0(individual, &population, &Purpose::Min, &mut rng()).unwrap();
+            assert!(!std::ptr::eq(partner, individual));
+        }
+    }
+
+    fn fitnesses(population: &Population<usize>) -> Vec<f32> {
+        population.iter().filter_map(|ind| ind.fitness).collect()
+    }
+
+    fn generation(fitnesses: &[f32]) -> Population<usize> {
+        fitnesses.iter().map(|fitness| individual(Vec::new(), *fitness)).collect()
+    }
+
+    #[test]
+    fn plus_keeps_the_best_of_parents_and_offspring() {
+        let replace = Replace::plus();
+        let population = replace.0(generation(&[1., 4.]), generation(&[3., 2.]), 2, &Purpose::Min, &mut rng());
+
+        assert_eq!(fitnesses(&population), vec![1., 2.]);
+    }
+
+    #[test]
+    fn comma_keeps_offspring_and_fills_with_best_parents() {
+        let replace = Replace::comma();
+        let population = replace.0(generation(&[1., 4.]), generation(&[3., 2.]), 2, &Purpose::Min, &mut rng());
+        assert_eq!(fitnesses(&population), vec![2., 3.]);
+
+        let population = replace.0(generation(&[4., 1.]), generation(&[3.]), 2, &Purpose::Min, &mut rng());
+        assert_eq!(fitnesses(&population), vec![3., 1.]);
+    }
+
+    #[test]
+    fn generational_keeps_the_elite() {
+        let replace = Replace::generational(1);
+        let population = replace.0(generation(&[5., 1.]), generation(&[7., 8., 9.]), 3, &Purpose::Min, &mut rng());
+
+        assert_eq!(population.len(), 3);
+        assert_eq!(population[0].fitness, Some(1.));
+        assert!(fitnesses(&population).iter().all(|fitness| *fitness != 5.));
+    }
+
+    #[test]
+    fn steady_state_replaces_worst_or_oldest_parents() {
+        let replace = Replace::replace_worst(1);
+        let population = replace.0(generation(&[3., 1., 2.]), generation(&[5., 0.]), 3, &Purpose::Min, &mut rng());
+        assert_eq!(fitnesses(&population), vec![1., 2., 0.]);
+
+        let mut parents = generation(&[3., 1., 2.]);
+        parents[0].age = 1;
+        parents[1].age = 5;
+        parents[2].age = 3;
+        let replace = Replace::replace_oldest(1);
+        let population = replace.0(parents, generation(&[0.]), 3, &Purpose::Min, &mut rng());
+        assert_eq!(fitnesses(&population), vec![3., 2., 0.]);
+    }
+
+    #[test]
+    fn linear_ranking_at_full_pressure_never_selects_the_worst() {
+        let population = generation(&[3., 1., 2., 0.]);
+        let select = Select::linear_ranking(2., Some(1.));
+        let mut rng = rng();
+
+        for _ in 0..20 {
+            let selected = select.0(population.clone(), &Purpose::Min, &mut rng);
+            assert_eq!(selected.len(), 4);
+            assert!(fitnesses(&selected).iter().all(|fitness| *fitness != 3.));
+        }
+    }
+
+    #[test]
+    fn truncation_keeps_the_elite_and_draws_from_the_best_part() {
+        let population = generation(&[5., 1., 4., 2., 3., 0.]);
+        let select = Select::truncation(0.5, 1, Some(1.));
+
+        let selected = select.0(population, &Purpose::Min, &mut rng());
+
+        assert_eq!(selected.len(), 6);
+        assert_eq!(selected[0].fitness, Some(0.));
+        assert!(fitnesses(&selected).iter().all(|fitness| *fitness <= 2.));
+    }
+
+    #[test]
+    fn boltzmann_starts_hot_after_reset() {
+        let mut fitness_values = vec![0.; 50];
+        fitness_values.extend(vec![1.; 50]);
+        let population = generation(&fitness_values);
+        let select = Select::boltzmann(1e6, 1e-12, 1e-6, Some(1.));
+        let worse = |selected: &Population<usize>| fitnesses(selected).iter().filter(|fitness| **fitness == 1.).count();
+        let mut rng = rng();
+
+        assert!(worse(&select.0(population.clone(), &Purpose::Min, &mut rng)) > 20);
+        // Cooled down to the minimum temperature, only the best are selected
+        assert_eq!(worse(&select.0(population.clone(), &Purpose::Min, &mut rng)), 0);
+
+        select.1.as_ref().unwrap()();
+        assert!(worse(&select.0(population, &Purpose::Min, &mut rng)) > 20);
+    }
+}
@@ -1,12 +1,39 @@
 use crate::algorithms::individual::Individual;
 use crate::algorithms::types::Purpose;
-use rand::rngs::ThreadRng;
+use rand::rngs::StdRng;
 
 pub type Population<T> = Vec<Individual<T>>;
 
-pub struct CrossoverFunc<T>(
-    pub Box<dyn Fn(&Individual<T>, &Individual<T>, &mut ThreadRng) -> (Vec<T>, Vec<T>)>,
-);
-pub struct GenerateFunc<T>(pub Box<dyn Fn(&mut ThreadRng) -> Vec<T>>);
-pub struct MutateFunc<T>(pub Box<dyn Fn(Vec<T>, &mut ThreadRng) -> Vec<T>>);
-pub struct SelectFunc<T>(pub Box<dyn Fn(Population<T>, &Purpose, &mut ThreadRng) -> Population<T>>);
+pub type CrossoverFuncRaw<T> = Box<dyn Fn(&Individual<T>, &Individual<T>, &mut StdRng) -> (Vec<T>, Vec<T>)>;
+pub type GenerateFuncRaw<T> = Box<dyn Fn(&mut StdRng) -> Vec<T>>;
+pub type MutateFuncRaw<T> = Box<dyn Fn(Vec<T>, &mut StdRng) -> Vec<T>>;
+pub type SelectFuncRaw<T> = Box<dyn Fn(Population<T>, &Purpose, &mut StdRng) -> Population<T>>;
+pub type ResetFuncRaw = Box<dyn Fn()>;
+pub type DistanceFuncRaw<T> = Box<dyn Fn(&[T], &[T]) -> f64>;
+pub type EquivalenceFuncRaw<T> = Box<dyn Fn(&[T], &[T]) -> bool>;
+pub type ReplaceFuncRaw<T> = Box<dyn Fn(Population<T>, Population<T>, usize, &Purpose, &mut StdRng) -> Population<T>>;
+pub type MateFuncRaw<T> = Box<
+    dyn for<'a> Fn(&Individual<T>, &'a [Individual<T>], &Purpose, &mut StdRng) -> Option<&'a Individual<T>>,
+>;
+
+pub struct CrossoverFunc<T>(pub CrossoverFuncRaw<T>);
+pub struct GenerateFunc<T>(pub GenerateFuncRaw<T>);
+pub struct MutateFunc<T>(pub MutateFuncRaw<T>);
+// The reset is called at the start of every run, so selections with own state start over
+pub struct SelectFunc<T>(pub SelectFuncRaw<T>, pub Option<ResetFuncRaw>);
+pub struct DistanceFunc<T>(pub DistanceFuncRaw<T>);
+pub struct EquivalenceFunc<T>(pub EquivalenceFuncRaw<T>);
+pub struct MateFunc<T>(pub MateFuncRaw<T>);
+pub struct ReplaceFunc<T>(pub ReplaceFuncRaw<T>);
+
+pub struct FitnessSharing<T> {
+    pub distance_func: DistanceFunc<T>,
+    pub sigma: f64,
+    pub alpha: f64,
+}
+
+impl<T> SelectFunc<T> {
+    pub fn new(select: SelectFuncRaw<T>) -> Self {
+        Self(select, None)
+    }
+}
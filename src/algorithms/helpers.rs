@@ -1,13 +1,35 @@
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use std::cell::Cell;
 use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::fmt::Debug;
+use std::hash::Hash;
 use rand::distributions::{WeightedIndex, Distribution};
-use rand::rngs::ThreadRng;
-use crate::algorithms::types::{FitnessFuncs, Population};
+use rand::rngs::StdRng;
+use crate::algorithms::types::{FitnessFuncs, FitnessRaw, Population};
 use crate::algorithms::individual::Individual;
-use crate::algorithms::types::Purpose;
+use crate::algorithms::types::{Purpose, Scaling};
 
-pub fn generate_two_points(offset_: Option<usize>, seq_length: usize, rng: &mut ThreadRng) -> (usize, usize) {
+thread_local! {
+    static SEED: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+// With a seed the generators made afterwards on this thread are reproducible, None brings back random seeds
+pub fn set_seed(seed: Option<u64>) {
+    SEED.with(|cell| cell.set(seed));
+}
+
+pub fn make_rng() -> StdRng {
+    SEED.with(|cell| match cell.get() {
+        Some(seed) => {
+            cell.set(Some(seed.wrapping_add(1)));
+            StdRng::seed_from_u64(seed)
+        }
+        None => StdRng::from_entropy(),
+    })
+}
+
+pub fn generate_two_points(offset_: Option<usize>, seq_length: usize, rng: &mut StdRng) -> (usize, usize) {
     if seq_length <= 1 {
         return (0, 0);
     }
@@ -37,7 +59,7 @@ pub fn generate_two_points(offset_: Option<usize>, seq_length: usize, rng: &mut
     }
 }
 
-pub fn process_two_points_or_generate(seq_length: usize, points: (Option<usize>, Option<usize>), rng: &mut ThreadRng) -> (usize, usize) {
+pub fn process_two_points_or_generate(seq_length: usize, points: (Option<usize>, Option<usize>), rng: &mut StdRng) -> (usize, usize) {
     let (point_left, point_right) = points;
     let middle = seq_length / 2;
     let left = match point_left {
@@ -58,10 +80,10 @@ pub fn process_two_points_or_generate(seq_length: usize, points: (Option<usize>,
 
 pub fn get_count_by_rate<T>(population_len: usize, rate: f32) -> usize {
     let count = (population_len as f32) * rate;
-    return count.round() as usize;
+    count.round() as usize
 }
 
-pub fn weighted_random_sampling<T: Clone + Debug>(items: &Vec<T>, weights: Vec<f32>, k: usize, rng: &mut ThreadRng) -> Result<Vec<T>, &'static str> {
+pub fn weighted_random_sampling<T: Clone + Debug>(items: &[T], weights: Vec<f32>, k: usize, rng: &mut StdRng) -> Result<Vec<T>, &'static str> {
     let dist = WeightedIndex::new(weights);
     if let Ok(dist) = dist {
         Ok(
@@ -102,14 +124,17 @@ fn fitnesses_min_diff<T>(population: &Population<T>, fitness_funcs: &FitnessFunc
 }
 
 pub fn calculate_fitnesses<T>(population: &mut Population<T>, fitness_funcs: &FitnessFuncs<T>) {
-    let (fitnesses_min, fitnesses_diff) = fitnesses_min_diff(&population, fitness_funcs);
+    let (fitnesses_min, fitnesses_diff) = fitnesses_min_diff(population, fitness_funcs);
 
     'outer: for ind in population.iter_mut() {
         let mut fitness = 0.;
 
         for (idx, fitness_raw) in ind.fitnesses.iter().enumerate() {
             if let Some(fitness_raw) = fitness_raw {
-                fitness += (fitness_raw - fitnesses_min[idx]) / fitnesses_diff[idx]
+                // All individuals are equal by this criterion, it can't tell them apart
+                if fitnesses_diff[idx] > 0. {
+                    fitness += (fitness_raw - fitnesses_min[idx]) / fitnesses_diff[idx]
+                }
             } else {
                 ind.fitness = None;
                 continue 'outer;
@@ -120,6 +145,113 @@ pub fn calculate_fitnesses<T>(population: &mut Population<T>, fitness_funcs: &Fi
     }
 }
 
+// Shift fitnesses so that the worst individual gets the zero weight whatever the purpose is
+pub fn windowed_weights<T>(population: &[Individual<T>], purpose: &Purpose) -> Vec<f32> {
+    let fitnesses = population.iter().filter_map(|ind| ind.fitness);
+    let (min, max) = fitnesses.fold((f32::MAX, f32::MIN), |(min, max), fitness| {
+        (min.min(fitness), max.max(fitness))
+    });
+
+    population.iter().map(|ind| match ind.fitness {
+        Some(fitness) => match purpose {
+            Purpose::Min => max - fitness + f32::EPSILON,
+            Purpose::Max => fitness - min + f32::EPSILON,
+        },
+        None => 0.,
+    }).collect()
+}
+
+// Sigma scaling: individuals further than `c` standard deviations below the mean get the zero weight
+pub fn sigma_weights<T>(population: &[Individual<T>], purpose: &Purpose, c: f32) -> Vec<f32> {
+    let fitnesses: Vec<f32> = population.iter().filter_map(|ind| ind.fitness).collect();
+    if fitnesses.is_empty() {
+        return vec![0.; population.len()];
+    }
+
+    let mean = fitnesses.iter().sum::<f32>() / fitnesses.len() as f32;
+    let std_dev = (fitnesses.iter().map(|fitness| (fitness - mean).powi(2)).sum::<f32>() / fitnesses.len() as f32).sqrt();
+
+    population.iter().map(|ind| match ind.fitness {
+        Some(_) if std_dev == 0. => 1.,
+        Some(fitness) => {
+            let deviation = match purpose {
+                Purpose::Min => mean - fitness,
+                Purpose::Max => fitness - mean,
+            };
+            (1. + deviation / (c * std_dev)).max(0.)
+        }
+        None => 0.,
+    }).collect()
+}
+
+pub fn scaled_weights<T>(population: &[Individual<T>], purpose: &Purpose, scaling: &Scaling) -> Vec<f32> {
+    match scaling {
+        Scaling::Windowing => windowed_weights(population, purpose),
+        Scaling::Sigma(c) => sigma_weights(population, purpose, *c),
+    }
+}
+
+pub fn stochastic_universal_sampling<T: Clone>(items: &[T], weights: &[f32], k: usize, rng: &mut StdRng) -> Result<Vec<T>, &'static str> {
+    let weights_sum: f32 = weights.iter().sum();
+    if items.is_empty() || k == 0 || weights_sum.is_nan() || weights_sum <= 0. {
+        return Err("List is empty");
+    }
+
+    let step = weights_sum / k as f32;
+    let start = rng.gen_range(0. ..step);
+    let mut selected: Vec<T> = Vec::with_capacity(k);
+    let mut cumulative = 0.;
+    let mut idx = 0;
+
+    for pointer in (0..k).map(|i| start + i as f32 * step) {
+        while idx < items.len() - 1 && cumulative + weights[idx] < pointer {
+            cumulative += weights[idx];
+            idx += 1;
+        }
+        selected.push(items[idx].clone());
+    }
+
+    Ok(selected)
+}
+
+// Single objective value of the individual for trajectory methods, lower is better whatever the purpose is
+pub fn energy<T>(individual: &Individual<T>, purpose: &Purpose) -> FitnessRaw {
+    let sum: FitnessRaw = individual.fitnesses.iter().copied().sum();
+
+    sum.map(|value| match purpose {
+        Purpose::Min => value,
+        Purpose::Max => -value,
+    })
+}
+
+pub fn best_by_energy<'a, T>(population: &'a [Individual<T>], purpose: &Purpose) -> Option<&'a Individual<T>> {
+    population
+        .iter()
+        .filter_map(|individual| energy(individual, purpose).map(|value| (individual, value)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(individual, _)| individual)
+}
+
+// Keeps `size` best distinct individuals sorted by energy
+pub fn update_archive<T: Clone + PartialEq>(archive: &mut Population<T>, individual: &Individual<T>, size: usize, purpose: &Purpose) {
+    let individual_energy = match energy(individual, purpose) {
+        Some(value) => value,
+        None => return,
+    };
+    if archive.iter().any(|ind| ind.value == individual.value) {
+        return;
+    }
+
+    let position = archive
+        .iter()
+        .position(|ind| energy(ind, purpose).is_none_or(|value| individual_energy < value))
+        .unwrap_or(archive.len());
+
+    if position < size {
+        archive.insert(position, individual.clone());
+        archive.truncate(size);
+    }
+}
 
 pub fn compare_by_fitness<T>(purpose: &Purpose) -> impl Fn(&Individual<T>, &Individual<T>) -> Ordering + '_ {
     let stub = match purpose {
@@ -127,7 +259,7 @@ pub fn compare_by_fitness<T>(purpose: &Purpose) -> impl Fn(&Individual<T>, &Indi
         Purpose::Max => Ordering::Less,
     };
 
-    return move |a: &Individual<T>, b: &Individual<T>| -> Ordering {
+    move |a: &Individual<T>, b: &Individual<T>| -> Ordering {
         let a_fitness = match a.fitness {
             Some(fit) => fit,
             None => return stub,
@@ -137,9 +269,111 @@ pub fn compare_by_fitness<T>(purpose: &Purpose) -> impl Fn(&Individual<T>, &Indi
             None => return stub
         };
 
-        return match purpose {
+        match purpose {
             Purpose::Min => a_fitness.total_cmp(&b_fitness),
             Purpose::Max => b_fitness.total_cmp(&a_fitness)
-        };
+        }
+    }
+}
+
+pub fn is_same_tour<T: PartialEq>(a: &[T], b: &[T]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    if a.is_empty() {
+        return true;
+    }
+
+    let len = a.len();
+    let offset = match a.iter().position(|el| *el == b[0]) {
+        Some(idx) => idx,
+        None => return false,
     };
-}
\ No newline at end of file
+
+    let forward = (0..len).all(|i| a[(offset + i) % len] == b[i]);
+    let backward = (0..len).all(|i| a[(offset + len - i) % len] == b[i]);
+
+    forward || backward
+}
+
+pub fn shared_edges_distance<T: Eq + Hash>(a: &[T], b: &[T]) -> f64 {
+    let len = a.len().max(b.len());
+    if len == 0 {
+        return 0.;
+    }
+
+    let edges: HashSet<(&T, &T)> = (0..a.len())
+        .flat_map(|i| {
+            let (from, to) = (&a[i], &a[(i + 1) % a.len()]);
+            [(from, to), (to, from)]
+        })
+        .collect();
+
+    let shared = (0..b.len())
+        .filter(|i| edges.contains(&(&b[*i], &b[(*i + 1) % b.len()])))
+        .count();
+
+    (len - shared) as f64 / len as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_tour_ignores_rotation_and_direction() {
+        assert!(is_same_tour(&[0, 1, 2, 3], &[2, 3, 0, 1]));
+        assert!(is_same_tour(&[0, 1, 2, 3], &[0, 3, 2, 1]));
+        assert!(!is_same_tour(&[0, 1, 2, 3], &[0, 2, 1, 3]));
+        assert!(!is_same_tour(&[0, 1, 2], &[0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn shared_edges_distance_counts_undirected_edges() {
+        assert_eq!(shared_edges_distance(&[0, 1, 2, 3], &[3, 2, 1, 0]), 0.);
+        // 0-1 and 2-3 are shared, 1-2 and 3-0 are not
+        assert_eq!(shared_edges_distance(&[0, 1, 2, 3], &[0, 1, 3, 2]), 0.5);
+        assert_eq!(shared_edges_distance::<usize>(&[], &[]), 0.);
+    }
+
+    fn generation(fitnesses: &[f32]) -> Population<usize> {
+        fitnesses
+            .iter()
+            .map(|fitness| Individual { value: Vec::new(), fitnesses: Vec::new(), fitness: Some(*fitness), age: 0 })
+            .collect()
+    }
+
+    #[test]
+    fn windowed_weights_give_the_worst_zero_weight() {
+        let population = generation(&[0., 1., 3.]);
+
+        let weights = windowed_weights(&population, &Purpose::Min);
+        assert!(weights[0] > weights[1] && weights[1] > weights[2]);
+        assert!(weights[2] <= f32::EPSILON);
+
+        let weights = windowed_weights(&population, &Purpose::Max);
+        assert!(weights[0] <= f32::EPSILON && weights[2] > weights[1]);
+    }
+
+    #[test]
+    fn sigma_weights_follow_the_standard_deviation() {
+        // Mean 2, standard deviation 1
+        let population = generation(&[1., 3., 1., 3.]);
+
+        let weights = sigma_weights(&population, &Purpose::Min, 1.);
+
+        assert_eq!(weights, vec![2., 0., 2., 0.]);
+        assert_eq!(sigma_weights(&generation(&[1., 1.]), &Purpose::Min, 2.), vec![1., 1.]);
+    }
+
+    #[test]
+    fn sus_selects_in_proportion_to_weights() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let selected = stochastic_universal_sampling(&[0, 1, 2], &[2., 1., 1.], 4, &mut rng).unwrap();
+
+        // Equally spaced pointers hit every item exactly in proportion
+        assert_eq!(selected, vec![0, 0, 1, 2]);
+        assert!(stochastic_universal_sampling(&[0], &[0.], 1, &mut rng).is_err());
+    }
+}
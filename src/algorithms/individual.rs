@@ -1,10 +1,12 @@
 use crate::algorithms::types::{Fitness, FitnessFuncs, FitnessRaw};
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Individual<T> {
     pub value: Vec<T>,
     pub fitnesses: Vec<FitnessRaw>,
     pub fitness: Fitness,
+    pub age: usize,
 }
 
 impl<T> Individual<T> {
@@ -15,6 +17,7 @@ impl<T> Individual<T> {
             value,
             fitnesses,
             fitness: None,
+            age: 0,
         }
     }
 }
\ No newline at end of file
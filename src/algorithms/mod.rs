@@ -2,7 +2,12 @@ pub mod ant_colony;
 pub mod bee_colony;
 pub mod types;
 pub mod genetic;
+pub mod particle_swarm;
+pub mod simulated_annealing;
+pub mod tabu_search;
 pub mod constants;
 pub mod helpers;
 pub mod individual;
+pub mod operators;
 pub mod solution;
+pub mod statistics;
@@ -0,0 +1,167 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use crate::algorithms::bee_colony::types::ResearchFunction;
+use crate::algorithms::genetic::types::MutateFunc;
+use crate::algorithms::helpers;
+
+// Permutation operators shared by the genetic algorithm (MutateFunc) and the bee colony (ResearchFunction)
+pub type OperatorFuncRaw<T> = Box<dyn Fn(&mut Vec<T>, &mut StdRng)>;
+pub struct Operator<T>(pub OperatorFuncRaw<T>);
+
+impl<T: 'static> Operator<T> {
+    pub fn swap_indexes(offset: Option<usize>) -> Self {
+        Operator(Box::new(move |value: &mut Vec<T>, rng: &mut StdRng| {
+            let (left, right) = helpers::generate_two_points(offset, value.len(), rng);
+            value.swap(left, right);
+        }))
+    }
+
+    pub fn reverse_elements(offset: Option<usize>) -> Self {
+        Operator(Box::new(move |value: &mut Vec<T>, rng: &mut StdRng| {
+            let (left, right) = helpers::generate_two_points(offset, value.len(), rng);
+            value[left..right].reverse();
+        }))
+    }
+
+    pub fn insertion(offset: Option<usize>) -> Self {
+        Operator(Box::new(move |value: &mut Vec<T>, rng: &mut StdRng| {
+            let (left, right) = helpers::generate_two_points(offset, value.len(), rng);
+            if left == right {
+                return;
+            }
+
+            let (from, to) = if rng.gen_bool(0.5) { (left, right) } else { (right, left) };
+            let element = value.remove(from);
+            value.insert(to, element);
+        }))
+    }
+
+    pub fn scramble(offset: Option<usize>) -> Self {
+        Operator(Box::new(move |value: &mut Vec<T>, rng: &mut StdRng| {
+            let (left, right) = helpers::generate_two_points(offset, value.len(), rng);
+            if left < right {
+                value[left..=right].shuffle(rng);
+            }
+        }))
+    }
+
+    // Cuts out the segment and puts it back at a random position
+    pub fn displacement(offset: Option<usize>) -> Self {
+        Operator(Box::new(move |value: &mut Vec<T>, rng: &mut StdRng| {
+            let (left, right) = helpers::generate_two_points(offset, value.len(), rng);
+            if value.is_empty() || right - left + 1 == value.len() {
+                return;
+            }
+
+            let segment: Vec<T> = value.drain(left..=right).collect();
+            let position = rng.gen_range(0..=value.len());
+            value.splice(position..position, segment);
+        }))
+    }
+
+    // Applies the best improving 2-opt move of the closed tour, the cost is expected to be symmetric
+    pub fn two_opt(cost_func: impl Fn(&T, &T) -> f64 + 'static) -> Self {
+        Operator(Box::new(move |value: &mut Vec<T>, _: &mut StdRng| {
+            if let Some((i, j)) = best_two_opt_move(value, &cost_func) {
+                value[i + 1..=j].reverse();
+            }
+        }))
+    }
+}
+
+pub fn two_opt_gain<T>(value: &[T], i: usize, j: usize, cost_func: &impl Fn(&T, &T) -> f64) -> f64 {
+    let len = value.len();
+    let (a, b) = (&value[i], &value[i + 1]);
+    let (c, d) = (&value[j], &value[(j + 1) % len]);
+
+    cost_func(a, b) + cost_func(c, d) - cost_func(a, c) - cost_func(b, d)
+}
+
+pub fn best_two_opt_move<T>(value: &[T], cost_func: &impl Fn(&T, &T) -> f64) -> Option<(usize, usize)> {
+    let len = value.len();
+    let mut best: Option<(usize, usize)> = None;
+    let mut best_gain = 0.;
+
+    for i in 0..len.saturating_sub(2) {
+        for j in i + 2..len {
+            if (j + 1) % len == i {
+                continue;
+            }
+
+            let gain = two_opt_gain(value, i, j, cost_func);
+            if gain > best_gain {
+                best_gain = gain;
+                best = Some((i, j));
+            }
+        }
+    }
+
+    best
+}
+
+impl<T: 'static> From<Operator<T>> for MutateFunc<T> {
+    fn from(operator: Operator<T>) -> Self {
+        MutateFunc(Box::new(move |mut value: Vec<T>, rng: &mut StdRng| {
+            (operator.0)(&mut value, rng);
+            value
+        }))
+    }
+}
+
+impl<T: Clone + 'static> From<Operator<T>> for ResearchFunction<T> {
+    fn from(operator: Operator<T>) -> Self {
+        ResearchFunction(Box::new(move |value: &Vec<T>, rng: &mut StdRng| {
+            let mut source = value.to_vec();
+            (operator.0)(&mut source, rng);
+            source
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn is_permutation(value: &[usize]) -> bool {
+        let mut sorted = value.to_vec();
+        sorted.sort();
+        sorted == (0..value.len()).collect::<Vec<usize>>()
+    }
+
+    #[test]
+    fn operators_keep_permutations() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let operators: Vec<Operator<usize>> = vec![
+            Operator::swap_indexes(None),
+            Operator::reverse_elements(None),
+            Operator::insertion(None),
+            Operator::scramble(None),
+            Operator::displacement(None),
+        ];
+
+        for operator in operators.iter() {
+            for len in [1, 2, 3, 5, 10] {
+                let mut value: Vec<usize> = (0..len).collect();
+                for _ in 0..50 {
+                    (operator.0)(&mut value, &mut rng);
+                    assert!(is_permutation(&value));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn two_opt_untangles_crossed_edges() {
+        // Cities on a line, the tour 0 2 1 3 crosses itself
+        let cost = |a: &usize, b: &usize| a.abs_diff(*b) as f64;
+        let mut value = vec![0, 2, 1, 3];
+
+        assert_eq!(two_opt_gain(&value, 0, 2, &cost), 2.);
+        (Operator::two_opt(cost).0)(&mut value, &mut StdRng::seed_from_u64(1));
+
+        assert_eq!(value, vec![0, 1, 2, 3]);
+        assert_eq!(best_two_opt_move(&value, &cost), None);
+    }
+}
@@ -0,0 +1,90 @@
+use std::cmp::Ordering;
+use crate::algorithms::genetic::types::GenerateFunc;
+use crate::algorithms::helpers;
+use crate::algorithms::individual::Individual;
+use crate::algorithms::particle_swarm::particle::Particle;
+use crate::algorithms::types::{FitnessFuncs, Population, Purpose};
+
+pub struct ParticleSwarmAlgorithm<T> {
+    pub fitness_funcs: FitnessFuncs<T>,
+    pub actors_count: usize,
+    pub iters_count: usize,
+    pub solutions_count: usize,
+    pub inertia: f64,
+    pub cognitive: f64,
+    pub social: f64,
+    pub generate_func: GenerateFunc<T>,
+    pub purpose: Purpose,
+}
+
+impl<T: Clone + PartialEq> ParticleSwarmAlgorithm<T> {
+    pub fn run(&self) -> Result<Population<T>, &'static str> {
+        self.run_observed(&mut |_, _| true)
+    }
+
+    // The observer gets the iteration number and the global best before the swarm moves
+    pub fn run_observed(&self, observer: &mut dyn FnMut(usize, &Individual<T>) -> bool) -> Result<Population<T>, &'static str> {
+        let mut rng = helpers::make_rng();
+        let mut swarm: Vec<Particle<T>> = (0..self.actors_count)
+            .map(|_| Particle::new(Individual::with_fitnesses(self.generate_func.0(&mut rng), &self.fitness_funcs)))
+            .collect();
+
+        for iter in 0..self.iters_count {
+            self.update_bests(&mut swarm);
+
+            let global_best = match self.global_best(&swarm) {
+                Some(best) => {
+                    if !observer(iter, best) {
+                        break;
+                    }
+                    best.value.clone()
+                }
+                None => return Err("Swarm is empty"),
+            };
+
+            for particle in swarm.iter_mut() {
+                particle.fly(&global_best, self.inertia, self.cognitive, self.social, &self.fitness_funcs, &mut rng);
+            }
+        }
+
+        self.update_bests(&mut swarm);
+
+        let mut population: Population<T> = Vec::with_capacity(swarm.len());
+        for particle in swarm {
+            if !population.iter().any(|ind| ind.value == particle.best.value) {
+                population.push(particle.best);
+            }
+        }
+
+        helpers::calculate_fitnesses(&mut population, &self.fitness_funcs);
+        population.sort_by(helpers::compare_by_fitness(&self.purpose));
+        population.truncate(self.solutions_count);
+        Ok(population)
+    }
+
+    // Positions and personal bests are normalized together, so their fitnesses are comparable
+    fn update_bests(&self, swarm: &mut [Particle<T>]) {
+        let mut population: Population<T> = swarm.iter().map(|particle| particle.position.clone()).collect();
+        population.extend(swarm.iter().map(|particle| particle.best.clone()));
+        helpers::calculate_fitnesses(&mut population, &self.fitness_funcs);
+
+        let (positions, bests) = population.split_at(swarm.len());
+        let compare = helpers::compare_by_fitness(&self.purpose);
+
+        for ((particle, position), best) in swarm.iter_mut().zip(positions).zip(bests) {
+            particle.position.fitness = position.fitness;
+            particle.best.fitness = best.fitness;
+
+            if compare(&particle.position, &particle.best) == Ordering::Less {
+                particle.best = particle.position.clone();
+            }
+        }
+    }
+
+    fn global_best<'a>(&self, swarm: &'a [Particle<T>]) -> Option<&'a Individual<T>> {
+        swarm
+            .iter()
+            .map(|particle| &particle.best)
+            .min_by(|a, b| helpers::compare_by_fitness(&self.purpose)(a, b))
+    }
+}
@@ -0,0 +1,3 @@
+pub mod algorithm;
+pub mod particle;
+pub mod types;
@@ -0,0 +1,101 @@
+use rand::rngs::StdRng;
+use rand::Rng;
+use crate::algorithms::individual::Individual;
+use crate::algorithms::types::FitnessFuncs;
+use super::types::Velocity;
+
+#[derive(Clone, Debug)]
+pub struct Particle<T> {
+    pub position: Individual<T>,
+    pub best: Individual<T>,
+    pub velocity: Velocity,
+}
+
+impl<T: Clone + PartialEq> Particle<T> {
+    pub fn new(position: Individual<T>) -> Self {
+        Self {
+            best: position.clone(),
+            position,
+            velocity: Vec::new(),
+        }
+    }
+
+    // v = w * v + c1 * r1 * (best - x) + c2 * r2 * (global_best - x), x = x + v
+    pub fn fly(&mut self, global_best: &[T], inertia: f64, cognitive: f64, social: f64, fitness_funcs: &FitnessFuncs<T>, rng: &mut StdRng) {
+        let mut velocity = scale(&self.velocity, inertia, rng);
+
+        let cognitive_rate = cognitive * rng.gen::<f64>();
+        velocity.extend(scale(&swap_sequence(&self.position.value, &self.best.value), cognitive_rate, rng));
+
+        let social_rate = social * rng.gen::<f64>();
+        velocity.extend(scale(&swap_sequence(&self.position.value, global_best), social_rate, rng));
+
+        let mut value = self.position.value.clone();
+        for (i, j) in velocity.iter() {
+            value.swap(*i, *j);
+        }
+
+        self.velocity = velocity;
+        self.position = Individual::with_fitnesses(value, fitness_funcs);
+    }
+}
+
+// Swaps that transform `from` into `to`
+pub fn swap_sequence<T: Clone + PartialEq>(from: &[T], to: &[T]) -> Velocity {
+    let mut value = from.to_vec();
+    let mut sequence: Velocity = Vec::new();
+
+    for i in 0..value.len().min(to.len()) {
+        if value[i] == to[i] {
+            continue;
+        }
+
+        if let Some(j) = value.iter().skip(i + 1).position(|el| *el == to[i]) {
+            value.swap(i, i + 1 + j);
+            sequence.push((i, i + 1 + j));
+        }
+    }
+
+    sequence
+}
+
+// Every swap is kept with `rate` probability
+fn scale(velocity: &Velocity, rate: f64, rng: &mut StdRng) -> Velocity {
+    if rate >= 1. {
+        return velocity.clone();
+    }
+
+    velocity.iter().filter(|_| rng.gen::<f64>() < rate).copied().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn swap_sequence_transforms_one_permutation_into_another() {
+        let from = vec![3, 0, 2, 1, 4];
+        let to = vec![0, 1, 2, 3, 4];
+
+        let mut value = from.clone();
+        for (i, j) in swap_sequence(&from, &to) {
+            value.swap(i, j);
+        }
+
+        assert_eq!(value, to);
+        assert!(swap_sequence(&to, &to).is_empty());
+    }
+
+    #[test]
+    fn full_social_rate_moves_to_the_global_best() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut particle = Particle::new(Individual::with_fitnesses(vec![2, 1, 0], &Vec::new()));
+
+        // Without inertia and the cognitive part the particle copies the global best
+        particle.fly(&[0, 1, 2], 0., 0., 1e9, &Vec::new(), &mut rng);
+
+        assert_eq!(particle.position.value, vec![0, 1, 2]);
+        assert_eq!(particle.best.value, vec![2, 1, 0]);
+    }
+}
@@ -0,0 +1,2 @@
+// Sequence of index swaps that moves a particle through the permutation space
+pub type Velocity = Vec<(usize, usize)>;
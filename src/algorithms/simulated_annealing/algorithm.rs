@@ -0,0 +1,199 @@
+use rand::rngs::StdRng;
+use rand::Rng;
+use crate::algorithms::genetic::types::{GenerateFunc, MutateFunc};
+use crate::algorithms::helpers;
+use crate::algorithms::individual::Individual;
+use crate::algorithms::simulated_annealing::types::Cooling;
+use crate::algorithms::types::{FitnessFuncs, FitnessRaw, Population, Purpose};
+
+pub struct SimulatedAnnealingAlgorithm<T> {
+    pub fitness_funcs: FitnessFuncs<T>,
+    pub iters_count: usize,
+    pub moves_count: usize,
+    pub solutions_count: usize,
+    pub temperature: Option<f64>,
+    pub temperature_min: f64,
+    pub initial_acceptance: f64,
+    pub cooling: Cooling,
+    pub neighbour_func: MutateFunc<T>,
+    pub generate_func: GenerateFunc<T>,
+    pub purpose: Purpose,
+}
+
+impl<T: Clone + PartialEq> SimulatedAnnealingAlgorithm<T> {
+    pub fn run(&self) -> Result<Population<T>, &'static str> {
+        self.run_observed(&mut |_, _| true)
+    }
+
+    // The observer gets the iteration number and the best individual after every temperature level
+    pub fn run_observed(&self, observer: &mut dyn FnMut(usize, &Individual<T>) -> bool) -> Result<Population<T>, &'static str> {
+        let mut rng = helpers::make_rng();
+        let mut archive: Population<T> = Vec::with_capacity(self.solutions_count);
+
+        let mut current = Individual::with_fitnesses(self.generate_func.0(&mut rng), &self.fitness_funcs);
+        let mut current_energy = helpers::energy(&current, &self.purpose);
+        let mut best_energy = current_energy;
+        helpers::update_archive(&mut archive, &current, self.solutions_count, &self.purpose);
+
+        let temperature_start = match self.temperature {
+            Some(temperature) => temperature,
+            None => self.estimate_temperature(&current, current_energy, &mut rng),
+        };
+        let mut temperature = temperature_start;
+        let mut stagnation = 0;
+
+        for iter in 0..self.iters_count {
+            let mut improved = false;
+
+            for _ in 0..self.moves_count {
+                let candidate = self.neighbour(&current, &mut rng);
+                let candidate_energy = match helpers::energy(&candidate, &self.purpose) {
+                    Some(value) => value,
+                    None => continue,
+                };
+
+                let accepted = match current_energy {
+                    Some(energy) => {
+                        let delta = candidate_energy - energy;
+                        delta <= 0. || rng.gen::<f64>() < (-delta / temperature).exp()
+                    }
+                    None => true,
+                };
+
+                if accepted {
+                    if best_energy.is_none_or(|best| candidate_energy < best) {
+                        best_energy = Some(candidate_energy);
+                        improved = true;
+                    }
+
+                    helpers::update_archive(&mut archive, &candidate, self.solutions_count, &self.purpose);
+                    current = candidate;
+                    current_energy = Some(candidate_energy);
+                }
+            }
+
+            stagnation = if improved { 0 } else { stagnation + 1 };
+            temperature = self.cool(temperature, temperature_start, &mut stagnation);
+
+            if archive.first().is_some_and(|best| !observer(iter, best)) {
+                break;
+            }
+        }
+
+        helpers::calculate_fitnesses(&mut archive, &self.fitness_funcs);
+        Ok(archive)
+    }
+
+    fn neighbour(&self, individual: &Individual<T>, rng: &mut StdRng) -> Individual<T> {
+        let value = self.neighbour_func.0(individual.value.clone(), rng);
+        Individual::with_fitnesses(value, &self.fitness_funcs)
+    }
+
+    // Temperature at which a worsening move of an average size is accepted with `initial_acceptance` probability
+    fn estimate_temperature(&self, initial: &Individual<T>, initial_energy: FitnessRaw, rng: &mut StdRng) -> f64 {
+        let initial_energy = match initial_energy {
+            Some(value) => value,
+            None => return 1.,
+        };
+
+        let deltas: Vec<f64> = (0..self.moves_count)
+            .filter_map(|_| helpers::energy(&self.neighbour(initial, rng), &self.purpose))
+            .map(|energy| energy - initial_energy)
+            .filter(|delta| *delta > 0.)
+            .collect();
+
+        if deltas.is_empty() {
+            return 1.;
+        }
+
+        let delta_mean = deltas.iter().sum::<f64>() / deltas.len() as f64;
+        -delta_mean / self.initial_acceptance.ln()
+    }
+
+    fn cool(&self, temperature: f64, temperature_start: f64, stagnation: &mut usize) -> f64 {
+        let temperature = match self.cooling {
+            Cooling::Geometric(alpha) => temperature * alpha,
+            Cooling::Linear(step) => temperature - step,
+            Cooling::LundyMees(beta) => temperature / (1. + beta * temperature),
+            Cooling::Reheating { alpha, patience, factor } => {
+                if *stagnation >= patience {
+                    *stagnation = 0;
+                    (temperature * factor).min(temperature_start)
+                } else {
+                    temperature * alpha
+                }
+            }
+        };
+
+        temperature.max(self.temperature_min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::types::FitnessFuncRaw;
+    use rand::SeedableRng;
+
+    fn algorithm(cooling: Cooling) -> SimulatedAnnealingAlgorithm<i64> {
+        SimulatedAnnealingAlgorithm {
+            fitness_funcs: vec![Box::new(|value: &Vec<i64>| Some(value[0].abs() as f64)) as FitnessFuncRaw<i64>],
+            iters_count: 100,
+            moves_count: 20,
+            solutions_count: 3,
+            temperature: None,
+            temperature_min: 0.5,
+            initial_acceptance: 0.5,
+            cooling,
+            neighbour_func: MutateFunc(Box::new(|value: Vec<i64>, rng: &mut StdRng| {
+                vec![value[0] + if rng.gen_bool(0.5) { 1 } else { -1 }]
+            })),
+            generate_func: GenerateFunc(Box::new(|_: &mut StdRng| vec![20])),
+            purpose: Purpose::Min,
+        }
+    }
+
+    #[test]
+    fn cooling_schedules() {
+        let mut stagnation = 0;
+
+        assert_eq!(algorithm(Cooling::Geometric(0.5)).cool(10., 10., &mut stagnation), 5.);
+        assert_eq!(algorithm(Cooling::Linear(3.)).cool(10., 10., &mut stagnation), 7.);
+        assert_eq!(algorithm(Cooling::LundyMees(0.1)).cool(10., 10., &mut stagnation), 5.);
+        // Never below the minimum temperature
+        assert_eq!(algorithm(Cooling::Linear(30.)).cool(10., 10., &mut stagnation), 0.5);
+    }
+
+    #[test]
+    fn reheating_after_stagnation() {
+        let algorithm = algorithm(Cooling::Reheating { alpha: 0.5, patience: 2, factor: 4. });
+        let mut stagnation = 1;
+        assert_eq!(algorithm.cool(4., 10., &mut stagnation), 2.);
+
+        stagnation = 2;
+        assert_eq!(algorithm.cool(4., 10., &mut stagnation), 10.);
+        assert_eq!(stagnation, 0);
+    }
+
+    #[test]
+    fn estimated_temperature_accepts_average_worsening() {
+        let algorithm = algorithm(Cooling::Geometric(0.9));
+        let initial = Individual::with_fitnesses(vec![20], &algorithm.fitness_funcs);
+        let energy = helpers::energy(&initial, &algorithm.purpose);
+
+        // Every worsening move costs 1, so exp(-1 / t) = 0.5
+        let temperature = algorithm.estimate_temperature(&initial, energy, &mut StdRng::seed_from_u64(1));
+
+        assert!((temperature - 1. / 2f64.ln()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn run_finds_the_minimum() {
+        helpers::set_seed(Some(1));
+        let archive = algorithm(Cooling::Geometric(0.9)).run().unwrap();
+        helpers::set_seed(None);
+
+        assert_eq!(archive[0].value, vec![0]);
+        assert!(archive.len() <= 3);
+    }
+}
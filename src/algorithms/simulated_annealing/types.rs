@@ -0,0 +1,23 @@
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum Cooling {
+    // t = t * alpha
+    Geometric(f64),
+    // t = t - step
+    Linear(f64),
+    // t = t / (1 + beta * t)
+    LundyMees(f64),
+    // Geometric cooling, the temperature is multiplied by `factor` after `patience` levels without improvement
+    Reheating { alpha: f64, patience: usize, factor: f64 },
+}
+
+impl Cooling {
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            Cooling::Geometric(alpha) => alpha > 0. && alpha < 1.,
+            Cooling::Linear(step) => step > 0.,
+            Cooling::LundyMees(beta) => beta > 0.,
+            Cooling::Reheating { alpha, factor, .. } => alpha > 0. && alpha < 1. && factor > 1.,
+        }
+    }
+}
@@ -0,0 +1,346 @@
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Statistics {
+    pub best: f64,
+    pub mean: f64,
+    pub median: f64,
+    pub std_dev: f64,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TestResult {
+    pub statistic: f64,
+    // Two-sided
+    pub p_value: f64,
+}
+
+// Best is the minimum, as distances and times are minimized
+pub fn describe(values: &[f64]) -> Option<Statistics> {
+    if values.is_empty() {
+        return None;
+    }
+
+    Some(Statistics {
+        best: values.iter().cloned().fold(f64::INFINITY, f64::min),
+        mean: mean(values),
+        median: median(values),
+        std_dev: std_dev(values),
+    })
+}
+
+pub fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+pub fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+
+    let middle = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[middle - 1] + sorted[middle]) / 2.
+    } else {
+        sorted[middle]
+    }
+}
+
+// Sample standard deviation
+pub fn std_dev(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.;
+    }
+
+    let mean = mean(values);
+    let sum: f64 = values.iter().map(|value| (value - mean).powi(2)).sum();
+    (sum / (values.len() - 1) as f64).sqrt()
+}
+
+// Wilcoxon signed-rank test for paired samples. Normal approximation with tie and continuity corrections,
+// so it is reliable from about 10 non-zero differences
+pub fn wilcoxon_signed_rank(first: &[f64], second: &[f64]) -> Option<TestResult> {
+    if first.len() != second.len() {
+        return None;
+    }
+
+    let differences: Vec<f64> = first.iter().zip(second).map(|(a, b)| a - b).filter(|d| *d != 0.).collect();
+    let n = differences.len() as f64;
+    if differences.is_empty() {
+        return Some(TestResult { statistic: 0., p_value: 1. });
+    }
+
+    let (ranks, ties) = rank(&differences.iter().map(|d| d.abs()).collect::<Vec<f64>>());
+    let positive: f64 = ranks.iter().zip(&differences).filter(|(_, d)| **d > 0.).map(|(rank, _)| rank).sum();
+    let negative = n * (n + 1.) / 2. - positive;
+
+    let expected = n * (n + 1.) / 4.;
+    let variance = n * (n + 1.) * (2. * n + 1.) / 24. - ties / 48.;
+
+    Some(TestResult {
+        statistic: positive.min(negative),
+        p_value: two_sided_p_value(positive - expected, variance),
+    })
+}
+
+// Mann-Whitney U test for independent samples, normal approximation with tie and continuity corrections
+pub fn mann_whitney_u(first: &[f64], second: &[f64]) -> Option<TestResult> {
+    if first.is_empty() || second.is_empty() {
+        return None;
+    }
+
+    let (n1, n2) = (first.len() as f64, second.len() as f64);
+    let n = n1 + n2;
+    let combined: Vec<f64> = first.iter().chain(second).cloned().collect();
+    let (ranks, ties) = rank(&combined);
+
+    let first_ranks: f64 = ranks[..first.len()].iter().sum();
+    let u1 = first_ranks - n1 * (n1 + 1.) / 2.;
+    let u2 = n1 * n2 - u1;
+
+    let expected = n1 * n2 / 2.;
+    let variance = n1 * n2 / 12. * ((n + 1.) - ties / (n * (n - 1.)));
+
+    Some(TestResult {
+        statistic: u1.min(u2),
+        p_value: two_sided_p_value(u1 - expected, variance),
+    })
+}
+
+// Average ranks starting from 1 and the sum of t^3 - t over groups of t tied values
+pub fn rank(values: &[f64]) -> (Vec<f64>, f64) {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|a, b| values[*a].total_cmp(&values[*b]));
+
+    let mut ranks = vec![0.; values.len()];
+    let mut ties = 0.;
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+
+        let rank = (start + end + 1) as f64 / 2.;
+        for idx in &order[start..end] {
+            ranks[*idx] = rank;
+        }
+
+        let t = (end - start) as f64;
+        ties += t.powi(3) - t;
+        start = end;
+    }
+
+    (ranks, ties)
+}
+
+fn two_sided_p_value(deviation: f64, variance: f64) -> f64 {
+    if variance <= 0. {
+        return 1.;
+    }
+
+    let z = (deviation.abs() - 0.5).max(0.) / variance.sqrt();
+    (2. * (1. - normal_cdf(z))).min(1.)
+}
+
+pub fn chi_squared_cdf(x: f64, degrees_of_freedom: f64) -> f64 {
+    if x <= 0. {
+        return 0.;
+    }
+
+    lower_regularized_gamma(degrees_of_freedom / 2., x / 2.)
+}
+
+pub fn normal_cdf(x: f64) -> f64 {
+    0.5 * (1. + erf(x / std::f64::consts::SQRT_2))
+}
+
+// Abramowitz and Stegun 7.1.26, the error is below 1.5e-7
+fn erf(x: f64) -> f64 {
+    let t = 1. / (1. + 0.3275911 * x.abs());
+    let polynomial = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let value = 1. - polynomial * (-x * x).exp();
+
+    if x < 0. { -value } else { value }
+}
+
+// Friedman test, blocks[i][j] is the value of treatment j in block i. Ranks are taken in every block,
+// with the chi-squared approximation and tie correction
+pub fn friedman(blocks: &[Vec<f64>]) -> Option<TestResult> {
+    let k = blocks.first()?.len();
+    if k < 2 || blocks.iter().any(|block| block.len() != k) {
+        return None;
+    }
+
+    let (n, k) = (blocks.len() as f64, k as f64);
+    let (rank_sums, ties) = rank_sums(blocks);
+    let statistic = 12. / (n * k * (k + 1.)) * rank_sums.iter().map(|sum| sum * sum).sum::<f64>() - 3. * n * (k + 1.);
+    let correction = 1. - ties / (n * (k.powi(3) - k));
+    if correction <= 0. {
+        return Some(TestResult { statistic: 0., p_value: 1. });
+    }
+
+    let statistic = statistic / correction;
+    Some(TestResult {
+        statistic,
+        p_value: 1. - chi_squared_cdf(statistic, k - 1.),
+    })
+}
+
+// Sums of the ranks of every treatment over the blocks and the tie term of rank
+pub fn rank_sums(blocks: &[Vec<f64>]) -> (Vec<f64>, f64) {
+    let mut sums = vec![0.; blocks.first().map_or(0, |block| block.len())];
+    let mut ties = 0.;
+
+    for block in blocks {
+        let (ranks, block_ties) = rank(block);
+        for (sum, rank) in sums.iter_mut().zip(ranks) {
+            *sum += rank;
+        }
+        ties += block_ties;
+    }
+
+    (sums, ties)
+}
+
+// Series for x < a + 1 and Lentz's continued fraction otherwise, as in Numerical Recipes
+fn lower_regularized_gamma(a: f64, x: f64) -> f64 {
+    let prefix = (-x + a * x.ln() - ln_gamma(a)).exp();
+
+    if x < a + 1. {
+        let mut term = 1. / a;
+        let mut sum = term;
+        for n in 1..500 {
+            term *= x / (a + n as f64);
+            sum += term;
+            if term.abs() < sum.abs() * 1e-14 {
+                break;
+            }
+        }
+        return (sum * prefix).min(1.);
+    }
+
+    let tiny = 1e-300;
+    let mut b = x + 1. - a;
+    let mut c = 1. / tiny;
+    let mut d = 1. / b;
+    let mut fraction = d;
+    for i in 1..500 {
+        let an = -(i as f64) * (i as f64 - a);
+        b += 2.;
+        d = an * d + b;
+        if d.abs() < tiny {
+            d = tiny;
+        }
+        c = b + an / c;
+        if c.abs() < tiny {
+            c = tiny;
+        }
+        d = 1. / d;
+        let delta = d * c;
+        fraction *= delta;
+        if (delta - 1.).abs() < 1e-14 {
+            break;
+        }
+    }
+
+    (1. - prefix * fraction).max(0.)
+}
+
+// Lanczos approximation, g = 7
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9, 676.520_368_121_885_1, -1_259.139_216_722_402_8, 771.323_428_777_653_1,
+        -176.615_029_162_140_6, 12.507_343_278_686_905, -0.138_571_095_265_720_12, 9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1. - x);
+    }
+
+    let x = x - 1.;
+    let sum = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (idx, coefficient)| sum + coefficient / (x + idx as f64 + 1.));
+    let t = x + 7.5;
+
+    0.5 * (2. * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(value: f64, expected: f64, tolerance: f64) {
+        assert!((value - expected).abs() <= tolerance, "{} is not {}", value, expected);
+    }
+
+    #[test]
+    fn describes_values() {
+        let statistics = describe(&[3., 1., 4., 2.]).unwrap();
+
+        assert_eq!((statistics.best, statistics.mean, statistics.median), (1., 2.5, 2.5));
+        assert_close(statistics.std_dev, (5f64 / 3.).sqrt(), 1e-12);
+        assert_eq!(median(&[5., 1., 3.]), 3.);
+        assert_eq!(std_dev(&[5.]), 0.);
+        assert!(describe(&[]).is_none());
+    }
+
+    #[test]
+    fn ties_share_the_average_rank() {
+        let (ranks, ties) = rank(&[30., 10., 20., 20.]);
+
+        assert_eq!(ranks, vec![4., 1., 2.5, 2.5]);
+        assert_eq!(ties, 6.);
+    }
+
+    #[test]
+    fn distributions_match_the_tables() {
+        assert_close(normal_cdf(0.), 0.5, 1e-7);
+        assert_close(normal_cdf(1.959964), 0.975, 1e-6);
+        assert_close(normal_cdf(-1.959964), 0.025, 1e-6);
+        assert_close(chi_squared_cdf(3.841459, 1.), 0.95, 1e-6);
+        assert_close(chi_squared_cdf(5.991465, 2.), 0.95, 1e-6);
+        assert_close(chi_squared_cdf(18.307038, 10.), 0.95, 1e-6);
+        assert_eq!(chi_squared_cdf(0., 3.), 0.);
+    }
+
+    #[test]
+    fn wilcoxon_signed_rank_test() {
+        let second: Vec<f64> = (1..=10).map(|value| value as f64).collect();
+        let first: Vec<f64> = second.iter().map(|value| value * 2.).collect();
+
+        let result = wilcoxon_signed_rank(&first, &second).unwrap();
+
+        assert_eq!(result.statistic, 0.);
+        assert_close(result.p_value, 0.005922, 1e-5);
+        assert_eq!(wilcoxon_signed_rank(&first, &first).unwrap().p_value, 1.);
+        assert!(wilcoxon_signed_rank(&first, &second[1..]).is_none());
+    }
+
+    #[test]
+    fn mann_whitney_u_test() {
+        let result = mann_whitney_u(&[1., 2., 3.], &[4., 5., 6.]).unwrap();
+
+        assert_eq!(result.statistic, 0.);
+        assert_close(result.p_value, 0.080856, 1e-5);
+        assert_close(mann_whitney_u(&[1., 2.], &[1., 2.]).unwrap().p_value, 1., 1e-7);
+        assert!(mann_whitney_u(&[], &[1.]).is_none());
+    }
+
+    #[test]
+    fn friedman_test() {
+        let blocks = vec![vec![1., 2., 3.]; 4];
+
+        let result = friedman(&blocks).unwrap();
+
+        assert_close(result.statistic, 8., 1e-12);
+        assert_close(result.p_value, (-4f64).exp(), 1e-6);
+        assert_eq!(rank_sums(&blocks).0, vec![4., 8., 12.]);
+        assert_eq!(friedman(&[vec![1., 1.], vec![2., 2.]]).unwrap().p_value, 1.);
+        assert!(friedman(&[vec![1.], vec![2.]]).is_none());
+        assert!(friedman(&[vec![1., 2.], vec![2.]]).is_none());
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use rand::rngs::StdRng;
+use rand::Rng;
+use crate::algorithms::genetic::types::GenerateFunc;
+use crate::algorithms::helpers;
+use crate::algorithms::individual::Individual;
+use crate::algorithms::tabu_search::types::{Attribute, Move, Neighbourhood};
+use crate::algorithms::types::{FitnessFuncs, Population, Purpose};
+
+pub struct TabuSearchAlgorithm<T> {
+    pub fitness_funcs: FitnessFuncs<T>,
+    pub iters_count: usize,
+    pub solutions_count: usize,
+    pub tenure: usize,
+    pub candidates_count: Option<usize>,
+    pub diversification: f64,
+    pub neighbourhood: Neighbourhood,
+    pub generate_func: GenerateFunc<T>,
+    pub purpose: Purpose,
+}
+
+impl<T: Clone + Eq + Hash + Ord> TabuSearchAlgorithm<T> {
+    pub fn run(&self) -> Result<Population<T>, &'static str> {
+        self.run_observed(&mut |_, _| true)
+    }
+
+    // The observer gets the iteration number and the best individual after every iteration
+    pub fn run_observed(&self, observer: &mut dyn FnMut(usize, &Individual<T>) -> bool) -> Result<Population<T>, &'static str> {
+        let mut rng = helpers::make_rng();
+        let mut archive: Population<T> = Vec::with_capacity(self.solutions_count);
+        let mut tabu: HashMap<Attribute<T>, usize> = HashMap::new();
+        let mut frequencies: HashMap<Attribute<T>, usize> = HashMap::new();
+
+        let mut current = Individual::with_fitnesses(self.generate_func.0(&mut rng), &self.fitness_funcs);
+        let mut current_energy = helpers::energy(&current, &self.purpose);
+        let mut best_energy = current_energy;
+        helpers::update_archive(&mut archive, &current, self.solutions_count, &self.purpose);
+
+        for iter in 0..self.iters_count {
+            let mut selected: Option<(Individual<T>, f64, f64, Attribute<T>)> = None;
+
+            for mv in self.moves(current.value.len(), &mut rng) {
+                let candidate = Individual::with_fitnesses(Self::apply(&current.value, mv), &self.fitness_funcs);
+                let candidate_energy = match helpers::energy(&candidate, &self.purpose) {
+                    Some(value) => value,
+                    None => continue,
+                };
+                let attribute = Self::attribute(&current.value, mv);
+
+                // Aspiration: a tabu move is allowed if it gives a new best solution
+                let is_tabu = tabu.get(&attribute).is_some_and(|expires| *expires > iter);
+                let is_aspirated = best_energy.is_none_or(|best| candidate_energy < best);
+                if is_tabu && !is_aspirated {
+                    continue;
+                }
+
+                // Long-term memory: non-improving moves that were made often are penalized
+                let score = if current_energy.is_some_and(|energy| candidate_energy >= energy) {
+                    let frequency = frequencies.get(&attribute).copied().unwrap_or(0);
+                    candidate_energy + self.diversification * frequency as f64 / (iter + 1) as f64
+                } else {
+                    candidate_energy
+                };
+
+                if selected.as_ref().is_none_or(|(_, _, best_score, _)| score < *best_score) {
+                    selected = Some((candidate, candidate_energy, score, attribute));
+                }
+            }
+
+            if let Some((candidate, candidate_energy, _, attribute)) = selected {
+                *frequencies.entry(attribute.clone()).or_insert(0) += 1;
+                tabu.insert(attribute, iter + 1 + self.tenure);
+
+                if best_energy.is_none_or(|best| candidate_energy < best) {
+                    best_energy = Some(candidate_energy);
+                }
+                helpers::update_archive(&mut archive, &candidate, self.solutions_count, &self.purpose);
+                current = candidate;
+                current_energy = Some(candidate_energy);
+            }
+
+            if archive.first().is_some_and(|best| !observer(iter, best)) {
+                break;
+            }
+        }
+
+        helpers::calculate_fitnesses(&mut archive, &self.fitness_funcs);
+        Ok(archive)
+    }
+
+    fn moves(&self, len: usize, rng: &mut StdRng) -> Vec<Move> {
+        if len < 3 {
+            return Vec::new();
+        }
+
+        match self.candidates_count {
+            Some(count) => (0..count).filter_map(|_| self.random_move(len, rng)).collect(),
+            None => self.all_moves(len),
+        }
+    }
+
+    fn all_moves(&self, len: usize) -> Vec<Move> {
+        match self.neighbourhood {
+            Neighbourhood::Swap => (0..len)
+                .flat_map(|i| (i + 1..len).map(move |j| Move::Swap(i, j)))
+                .collect(),
+            Neighbourhood::TwoOpt => (0..len - 2)
+                .flat_map(|i| (i + 2..len).map(move |j| Move::TwoOpt(i, j)))
+                .filter(|mv| !matches!(mv, Move::TwoOpt(i, j) if (j + 1) % len == *i))
+                .collect(),
+            Neighbourhood::Insertion => (0..len)
+                .flat_map(|i| (0..len).filter(move |j| *j != i).map(move |j| Move::Insertion(i, j)))
+                .collect(),
+        }
+    }
+
+    // One of all_moves, None when there are no moves of the neighbourhood
+    fn random_move(&self, len: usize, rng: &mut StdRng) -> Option<Move> {
+        match self.neighbourhood {
+            Neighbourhood::Swap => {
+                let i = rng.gen_range(0..len - 1);
+                Some(Move::Swap(i, rng.gen_range(i + 1..len)))
+            }
+            Neighbourhood::TwoOpt => {
+                // Reversing all but the first element of the closed tour gives the same tour
+                if len < 4 {
+                    return None;
+                }
+
+                let i = rng.gen_range(0..len - 2);
+                let j_max = if i == 0 { len - 1 } else { len };
+                Some(Move::TwoOpt(i, rng.gen_range(i + 2..j_max)))
+            }
+            Neighbourhood::Insertion => {
+                let i = rng.gen_range(0..len);
+                let j = (i + rng.gen_range(1..len)) % len;
+                Some(Move::Insertion(i, j))
+            }
+        }
+    }
+
+    fn apply(value: &[T], mv: Move) -> Vec<T> {
+        let mut value = value.to_vec();
+        match mv {
+            Move::Swap(i, j) => value.swap(i, j),
+            Move::TwoOpt(i, j) => value[i + 1..=j].reverse(),
+            Move::Insertion(i, j) => {
+                let element = value.remove(i);
+                value.insert(j, element);
+            }
+        }
+        value
+    }
+
+    fn attribute(value: &[T], mv: Move) -> Attribute<T> {
+        let (a, b) = match mv {
+            Move::Swap(i, j) => (&value[i], &value[j]),
+            Move::TwoOpt(i, j) => (&value[i + 1], &value[j]),
+            Move::Insertion(i, _) => (&value[i], &value[i]),
+        };
+
+        if a <= b {
+            (a.clone(), b.clone())
+        } else {
+            (b.clone(), a.clone())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::types::FitnessFuncRaw;
+    use rand::SeedableRng;
+    use rand::seq::SliceRandom;
+
+    // Sum of |value - index|, the sorted sequence is the only optimum
+    fn algorithm(neighbourhood: Neighbourhood, candidates_count: Option<usize>) -> TabuSearchAlgorithm<usize> {
+        TabuSearchAlgorithm {
+            fitness_funcs: vec![Box::new(|value: &Vec<usize>| {
+                Some(value.iter().enumerate().map(|(idx, el)| idx.abs_diff(*el) as f64).sum())
+            }) as FitnessFuncRaw<usize>],
+            iters_count: 50,
+            solutions_count: 3,
+            tenure: 3,
+            candidates_count,
+            diversification: 0.,
+            neighbourhood,
+            generate_func: GenerateFunc(Box::new(|rng: &mut StdRng| {
+                let mut value: Vec<usize> = (0..6).collect();
+                value.shuffle(rng);
+                value
+            })),
+            purpose: Purpose::Min,
+        }
+    }
+
+    #[test]
+    fn random_moves_are_among_all_moves() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        for neighbourhood in [Neighbourhood::Swap, Neighbourhood::TwoOpt, Neighbourhood::Insertion] {
+            let algorithm = algorithm(neighbourhood, Some(10));
+            for len in 3..8 {
+                let all_moves = algorithm.all_moves(len);
+                for _ in 0..200 {
+                    match algorithm.random_move(len, &mut rng) {
+                        Some(mv) => assert!(all_moves.contains(&mv), "{:?} for {} elements", mv, len),
+                        None => assert!(all_moves.is_empty()),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn two_opt_has_no_moves_on_a_triangle() {
+        let algorithm = algorithm(Neighbourhood::TwoOpt, Some(10));
+
+        assert!(algorithm.all_moves(3).is_empty());
+        assert!(algorithm.moves(3, &mut StdRng::seed_from_u64(1)).is_empty());
+    }
+
+    #[test]
+    fn full_neighbourhood_reaches_the_optimum() {
+        // 2-opt keeps the first element in place, it is about closed tours
+        for neighbourhood in [Neighbourhood::Swap, Neighbourhood::Insertion] {
+            let archive = algorithm(neighbourhood, None).run().unwrap();
+
+            assert_eq!(archive[0].value, (0..6).collect::<Vec<usize>>());
+        }
+    }
+}
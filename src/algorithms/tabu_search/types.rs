@@ -0,0 +1,20 @@
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum Neighbourhood {
+    Swap,
+    TwoOpt,
+    Insertion,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Move {
+    // Exchange elements at both indexes
+    Swap(usize, usize),
+    // Reverse elements between the indexes, the left one is excluded
+    TwoOpt(usize, usize),
+    // Move element from the first index to the second one
+    Insertion(usize, usize),
+}
+
+// Pair of elements touched by a move, it is what becomes tabu
+pub type Attribute<T> = (T, T);
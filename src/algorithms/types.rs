@@ -1,11 +1,19 @@
 use crate::algorithms::individual::Individual;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
 pub enum Purpose {
     Min,
     Max,
 }
 
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum Scaling {
+    Windowing,
+    Sigma(f32),
+}
+
 pub type Fitness = Option<f32>;
 pub type FitnessRaw = Option<f64>;
 pub type FitnessFuncRaw<T> = Box<dyn Fn(&Vec<T>) -> FitnessRaw>;
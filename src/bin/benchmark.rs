@@ -0,0 +1,33 @@
+use std::fs;
+use std::path::Path;
+use phd_cand::problems::travelling_salesman::experiment::BenchmarkConfig;
+
+// Usage: benchmark <config.toml> [output directory for CSV and Markdown tables]
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let config_path = args.next().expect("Path to the benchmark config is expected");
+
+    let benchmark = BenchmarkConfig::from_file(&config_path)
+        .and_then(BenchmarkConfig::into_benchmark)
+        .unwrap_or_else(|error| panic!("{}", error));
+    let report = benchmark.run().unwrap();
+
+    println!("{}", report.summaries_markdown());
+    println!("{}", report.comparisons_markdown());
+
+    if let Some(output) = args.next() {
+        let output = Path::new(&output);
+        fs::create_dir_all(output).unwrap();
+
+        let files = [
+            ("runs.csv", report.runs_csv()),
+            ("summaries.csv", report.summaries_csv()),
+            ("comparisons.csv", report.comparisons_csv()),
+            ("summaries.md", report.summaries_markdown()),
+            ("comparisons.md", report.comparisons_markdown()),
+        ];
+        for (name, content) in files {
+            fs::write(output.join(name), content).unwrap();
+        }
+    }
+}
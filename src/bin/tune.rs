@@ -0,0 +1,21 @@
+use phd_cand::problems::travelling_salesman::experiment::TuningConfig;
+
+// Usage: tune <config.toml>, the tuned solver config is printed as TOML
+fn main() {
+    let config_path = std::env::args().nth(1).expect("Path to the tuning config is expected");
+
+    let tuner = TuningConfig::from_file(&config_path)
+        .and_then(TuningConfig::into_tuner)
+        .unwrap_or_else(|error| panic!("{}", error));
+    let tuned = tuner.run().unwrap_or_else(|error| panic!("{}", error));
+
+    for (values, error) in tuned.failed.iter() {
+        let values: Vec<String> = values.iter().map(|(name, value)| format!("{} = {}", name, value)).collect();
+        eprintln!("Failed: {}: {}", values.join(", "), error);
+    }
+    for (name, value) in tuned.values.iter() {
+        eprintln!("{} = {}", name, value);
+    }
+    eprintln!("Runs: {}", tuned.evaluations);
+    println!("{}", toml::to_string(&tuned.config).unwrap());
+}
@@ -1,15 +1,36 @@
-mod algorithms;
-mod problems;
-
 use std::time::{Instant};
-use crate::algorithms::bee_colony::research_methods;
-use crate::algorithms::genetic::methods::{Mutate, Select};
-use crate::algorithms::genetic::types::{MutateFunc, SelectFunc};
-use crate::problems::travelling_salesman::algorithms::ant_colony::builder::TSAntColonyAlgorithmBuilder;
-use crate::problems::travelling_salesman::algorithms::bee_colony::builder::TSBeeColonyAlgorithmBuilder;
-use crate::problems::travelling_salesman::algorithms::genetic::builder::TSGeneticAlgorithmBuilder;
+use phd_cand::problems::travelling_salesman::algorithms::ant_colony::builder::TSAntColonyAlgorithmBuilder;
 
 fn main() {
+    #[cfg(feature = "config")]
+    if let Some(path) = std::env::args().nth(1) {
+        let experiment = phd_cand::problems::travelling_salesman::experiment::Experiment::from_file(&path)
+            .unwrap_or_else(|error| panic!("{}", error));
+
+        let instance = experiment.load().unwrap_or_else(|error| panic!("{}", error));
+
+        let time_start = Instant::now();
+        let solutions = experiment.run_on(&instance).unwrap();
+        println!("{:?}", time_start.elapsed());
+        let explainer = experiment.explainer(&instance).unwrap_or_else(|error| panic!("{}", error));
+        for solution in solutions {
+            match solution.time {
+                Some(time) => println!(
+                    "{} (distance {}, penalty {}, time {})",
+                    solution.format_path(&instance.city_names), solution.distance, solution.penalty, time
+                ),
+                None => println!(
+                    "{} (distance {}, penalty {})",
+                    solution.format_path(&instance.city_names), solution.distance, solution.penalty
+                ),
+            }
+            if !experiment.solver.rules.is_empty() {
+                println!("  {}", explainer.explain(&solution.path));
+            }
+        }
+        return;
+    }
+
     let matrix = vec![
         vec![0.0, 29.0, 20.0, 21.0, 16.0, 31.0, 100.0, 12.0, 4.0, 31.0],
         vec![29.0, 0.0, 15.0, 29.0, 28.0, 40.0, 72.0, 21.0, 29.0, 41.0],
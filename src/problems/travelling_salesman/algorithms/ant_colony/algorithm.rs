@@ -1,58 +1,103 @@
 use crate::algorithms::ant_colony::algorithm::AntColonyAlgorithm as Parent;
 use crate::algorithms::ant_colony::ant::Ant;
 use crate::algorithms::ant_colony::types::{City, PheromoneMatrix};
+use crate::problems::travelling_salesman::constraints::Constraints;
 use crate::problems::travelling_salesman::helpers;
 use crate::problems::travelling_salesman::solution::Solution;
+use crate::problems::travelling_salesman::solver::Solver;
 use crate::problems::travelling_salesman::types::{RuleFn, TimeMatrix};
 use lru::LruCache;
-use rand::thread_rng;
 use std::cell::RefCell;
+use crate::algorithms::helpers::make_rng;
 
 pub struct TSAntColonyAlgorithm {
     pub algo: Parent,
     pub rules: Vec<RuleFn>,
+    // Rules of the constraints, partial paths are kept by the constraints instead
+    pub hard_rules: Vec<RuleFn>,
+    pub constraints: Constraints,
     pub time_matrix: Option<TimeMatrix>,
+    // Penalties of the rules on partial paths, the length of the path is not kept
     pub penalty_cache: RefCell<LruCache<Vec<City>, Option<f64>>>,
+    pub initial_paths: Vec<Vec<City>>,
+    // Smallest positive edge cost, non-positive move costs are shifted up to it
+    pub min_edge_cost: f64,
 }
 
 impl TSAntColonyAlgorithm {
-    pub fn run(&self) -> Result<Vec<Solution>, &str> {
+    pub fn run(&self) -> Result<Vec<Solution>, &'static str> {
+        self.run_with(&[])
+    }
+
+    // Given and own initial paths become best-so-far solutions and lay the initial pheromone trail
+    pub fn run_with(&self, initial_paths: &[Vec<City>]) -> Result<Vec<Solution>, &'static str> {
+        self.run_observed(initial_paths, &mut |_, _| true)
+    }
+
+    // The observer gets the iteration number and the best solution after every iteration
+    pub fn run_observed(
+        &self,
+        initial_paths: &[Vec<City>],
+        observer: &mut dyn FnMut(usize, &Solution) -> bool,
+    ) -> Result<Vec<Solution>, &'static str> {
         let cities_count = self.algo.cities_count();
         let mut pheromone_matrix: PheromoneMatrix = Parent::generate_pheromone_matrix(cities_count);
         let mut solutions: Vec<Solution> = Vec::new();
-        let mut rng = thread_rng();
+        for path in initial_paths.iter().chain(self.initial_paths.iter()) {
+            if let Some((distance, penalty)) = self.calculate_distance(path) {
+                // Bonuses may make the cost non-positive, such tours are kept but lay no trail
+                let cost = distance + penalty;
+                if cost > 0. {
+                    for (idx, city) in path.iter().enumerate() {
+                        pheromone_matrix[*city][path[(idx + 1) % cities_count]] += self.algo.q / cost;
+                    }
+                }
+                solutions.push(self.make_solution(path.clone(), distance, penalty, self.get_ant_time(path), 0.));
+            }
+        }
+        let mut rng = make_rng();
         let mut colony: Vec<Ant> = (0..self.algo.actors_count)
             .map(|_| Ant::new(cities_count, &mut rng))
             .collect();
 
-        'outer: for _ in 1..=self.algo.iters_count {
+        for iter in 0..self.algo.iters_count {
             let mut iter_pheromone_matrix: PheromoneMatrix =
                 Parent::generate_pheromone_matrix(cities_count);
 
             for ant in colony.iter_mut() {
+                if !self.constraints.allows(&[], ant.current_city()) {
+                    if let Some(start) = self.constraints.start(&mut rng) {
+                        ant.path = vec![start];
+                    }
+                }
+
+                // An excluded tour is dropped, the ant starts the next one from its last city
+                let mut excluded = false;
+                let (mut distance, mut penalty) = (0., 0.);
                 for _ in 0..cities_count - 1 {
-                    let probabilities = self.get_probabilities_list(&ant, &mut pheromone_matrix)?;
-                    let city = self.algo.select_city(probabilities)?;
+                    let probabilities = self.get_probabilities_list(ant, &mut pheromone_matrix)?;
+                    let city = self.algo.select_city(probabilities, &mut rng)?;
                     ant.go_to(city);
 
-                    match self.get_ant_distance(&ant) {
-                        Some(d) if d > 0. => {
+                    match self.get_ant_distance(ant) {
+                        Some(evaluation) => {
+                            (distance, penalty) = evaluation;
+                            let d = distance + penalty;
                             ant.distance = d;
                             ant.time = self.get_ant_time(&ant.path);
-                            iter_pheromone_matrix[ant.previous_city()][city] += self.algo.q / d
+                            if d > 0. {
+                                iter_pheromone_matrix[ant.previous_city()][city] += self.algo.q / d
+                            }
+                        }
+                        None => {
+                            excluded = true;
+                            break;
                         }
-                        None => continue 'outer,
-                        _ => continue,
                     }
                 }
 
-                if ant.path.len() == self.algo.cities_count() {
-                    solutions.push(Solution {
-                        path: ant.path.clone(),
-                        distance: ant.distance,
-                        time: ant.time,
-                        fitness: 1.,
-                    });
+                if !excluded && ant.path.len() == self.algo.cities_count() {
+                    solutions.push(self.make_solution(ant.path.clone(), distance, penalty, ant.time, 1.));
                 }
                 ant.reset_path();
             }
@@ -69,51 +114,31 @@ impl TSAntColonyAlgorithm {
                 .distance;
             let distance_diff = distance_max - distance_min;
 
-            let time_min = if self.time_matrix.is_none() {
-                1.
-            } else {
-                solutions
-                    .iter()
-                    .min_by(|a, b| {
-                        let a_time = a.time.unwrap();
-                        let b_time = b.time.unwrap();
-
-                        a_time.partial_cmp(&b_time).unwrap()
-                    })
-                    .unwrap()
-                    .time
-                    .unwrap() as f64
-            };
-            let time_max = if self.time_matrix.is_none() {
-                1.
-            } else {
-                solutions
-                    .iter()
-                    .max_by(|a, b| {
-                        let a_time = a.time.unwrap();
-                        let b_time = b.time.unwrap();
-
-                        a_time.partial_cmp(&b_time).unwrap()
-                    })
-                    .unwrap()
-                    .time
-                    .unwrap() as f64
-            };
+            // Every ant may be excluded by the rules, then there is nothing to rank yet
+            if solutions.is_empty() {
+                self.algo.vape_pheromone(&mut pheromone_matrix, &iter_pheromone_matrix);
+                continue;
+            }
+
+            let times = solutions.iter().filter_map(|sol| sol.time).map(|time| time as f64);
+            let time_min = times.clone().fold(f64::INFINITY, f64::min);
+            let time_max = times.fold(f64::NEG_INFINITY, f64::max);
             let time_diff = time_max - time_min;
 
-            solutions.dedup_by(|a, b| a.distance == b.distance);
+            // Equal values of a whole iteration are all ranked 0 instead of NaN
+            let scale = |value: f64, min: f64, diff: f64| if diff > 0. { (value - min) / diff } else { 0. };
+            solutions.dedup_by(|a, b| a.cost() == b.cost());
             solutions = solutions
                 .into_iter()
                 .map(|sol| Solution {
-                    path: sol.path,
-                    distance: sol.distance,
-                    time: sol.time,
-                    fitness: if self.time_matrix.is_some() {
-                        ((sol.distance - distance_min) / distance_diff) as f32
-                            + ((sol.time.unwrap() as f64 - time_min) / time_diff) as f32
-                    } else {
-                        ((sol.distance - distance_min) / distance_diff) as f32
+                    fitness: match (self.time_matrix.is_some(), sol.time) {
+                        (true, Some(time)) => {
+                            scale(sol.cost(), distance_min, distance_diff) as f32
+                                + scale(time as f64, time_min, time_diff) as f32
+                        }
+                        _ => scale(sol.cost(), distance_min, distance_diff) as f32,
                     },
+                    ..sol
                 })
                 .collect();
 
@@ -121,6 +146,10 @@ impl TSAntColonyAlgorithm {
             solutions.truncate(self.algo.solutions_count);
             self.algo
                 .vape_pheromone(&mut pheromone_matrix, &iter_pheromone_matrix);
+
+            if solutions.first().is_some_and(|best| !observer(iter, best)) {
+                break;
+            }
         }
 
         Ok(solutions)
@@ -130,7 +159,7 @@ impl TSAntColonyAlgorithm {
         &self,
         ant: &Ant,
         pheromone_matrix: &mut PheromoneMatrix,
-    ) -> Result<Vec<f64>, &str> {
+    ) -> Result<Vec<f64>, &'static str> {
         let cities_preferences = self.get_ant_preferences(ant, pheromone_matrix);
         let cities_preferences_sum: f64 = cities_preferences.iter().sum();
 
@@ -149,109 +178,353 @@ impl TSAntColonyAlgorithm {
         }
     }
 
+    // Cities the constraints forbid are masked out, unless the ant has nowhere else to go
     fn get_ant_preferences(&self, ant: &Ant, pheromone_matrix: &mut PheromoneMatrix) -> Vec<f64> {
-        let get_ant_preference_to = |city: City| -> f64 {
-            if ant.path.contains(&city) {
-                return 0.;
-            }
-
-            let visibility = self.get_ant_visibility(&ant, &city);
-            let pheromone = pheromone_matrix[ant.current_city()][city];
-
-            visibility.powf(self.algo.alpha) * pheromone.powf(self.algo.beta)
-        };
-
-        self.algo
+        let costs: Vec<Option<f64>> = self.algo
             .cities_list()
             .into_iter()
-            .map(get_ant_preference_to)
-            .collect()
-    }
+            .map(|city| self.get_ant_cost(ant, &city))
+            .collect();
 
-    fn get_ant_distance(&self, ant: &Ant) -> Option<f64> {
-        if ant.path.len() <= 1 {
-            Some(0.)
-        } else {
-            let mut cache = self.penalty_cache.borrow_mut();
+        // Bonuses of the rules may make costs non-positive, all of them are shifted alike
+        // so the cheapest move stays the most attractive one
+        let cost_min = costs.iter().flatten().cloned().fold(f64::INFINITY, f64::min);
+        let shift = if cost_min <= 0. { self.min_edge_cost - cost_min } else { 0. };
 
-            if let Some(result_raw) = cache.get(&ant.path.clone()) {
-                if result_raw.is_none() || result_raw.unwrap_or(0.) > 0. {
-                    return *result_raw;
-                }
-            }
+        let preferences: Vec<f64> = costs
+            .into_iter()
+            .enumerate()
+            .map(|(city, cost)| match cost {
+                Some(cost) => {
+                    let visibility = 1. / (cost + shift);
+                    let pheromone = pheromone_matrix[ant.current_city()][city];
 
-            let result = self.calculate_distance(ant);
-            cache.put(ant.path.clone(), result);
-            return result;
+                    visibility.powf(self.algo.alpha) * pheromone.powf(self.algo.beta)
+                }
+                None => 0.,
+            })
+            .collect();
+        if self.constraints.is_empty() {
+            return preferences;
         }
-    }
 
-    fn get_ant_visibility(&self, ant: &Ant, city: &City) -> f64 {
-        if ant.path.contains(&city) {
-            return 0.;
+        let masked: Vec<f64> = preferences
+            .iter()
+            .enumerate()
+            .map(|(city, preference)| if self.constraints.allows(&ant.path, city) { *preference } else { 0. })
+            .collect();
+        if masked.iter().any(|preference| *preference > 0.) {
+            masked
+        } else {
+            preferences
         }
+    }
 
-        let penalty = self.get_penalty_to_city(&ant.path, city);
-        if let None = penalty {
-            return 0.;
+    // Length and penalty of the path. Complete tours skip the cache, so the hard rules always run on them
+    fn get_ant_distance(&self, ant: &Ant) -> Option<(f64, f64)> {
+        if ant.path.len() <= 1 {
+            Some((0., 0.))
+        } else if ant.path.len() == self.algo.cities_count() {
+            self.calculate_distance(&ant.path)
+        } else {
+            let penalty = self.get_penalty(&ant.path)?;
+            Some((helpers::calculate_distance(&self.algo.matrix, &ant.path), penalty))
         }
+    }
 
-        let distance = self.algo.matrix[ant.current_city()][*city] + penalty.unwrap();
-        if distance == 0. {
-            return 0.;
+    // Cost of the move to the city with the penalty and the travel time, None if the ant cannot go there
+    fn get_ant_cost(&self, ant: &Ant, city: &City) -> Option<f64> {
+        if ant.path.contains(city) {
+            return None;
         }
 
+        let penalty = self.get_penalty_to_city(&ant.path, city)?;
         let time = match &self.time_matrix {
             Some(time_matrix) => time_matrix[ant.current_city()][*city],
             None => 0,
         } as f64;
 
-        1. / (distance + time)
+        Some(self.algo.matrix[ant.current_city()][*city] + penalty + time)
     }
 
-    fn calculate_distance(&self, ant: &Ant) -> Option<f64> {
-        let penalty = if self.rules.is_empty() {
-            0.
+    // Length of the path and the penalty of the rules, the hard rules are checked on complete tours only
+    fn calculate_distance(&self, path: &[City]) -> Option<(f64, f64)> {
+        let hard_rules: &[RuleFn] = if path.len() == self.algo.cities_count() {
+            &self.hard_rules
         } else {
-            let mut p = 0;
-            for evaluate in self.rules.iter() {
-                match evaluate(&ant.path) {
-                    Some(pen) => p += pen,
-                    None => return None,
-                }
-            }
-            p as f64
+            &[]
         };
+        let penalty = self.evaluate_rules(self.rules.iter().chain(hard_rules), path)?;
 
-        Some(helpers::calculate_distance(&self.algo.matrix, &ant.path) + penalty)
+        Some((helpers::calculate_distance(&self.algo.matrix, path), penalty))
     }
 
-    fn get_ant_time(&self, path: &Vec<City>) -> Option<usize> {
-        if let Some(time_matrix) = &self.time_matrix {
-            Some(helpers::calculate_time(time_matrix, path))
-        } else {
-            None
+    fn make_solution(&self, path: Vec<City>, distance: f64, penalty: f64, time: Option<usize>, fitness: f32) -> Solution {
+        Solution {
+            path,
+            distance,
+            time,
+            penalty,
+            fitness,
         }
     }
 
-    fn get_penalty_to_city(&self, path: &Vec<City>, city: &City) -> Option<f64> {
-        let mut cache = self.penalty_cache.borrow_mut();
-        let mut path = path.clone();
+    fn get_ant_time(&self, path: &[City]) -> Option<usize> {
+        self.time_matrix.as_ref().map(|time_matrix| helpers::calculate_time(time_matrix, path))
+    }
+
+    fn get_penalty_to_city(&self, path: &[City], city: &City) -> Option<f64> {
+        let mut path = path.to_vec();
         path.push(*city);
-        if let Some(result) = cache.get(&path) {
+        self.get_penalty(&path)
+    }
+
+    // Penalty of the rules on a partial path, the hard rules are left to the constraints
+    fn get_penalty(&self, path: &[City]) -> Option<f64> {
+        let mut cache = self.penalty_cache.borrow_mut();
+        if let Some(result) = cache.get(path) {
             *result
         } else {
-            let mut sum = 0;
-            for evaluate in self.rules.iter() {
-                if let Some(penalty) = evaluate(&path) {
-                    sum += penalty;
-                } else {
-                    return None;
-                }
+            let result = self.evaluate_rules(self.rules.iter(), path);
+            cache.put(path.to_vec(), result);
+            result
+        }
+    }
+
+    fn evaluate_rules<'a>(&self, rules: impl Iterator<Item = &'a RuleFn>, path: &[City]) -> Option<f64> {
+        let path = path.to_vec();
+        let mut sum = 0;
+        for evaluate in rules {
+            sum += evaluate(&path)?;
+        }
+
+        Some(sum as f64)
+    }
+}
+
+impl Solver for TSAntColonyAlgorithm {
+    fn name(&self) -> &'static str {
+        "ant_colony"
+    }
+
+    fn parameters(&self) -> String {
+        format!(
+            "actors_count: {}, iters_count: {}, solutions_count: {}, alpha: {}, beta: {}, p: {}, q: {}",
+            self.algo.actors_count, self.algo.iters_count, self.algo.solutions_count,
+            self.algo.alpha, self.algo.beta, self.algo.p, self.algo.q
+        )
+    }
+
+    fn run_with_observer(&self, observer: &mut dyn FnMut(usize, &Solution) -> bool) -> Result<Vec<Solution>, &'static str> {
+        self.run_observed(&[], observer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::problems::travelling_salesman::exact::held_karp;
+    use crate::problems::travelling_salesman::fixtures;
+    use crate::algorithms::ant_colony::algorithm::AntColonyAlgorithm;
+    use crate::algorithms::ant_colony::ant::Ant;
+    use crate::algorithms::helpers::set_seed;
+    use crate::problems::travelling_salesman::helpers::calculate_distance;
+    use crate::problems::travelling_salesman::types::Matrix;
+    use super::super::builder::TSAntColonyAlgorithmBuilder;
+    use super::TSAntColonyAlgorithm;
+
+    fn preferences_from_start(algorithm: &TSAntColonyAlgorithm) -> Vec<f64> {
+        let ant = Ant { path: vec![0], distance: 0., time: None };
+        let mut pheromone_matrix = AntColonyAlgorithm::generate_pheromone_matrix(algorithm.algo.cities_count());
+        algorithm.get_ant_preferences(&ant, &mut pheromone_matrix)
+    }
+
+    fn most_preferred(preferences: &[f64]) -> usize {
+        (0..preferences.len()).max_by(|a, b| preferences[*a].total_cmp(&preferences[*b])).unwrap()
+    }
+
+    #[test]
+    fn bonus_moves_are_the_most_attractive() {
+        let matrix = fixtures::matrix();
+        let algorithm = TSAntColonyAlgorithmBuilder::new(matrix.clone())
+            .rules(vec!["edge from 0 to 6 : -1000".to_string()])
+            .build();
+
+        let preferences = preferences_from_start(&algorithm);
+
+        assert_eq!(most_preferred(&preferences), 6);
+        assert_eq!(preferences[0], 0.);
+        // The other moves keep the order of their costs
+        let mut cities: Vec<usize> = (1..10).filter(|city| *city != 6).collect();
+        cities.sort_by(|a, b| matrix[0][*a].total_cmp(&matrix[0][*b]));
+        assert!(cities.windows(2).all(|pair| preferences[pair[0]] >= preferences[pair[1]]));
+        assert!(preferences[1..].iter().all(|preference| preference.is_finite() && *preference > 0.));
+    }
+
+    #[test]
+    fn zero_cost_moves_are_attractive() {
+        let mut matrix: Matrix = fixtures::matrix();
+        matrix[0][7] = 0.;
+        let algorithm = TSAntColonyAlgorithmBuilder::new(matrix).build();
+
+        let preferences = preferences_from_start(&algorithm);
+
+        assert_eq!(most_preferred(&preferences), 7);
+    }
+
+    #[test]
+    fn runs_with_large_bonuses() {
+        set_seed(Some(3));
+        let algorithm = TSAntColonyAlgorithmBuilder::new(fixtures::matrix())
+            .actors_count(10)
+            .iters_count(10)
+            .rules(vec!["edge from 0 to 6 : -1000".to_string()])
+            .build();
+
+        let solutions = algorithm.run().unwrap();
+        set_seed(None);
+
+        assert_eq!(solutions[0].penalty, -1000.);
+    }
+
+    #[test]
+    fn initial_solutions_are_kept() {
+        let optimum = held_karp(&fixtures::matrix()).unwrap().path;
+        let algorithm = TSAntColonyAlgorithmBuilder::new(fixtures::matrix())
+            .actors_count(5)
+            .iters_count(3)
+            .initial_solutions(vec![optimum])
+            .build();
+
+        let solutions = algorithm.run().unwrap();
+
+        assert_eq!(solutions[0].cost(), fixtures::OPTIMUM);
+    }
+
+    #[test]
+    fn initial_solutions_with_bonuses_are_kept() {
+        let path: Vec<usize> = (0..10).collect();
+        let algorithm = TSAntColonyAlgorithmBuilder::new(fixtures::matrix())
+            .iters_count(0)
+            .rules(vec!["edge from 0 to 1 : -1000".to_string()])
+            .initial_solutions(vec![path.clone()])
+            .build();
+
+        let solutions = algorithm.run().unwrap();
+
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].path, path);
+        assert!(solutions[0].cost() <= 0.);
+    }
+
+    #[test]
+    #[should_panic(expected = "not a permutation")]
+    fn initial_solutions_must_be_permutations() {
+        TSAntColonyAlgorithmBuilder::new(fixtures::matrix()).initial_solutions(vec![vec![0, 1, 1]]);
+    }
+
+    #[test]
+    fn tours_of_the_mask_fallback_are_feasible() {
+        // City 9 may only follow city 0, so ants often end up with nowhere else to go
+        let mut rules: Vec<String> = (1..9).map(|city| format!("edge from {} to 9 : exclude", city)).collect();
+        // Positive penalty of every complete tour, kept in the cache of the partial paths
+        rules.push("5 at position [1,] : 3".to_string());
+
+        for seed in 0..5 {
+            set_seed(Some(seed));
+            let algorithm = TSAntColonyAlgorithmBuilder::new(fixtures::matrix())
+                .actors_count(10)
+                .iters_count(10)
+                .rules(rules.clone())
+                .build();
+
+            let solutions = algorithm.run().unwrap();
+            set_seed(None);
+
+            assert!(!solutions.is_empty());
+            for solution in solutions {
+                assert!(algorithm.constraints.is_feasible(&solution.path), "{:?}", solution.path);
+                assert_eq!(solution.penalty, 3.);
             }
+        }
+    }
+
+    #[test]
+    fn partial_paths_get_the_distance_with_the_penalty() {
+        let matrix = fixtures::matrix();
+        let algorithm = TSAntColonyAlgorithmBuilder::new(matrix.clone())
+            .rules(vec!["1 follows 0 : 7".to_string()])
+            .build();
+        // The preferences keep the penalties of the moves in the cache
+        preferences_from_start(&algorithm);
+
+        let ant = Ant { path: vec![0, 1], distance: 0., time: None };
 
-            cache.put(path.clone(), Some(sum as f64));
-            Some(sum as f64)
+        assert_eq!(algorithm.get_ant_distance(&ant), Some((matrix[0][1] + matrix[1][0], 7.)));
+    }
+
+    #[test]
+    fn solutions_keep_the_penalties_apart() {
+        set_seed(Some(2));
+        let matrix = fixtures::matrix();
+        let algorithm = TSAntColonyAlgorithmBuilder::new(matrix.clone())
+            .actors_count(10)
+            .iters_count(5)
+            .rules(vec!["1 follows 0 : 7".to_string(), "5 at position [1,] : 3".to_string()])
+            .build();
+
+        let solutions = algorithm.run().unwrap();
+        set_seed(None);
+
+        for solution in solutions {
+            let follows = solution.path.windows(2).any(|pair| pair == [0, 1]);
+            assert_eq!(solution.distance, calculate_distance(&matrix, &solution.path));
+            assert_eq!(solution.penalty, if follows { 10. } else { 3. });
         }
     }
+
+    #[test]
+    fn runs_when_every_ant_is_excluded() {
+        set_seed(Some(1));
+        let algorithm = TSAntColonyAlgorithmBuilder::new(fixtures::matrix())
+            .actors_count(5)
+            .iters_count(3)
+            .time_matrix(fixtures::time_matrix())
+            // No tour can enter city 9, the ants get there through the mask fallback
+            .rules((0..9).map(|city| format!("edge from {} to 9 : exclude", city)).collect())
+            .build();
+
+        let solutions = algorithm.run().unwrap();
+        set_seed(None);
+
+        assert!(solutions.is_empty());
+    }
+
+    #[test]
+    fn tours_of_equal_cost_and_time_are_ranked() {
+        // Every tour of three cities has the same length and time
+        let matrix: Matrix = vec![vec![0., 1., 2.], vec![1., 0., 3.], vec![2., 3., 0.]];
+        let time_matrix = vec![vec![0, 4, 5], vec![4, 0, 6], vec![5, 6, 0]];
+        set_seed(Some(1));
+        let algorithm = TSAntColonyAlgorithmBuilder::new(matrix)
+            .actors_count(3)
+            .iters_count(3)
+            .time_matrix(time_matrix)
+            .build();
+
+        let solutions = algorithm.run().unwrap();
+        set_seed(None);
+
+        assert!(!solutions.is_empty());
+        assert!(solutions.iter().all(|solution| solution.fitness == 0.), "{:?}", solutions);
+    }
+
+    #[test]
+    fn conflicting_fixed_positions_stay_rules() {
+        let algorithm = TSAntColonyAlgorithmBuilder::new(fixtures::matrix())
+            .rules(vec!["not 2 at position 1 : exclude".to_string(), "not 3 at position 1 : exclude".to_string()])
+            .build();
+
+        assert_eq!(algorithm.hard_rules.len(), 1);
+        assert_eq!(algorithm.rules.len(), 1);
+    }
 }
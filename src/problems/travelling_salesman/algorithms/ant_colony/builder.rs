@@ -3,15 +3,22 @@ use std::num::NonZeroUsize;
 use lru::LruCache;
 use crate::algorithms::ant_colony::algorithm::AntColonyAlgorithm;
 use crate::algorithms::constants::{ACTORS_COUNT, ITERS_COUNT, SOLUTIONS_COUNT};
-use crate::problems::travelling_salesman::rules::parse_rule;
-use crate::problems::travelling_salesman::types::{City, Matrix, RuleFn, RuleStr, TimeMatrix};
+use crate::problems::travelling_salesman::constraints::Constraints;
+use crate::problems::travelling_salesman::constructions::Construction;
+use crate::problems::travelling_salesman::helpers::is_permutation;
+use crate::problems::travelling_salesman::rules::{parse_rule, parse_rule_in, Language};
+use crate::problems::travelling_salesman::types::{City, CityNames, Matrix, RuleFn, RuleStr, TimeMatrix};
 use super::algorithm::TSAntColonyAlgorithm;
+use crate::algorithms::helpers::make_rng;
 
 pub struct TSAntColonyAlgorithmBuilder {
     matrix: Matrix,
     rules: Vec<RuleFn>,
+    hard_rules: Vec<RuleFn>,
+    constraints: Constraints,
     penalty_cache: RefCell<LruCache<Vec<City>, Option<f64>>>,
     time_matrix: Option<TimeMatrix>,
+    city_names: CityNames,
     actors_count: usize,
     iters_count: usize,
     solutions_count: usize,
@@ -19,6 +26,8 @@ pub struct TSAntColonyAlgorithmBuilder {
     q: f64,
     alpha: f64,
     beta: f64,
+    constructions: Vec<Construction>,
+    initial_solutions: Vec<Vec<City>>,
 }
 
 impl TSAntColonyAlgorithmBuilder {
@@ -30,15 +39,20 @@ impl TSAntColonyAlgorithmBuilder {
         Self {
             matrix,
             rules: Vec::new(),
+            hard_rules: Vec::new(),
+            constraints: Constraints::default(),
             penalty_cache: RefCell::new(LruCache::new(NonZeroUsize::new(300).unwrap())),
             actors_count: ACTORS_COUNT,
             iters_count: ITERS_COUNT,
             solutions_count: SOLUTIONS_COUNT,
             time_matrix: None,
+            city_names: Vec::new(),
             p: 1.,
             q: 1.,
             alpha: 1.,
-            beta: 1.
+            beta: 1.,
+            constructions: Vec::new(),
+            initial_solutions: Vec::new(),
         }
     }
 
@@ -86,17 +100,84 @@ impl TSAntColonyAlgorithmBuilder {
         self
     }
 
+    // Names let rules refer to cities by name, they must be set before the rules
+    pub fn city_names(mut self, city_names: CityNames) -> Self {
+        if city_names.len() != self.matrix.len() {
+            panic!("City names count is not equal distance matrix size")
+        }
+        if city_names.iter().enumerate().any(|(idx, name)| city_names[..idx].contains(name)) {
+            panic!("City names must be unique")
+        }
+
+        self.city_names = city_names;
+        self
+    }
+
+    // Ants do not go to the cities the hard constraints among the rules forbid,
+    // the rules of the constraints are checked on complete tours only
     pub fn rules(mut self, rules: Vec<RuleStr>) -> Self {
-        self.rules = rules.into_iter().map(|rule_str| {
-            parse_rule(rule_str, self.matrix.clone(), self.time_matrix.clone())
-        }).collect();
+        let (constraints, hard_rules, rules) = Constraints::partition_rules(rules, None, &self.city_names, self.matrix.len());
+        self.constraints = constraints;
+        let parse = |rule_str: RuleStr| {
+            parse_rule(rule_str, &self.city_names, self.matrix.clone(), self.time_matrix.clone())
+                .unwrap_or_else(|error| panic!("{}", error))
+        };
+        self.hard_rules = hard_rules.into_iter().map(parse).collect();
+        self.rules = rules.into_iter().map(parse).collect();
+
+        self
+    }
+
+    // Same as rules, but the language is given for the whole set instead of being detected
+    pub fn rules_in(mut self, rules: Vec<RuleStr>, language: Language) -> Self {
+        let (constraints, hard_rules, rules) = Constraints::partition_rules(rules, Some(language), &self.city_names, self.matrix.len());
+        self.constraints = constraints;
+        let parse = |rule_str: RuleStr| {
+            parse_rule_in(rule_str, language, &self.city_names, self.matrix.clone(), self.time_matrix.clone())
+                .unwrap_or_else(|error| panic!("{}", error))
+        };
+        self.hard_rules = hard_rules.into_iter().map(parse).collect();
+        self.rules = rules.into_iter().map(parse).collect();
 
         self
     }
 
+    // Constructed tours seed the best-so-far solutions and the initial pheromone trail
+    pub fn constructions(mut self, constructions: Vec<Construction>) -> Self {
+        self.constructions = constructions;
+        self
+    }
+
+    // Initial solutions are best-so-far from the start and seed pheromone along their edges
+    pub fn initial_solutions(mut self, initial_solutions: Vec<Vec<City>>) -> Self {
+        if !initial_solutions.iter().all(|path| is_permutation(path, self.matrix.len())) {
+            panic!("Initial solution is not a permutation of cities")
+        }
+
+        self.initial_solutions = initial_solutions;
+        self
+    }
+
     pub fn build(self) -> TSAntColonyAlgorithm {
+        let mut rng = make_rng();
+        let mut initial_paths = self.initial_solutions;
+        initial_paths.extend(self.constructions
+            .iter()
+            .map(|construction| self.constraints.repair(construction.build(&self.matrix, &mut rng))));
+
+        let min_edge_cost = self.matrix
+            .iter()
+            .flatten()
+            .cloned()
+            .filter(|cost| *cost > 0.)
+            .fold(f64::INFINITY, f64::min);
+
         TSAntColonyAlgorithm {
+            initial_paths,
+            min_edge_cost: if min_edge_cost.is_finite() { min_edge_cost } else { 1. },
             rules: self.rules,
+            hard_rules: self.hard_rules,
+            constraints: self.constraints,
             penalty_cache: self.penalty_cache,
             time_matrix: self.time_matrix,
             algo: AntColonyAlgorithm {
@@ -1,17 +1,80 @@
-use crate::problems::travelling_salesman::types::{City};
+use crate::problems::travelling_salesman::types::{City, RuleFitness};
 use crate::algorithms::bee_colony::algorithm::BeeColonyAlgorithm;
-use crate::problems::travelling_salesman::helpers::make_solutions;
+use crate::problems::travelling_salesman::helpers::{make_solution, make_solutions};
 use crate::problems::travelling_salesman::solution::Solution;
+use crate::problems::travelling_salesman::solver::Solver;
 
 pub struct TSBeeColonyAlgorithm {
     pub algo: BeeColonyAlgorithm<City>,
+    // Solutions get the length of the tour and the penalties apart
+    pub rule_fitness: RuleFitness,
 }
 
 impl TSBeeColonyAlgorithm {
     pub fn run(&self) -> Result<Vec<Solution>, &'static str> {
-        let population = self.algo.run().unwrap();
-        let solutions = make_solutions(population, self.algo.solutions_count, &self.algo.fitness_funcs);
+        self.run_with(Vec::new())
+    }
+
+    pub fn run_with(&self, initial_paths: Vec<Vec<City>>) -> Result<Vec<Solution>, &'static str> {
+        self.run_observed(initial_paths, &mut |_, _| true)
+    }
+
+    pub fn run_observed(
+        &self,
+        initial_paths: Vec<Vec<City>>,
+        observer: &mut dyn FnMut(usize, &Solution) -> bool,
+    ) -> Result<Vec<Solution>, &'static str> {
+        let population = self.algo.run_observed(initial_paths, &mut |iter, best| {
+            make_solution(best, &self.rule_fitness).is_none_or(|solution| observer(iter, &solution))
+        })?;
+        let solutions = make_solutions(population, self.algo.solutions_count, &self.rule_fitness);
 
         Ok(solutions)
     }
-}
\ No newline at end of file
+}
+
+impl Solver for TSBeeColonyAlgorithm {
+    fn name(&self) -> &'static str {
+        "bee_colony"
+    }
+
+    fn parameters(&self) -> String {
+        format!(
+            "actors_count: {}, iters_count: {}, solutions_count: {}, workers_part: {}",
+            self.algo.actors_count, self.algo.iters_count, self.algo.solutions_count, self.algo.workers_part
+        )
+    }
+
+    fn run_with_observer(&self, observer: &mut dyn FnMut(usize, &Solution) -> bool) -> Result<Vec<Solution>, &'static str> {
+        self.run_observed(Vec::new(), observer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithms::bee_colony::research_methods;
+    use crate::problems::travelling_salesman::exact::held_karp;
+    use crate::problems::travelling_salesman::fixtures;
+    use super::super::builder::TSBeeColonyAlgorithmBuilder;
+
+    fn builder() -> TSBeeColonyAlgorithmBuilder {
+        TSBeeColonyAlgorithmBuilder::new(fixtures::matrix(), research_methods::swap_indexes(None))
+            .actors_count(10)
+            .iters_count(3)
+    }
+
+    #[test]
+    fn initial_solutions_are_kept() {
+        let optimum = held_karp(&fixtures::matrix()).unwrap().path;
+
+        let solutions = builder().initial_solutions(vec![optimum]).build().run().unwrap();
+
+        assert_eq!(solutions[0].cost(), fixtures::OPTIMUM);
+    }
+
+    #[test]
+    #[should_panic(expected = "not a permutation")]
+    fn initial_solutions_must_be_permutations() {
+        builder().initial_solutions(vec![(0..9).collect()]);
+    }
+}
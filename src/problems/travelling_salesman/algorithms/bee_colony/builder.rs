@@ -1,25 +1,31 @@
-use rand::prelude::SliceRandom;
-use rand::thread_rng;
 use crate::algorithms::bee_colony::{
     algorithm::BeeColonyAlgorithm,
     types::{GenerateFuncRaw, ResearchFunction},
 };
 use crate::algorithms::constants::{ACTORS_COUNT, ITERS_COUNT, SOLUTIONS_COUNT};
-use crate::algorithms::types::{FitnessFuncRaw, Purpose};
-use crate::problems::travelling_salesman::helpers::{calculate_distance_with_rules, time_fitness};
-use crate::problems::travelling_salesman::rules::parse_rule;
-use crate::problems::travelling_salesman::types::{Matrix, City, RuleFn, RuleStr, TimeMatrix};
+use crate::algorithms::types::Purpose;
+use crate::problems::travelling_salesman::constraints::Constraints;
+use crate::problems::travelling_salesman::constructions::{generate_tour, Construction};
+use crate::problems::travelling_salesman::helpers::{is_permutation, make_fitness_funcs, rule_fitness};
+use crate::problems::travelling_salesman::rules::{parse_rule, parse_rule_in, Language};
+use crate::problems::travelling_salesman::types::{Matrix, City, CityNames, RuleFn, RuleStr, TimeMatrix};
 use super::algorithm::TSBeeColonyAlgorithm;
+use crate::algorithms::helpers::make_rng;
 
 pub struct TSBeeColonyAlgorithmBuilder {
     pub matrix: Matrix,
     pub rules: Vec<RuleFn>,
+    pub constraints: Constraints,
     pub time_matrix: Option<TimeMatrix>,
+    pub city_names: CityNames,
     pub actors_count: usize,
     pub iters_count: usize,
     pub solutions_count: usize,
     pub workers_part: f32,
     pub research_func: ResearchFunction<City>,
+    pub constructions: Vec<Construction>,
+    pub constructions_part: f32,
+    pub initial_solutions: Vec<Vec<City>>,
 }
 
 impl TSBeeColonyAlgorithmBuilder {
@@ -30,12 +36,17 @@ impl TSBeeColonyAlgorithmBuilder {
         Self {
             matrix,
             time_matrix: None,
+            city_names: Vec::new(),
             rules: vec![],
+            constraints: Constraints::default(),
             actors_count: ACTORS_COUNT,
             iters_count: ITERS_COUNT,
             solutions_count: SOLUTIONS_COUNT,
             workers_part: 0.7,
             research_func,
+            constructions: Vec::new(),
+            constructions_part: 0.,
+            initial_solutions: Vec::new(),
         }
     }
 
@@ -62,9 +73,23 @@ impl TSBeeColonyAlgorithmBuilder {
         self
     }
 
+    // Hard constraints among the rules are kept by repairing generated and researched food sources
     pub fn rules(mut self, rules: Vec<RuleStr>) -> Self {
+        self.constraints = Constraints::from_rules(&rules, None, &self.city_names, self.matrix.len());
         self.rules = rules.into_iter().map(|rule_str| {
-            parse_rule(rule_str, self.matrix.clone(), self.time_matrix.clone())
+            parse_rule(rule_str, &self.city_names, self.matrix.clone(), self.time_matrix.clone())
+                .unwrap_or_else(|error| panic!("{}", error))
+        }).collect();
+
+        self
+    }
+
+    // Same as rules, but the language is given for the whole set instead of being detected
+    pub fn rules_in(mut self, rules: Vec<RuleStr>, language: Language) -> Self {
+        self.constraints = Constraints::from_rules(&rules, Some(language), &self.city_names, self.matrix.len());
+        self.rules = rules.into_iter().map(|rule_str| {
+            parse_rule_in(rule_str, language, &self.city_names, self.matrix.clone(), self.time_matrix.clone())
+                .unwrap_or_else(|error| panic!("{}", error))
         }).collect();
 
         self
@@ -79,25 +104,52 @@ impl TSBeeColonyAlgorithmBuilder {
         self
     }
 
-    pub fn build(self) -> TSBeeColonyAlgorithm {
-        let cities_count = self.matrix.len();
+    // Names let rules refer to cities by name, they must be set before the rules
+    pub fn city_names(mut self, city_names: CityNames) -> Self {
+        if city_names.len() != self.matrix.len() {
+            panic!("City names count is not equal distance matrix size")
+        }
+        if city_names.iter().enumerate().any(|(idx, name)| city_names[..idx].contains(name)) {
+            panic!("City names must be unique")
+        }
+
+        self.city_names = city_names;
+        self
+    }
+
+    // Each generated tour is built by one of the constructions with `part` probability, others are random
+    pub fn constructions(mut self, constructions: Vec<Construction>, part: f32) -> Self {
+        if !(0. ..=1.).contains(&part) {
+            panic!("Value must be 0 <= part <= 1")
+        }
 
-        let mut fitness_funcs = vec![
-            Box::new(calculate_distance_with_rules(self.matrix, self.rules)) as FitnessFuncRaw<City>,
-        ];
+        self.constructions = constructions;
+        self.constructions_part = part;
+        self
+    }
 
-        if let Some(time_matrix) = self.time_matrix {
-            fitness_funcs.push(time_fitness(time_matrix))
+    pub fn initial_solutions(mut self, initial_solutions: Vec<Vec<City>>) -> Self {
+        if !initial_solutions.iter().all(|path| is_permutation(path, self.matrix.len())) {
+            panic!("Initial solution is not a permutation of cities")
         }
 
+        self.initial_solutions = initial_solutions;
+        self
+    }
+
+    pub fn build(self) -> TSBeeColonyAlgorithm {
+        let matrix = self.matrix.clone();
+        let (constructions, part) = (self.constructions, self.constructions_part);
+
+        let rule_fitness = rule_fitness(self.matrix.clone(), self.rules);
+        let fitness_funcs = make_fitness_funcs(&rule_fitness, self.time_matrix);
+
         let generate_func: GenerateFuncRaw<City> = Box::new(move || {
-            let mut rng = thread_rng();
-            let mut value: Vec<usize> = (0..cities_count).collect();
-            value.shuffle(&mut rng);
-            value
+            generate_tour(&matrix, &constructions, part, &mut make_rng())
         });
 
         TSBeeColonyAlgorithm {
+            rule_fitness,
             algo: BeeColonyAlgorithm {
                 actors_count: self.actors_count,
                 iters_count: self.iters_count,
@@ -105,8 +157,9 @@ impl TSBeeColonyAlgorithmBuilder {
                 workers_part: self.workers_part,
                 purpose: Purpose::Min,
                 fitness_funcs,
-                research_func: self.research_func,
-                generate_func: Box::new(generate_func),
+                research_func: self.constraints.repair_research(self.research_func),
+                generate_func: self.constraints.repair_source(generate_func),
+                initial_values: self.initial_solutions,
             }
         }
     }
@@ -1,21 +1,93 @@
 use crate::algorithms::genetic::algorithm::GeneticAlgorithm;
-use crate::problems::travelling_salesman::helpers::make_solutions;
+use crate::problems::travelling_salesman::helpers::{make_solution, make_solutions};
 use crate::problems::travelling_salesman::solution::Solution;
-use crate::problems::travelling_salesman::types::City;
+use crate::problems::travelling_salesman::solver::Solver;
+use crate::problems::travelling_salesman::types::{City, RuleFitness};
 
 pub struct TSGeneticAlgorithm {
     pub algo: GeneticAlgorithm<City>,
+    // Solutions get the length of the tour and the penalties apart
+    pub rule_fitness: RuleFitness,
 }
 
 impl TSGeneticAlgorithm {
     pub fn run(&self) -> Result<Vec<Solution>, &'static str> {
-        let population = self.algo.run().unwrap();
+        self.run_with(Vec::new())
+    }
+
+    pub fn run_with(&self, initial_paths: Vec<Vec<City>>) -> Result<Vec<Solution>, &'static str> {
+        self.run_observed(initial_paths, &mut |_, _| true)
+    }
+
+    pub fn run_observed(
+        &self,
+        initial_paths: Vec<Vec<City>>,
+        observer: &mut dyn FnMut(usize, &Solution) -> bool,
+    ) -> Result<Vec<Solution>, &'static str> {
+        let population = self.algo.run_observed(initial_paths, &mut |iter, best| {
+            make_solution(best, &self.rule_fitness).is_none_or(|solution| observer(iter, &solution))
+        })?;
         let solutions: Vec<Solution> = make_solutions(
             population,
             self.algo.solutions_count,
-            &self.algo.fitness_funcs,
+            &self.rule_fitness
         );
 
         Ok(solutions)
     }
 }
+
+impl Solver for TSGeneticAlgorithm {
+    fn name(&self) -> &'static str {
+        "genetic"
+    }
+
+    fn parameters(&self) -> String {
+        format!(
+            "actors_count: {}, iters_count: {}, solutions_count: {}, p_mutation: {}",
+            self.algo.actors_count, self.algo.iters_count, self.algo.solutions_count, self.algo.p_mutation
+        )
+    }
+
+    fn run_with_observer(&self, observer: &mut dyn FnMut(usize, &Solution) -> bool) -> Result<Vec<Solution>, &'static str> {
+        self.run_observed(Vec::new(), observer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithms::genetic::methods::{Mutate, Select};
+    use crate::problems::travelling_salesman::exact::held_karp;
+    use crate::problems::travelling_salesman::fixtures;
+    use super::super::builder::TSGeneticAlgorithmBuilder;
+
+    fn builder() -> TSGeneticAlgorithmBuilder {
+        TSGeneticAlgorithmBuilder::new(fixtures::matrix(), Mutate::swap_indexes(None), Select::tournament(3, None))
+            .actors_count(10)
+            .iters_count(3)
+    }
+
+    #[test]
+    fn initial_solutions_are_kept() {
+        let optimum = held_karp(&fixtures::matrix()).unwrap().path;
+
+        let solutions = builder().initial_solutions(vec![optimum]).build().run().unwrap();
+
+        assert_eq!(solutions[0].cost(), fixtures::OPTIMUM);
+    }
+
+    #[test]
+    fn given_paths_are_kept() {
+        let optimum = held_karp(&fixtures::matrix()).unwrap().path;
+
+        let solutions = builder().build().run_with(vec![optimum]).unwrap();
+
+        assert_eq!(solutions[0].cost(), fixtures::OPTIMUM);
+    }
+
+    #[test]
+    #[should_panic(expected = "not a permutation")]
+    fn initial_solutions_must_be_permutations() {
+        builder().initial_solutions(vec![(0..9).collect()]);
+    }
+}
@@ -1,25 +1,38 @@
 use crate::algorithms::constants::{ACTORS_COUNT, ITERS_COUNT, SOLUTIONS_COUNT};
 use crate::algorithms::genetic::algorithm::GeneticAlgorithm;
-use crate::algorithms::genetic::types::{GenerateFunc, MutateFunc, SelectFunc};
+use crate::algorithms::genetic::types::{
+    FitnessSharing, GenerateFunc, MateFunc, MutateFunc, ReplaceFunc, SelectFunc,
+};
 use crate::problems::travelling_salesman::algorithms::genetic::algorithm::TSGeneticAlgorithm;
-use crate::problems::travelling_salesman::types::{City, Matrix, RuleFn, RuleStr, TimeMatrix};
-use rand::prelude::SliceRandom;
-use rand::rngs::ThreadRng;
-use crate::algorithms::genetic::methods::Crossover;
-use crate::algorithms::types::{FitnessFuncRaw, Purpose};
-use crate::problems::travelling_salesman::helpers::{calculate_distance_with_rules, time_fitness};
-use crate::problems::travelling_salesman::rules::parse_rule;
+use crate::problems::travelling_salesman::types::{City, CityNames, Matrix, RuleFn, RuleStr, TimeMatrix};
+use rand::rngs::StdRng;
+use crate::algorithms::genetic::methods::{Crossover, Distance, Equivalence, Mate, Replace};
+use crate::algorithms::types::Purpose;
+use crate::problems::travelling_salesman::constraints::Constraints;
+use crate::problems::travelling_salesman::constructions::{generate_tour, Construction};
+use crate::problems::travelling_salesman::helpers::{is_permutation, make_fitness_funcs, rule_fitness};
+use crate::problems::travelling_salesman::rules::{parse_rule, parse_rule_in, Language};
 
 pub struct TSGeneticAlgorithmBuilder {
     matrix: Matrix,
     time_matrix: Option<TimeMatrix>,
+    city_names: CityNames,
     actors_count: usize,
     iters_count: usize,
     solutions_count: usize,
     p_mutation: f32,
     mutate_func: MutateFunc<City>,
     select_func: SelectFunc<City>,
+    mate_func: MateFunc<City>,
+    replace_func: ReplaceFunc<City>,
     rules: Vec<RuleFn>,
+    constraints: Constraints,
+    eliminate_duplicates: bool,
+    sharing: Option<(f64, f64)>,
+    crowding: bool,
+    constructions: Vec<Construction>,
+    constructions_part: f32,
+    initial_solutions: Vec<Vec<City>>,
 }
 
 impl TSGeneticAlgorithmBuilder {
@@ -31,13 +44,23 @@ impl TSGeneticAlgorithmBuilder {
         Self {
             matrix,
             time_matrix: None,
+            city_names: Vec::new(),
             mutate_func,
             select_func,
+            mate_func: Mate::random(),
+            replace_func: Replace::plus(),
             actors_count: ACTORS_COUNT,
             solutions_count: SOLUTIONS_COUNT,
             iters_count: ITERS_COUNT,
             rules: Vec::new(),
+            constraints: Constraints::default(),
             p_mutation: 0.3,
+            eliminate_duplicates: false,
+            sharing: None,
+            crowding: false,
+            constructions: Vec::new(),
+            constructions_part: 0.,
+            initial_solutions: Vec::new(),
         }
     }
 
@@ -65,6 +88,19 @@ impl TSGeneticAlgorithmBuilder {
         self
     }
 
+    // Names let rules refer to cities by name, they must be set before the rules
+    pub fn city_names(mut self, city_names: CityNames) -> Self {
+        if city_names.len() != self.matrix.len() {
+            panic!("City names count is not equal distance matrix size")
+        }
+        if city_names.iter().enumerate().any(|(idx, name)| city_names[..idx].contains(name)) {
+            panic!("City names must be unique")
+        }
+
+        self.city_names = city_names;
+        self
+    }
+
     pub fn p_mutation(mut self, p_mutation: f32) -> Self {
         if p_mutation < 1. {
             self.p_mutation = p_mutation;
@@ -74,43 +110,122 @@ impl TSGeneticAlgorithmBuilder {
         }
     }
 
+    // Hard constraints among the rules are kept by repairing generated, crossed and mutated tours
     pub fn rules(mut self, rules: Vec<RuleStr>) -> Self {
+        self.constraints = Constraints::from_rules(&rules, None, &self.city_names, self.matrix.len());
         self.rules = rules.into_iter().map(|rule_str| {
-            parse_rule(rule_str, self.matrix.clone(), self.time_matrix.clone())
+            parse_rule(rule_str, &self.city_names, self.matrix.clone(), self.time_matrix.clone())
+                .unwrap_or_else(|error| panic!("{}", error))
         }).collect();
 
         self
     }
 
-    pub fn build(self) -> TSGeneticAlgorithm {
-        let cities_count = self.matrix.len();
+    // Same as rules, but the language is given for the whole set instead of being detected
+    pub fn rules_in(mut self, rules: Vec<RuleStr>, language: Language) -> Self {
+        self.constraints = Constraints::from_rules(&rules, Some(language), &self.city_names, self.matrix.len());
+        self.rules = rules.into_iter().map(|rule_str| {
+            parse_rule_in(rule_str, language, &self.city_names, self.matrix.clone(), self.time_matrix.clone())
+                .unwrap_or_else(|error| panic!("{}", error))
+        }).collect();
+
+        self
+    }
+
+    pub fn mate_func(mut self, mate_func: MateFunc<City>) -> Self {
+        self.mate_func = mate_func;
+        self
+    }
+
+    pub fn replace_func(mut self, replace_func: ReplaceFunc<City>) -> Self {
+        self.replace_func = replace_func;
+        self
+    }
+
+    pub fn eliminate_duplicates(mut self, eliminate_duplicates: bool) -> Self {
+        self.eliminate_duplicates = eliminate_duplicates;
+        self
+    }
+
+    pub fn fitness_sharing(mut self, sigma: f64, alpha: f64) -> Self {
+        if sigma <= 0. || sigma > 1. {
+            panic!("Value must be 0 < sigma <= 1")
+        }
+        if alpha <= 0. {
+            panic!("Value must be alpha > 0")
+        }
+
+        self.sharing = Some((sigma, alpha));
+        self
+    }
+
+    pub fn crowding(mut self, crowding: bool) -> Self {
+        self.crowding = crowding;
+        self
+    }
 
-        let mut fitness_funcs = vec![
-            Box::new(calculate_distance_with_rules(self.matrix, self.rules)) as FitnessFuncRaw<City>,
-        ];
+    // Each generated tour is built by one of the constructions with `part` probability, others are random
+    pub fn constructions(mut self, constructions: Vec<Construction>, part: f32) -> Self {
+        if !(0. ..=1.).contains(&part) {
+            panic!("Value must be 0 <= part <= 1")
+        }
 
-        if let Some(time_matrix) = self.time_matrix {
-            fitness_funcs.push(time_fitness(time_matrix))
+        self.constructions = constructions;
+        self.constructions_part = part;
+        self
+    }
+
+    pub fn initial_solutions(mut self, initial_solutions: Vec<Vec<City>>) -> Self {
+        if !initial_solutions.iter().all(|path| is_permutation(path, self.matrix.len())) {
+            panic!("Initial solution is not a permutation of cities")
         }
 
-        let generate_func: GenerateFunc<City> = GenerateFunc(Box::new(move |rng: &mut ThreadRng| {
-            let mut value: Vec<usize> = (0..cities_count).collect();
-            value.shuffle(rng);
-            value
+        self.initial_solutions = initial_solutions;
+        self
+    }
+
+    pub fn build(self) -> TSGeneticAlgorithm {
+        let matrix = self.matrix.clone();
+        let (constructions, part) = (self.constructions, self.constructions_part);
+
+        let rule_fitness = rule_fitness(self.matrix.clone(), self.rules);
+        let fitness_funcs = make_fitness_funcs(&rule_fitness, self.time_matrix);
+
+        let generate_func: GenerateFunc<City> = GenerateFunc(Box::new(move |rng: &mut StdRng| {
+            generate_tour(&matrix, &constructions, part, rng)
         }));
 
         TSGeneticAlgorithm {
+            rule_fitness,
             algo: GeneticAlgorithm {
                 fitness_funcs,
-                generate_func,
+                generate_func: self.constraints.repair_generate(generate_func),
                 purpose: Purpose::Min,
                 actors_count: self.actors_count,
                 iters_count: self.iters_count,
                 solutions_count: self.solutions_count,
                 p_mutation: self.p_mutation,
-                crossover_func: Crossover::ordered(),
-                mutate_func: self.mutate_func,
+                crossover_func: self.constraints.repair_crossover(Crossover::ordered()),
+                mutate_func: self.constraints.repair_mutate(self.mutate_func),
                 select_func: self.select_func,
+                mate_func: self.mate_func,
+                replace_func: self.replace_func,
+                duplicates_func: if self.eliminate_duplicates {
+                    Some(Equivalence::tour())
+                } else {
+                    None
+                },
+                sharing: self.sharing.map(|(sigma, alpha)| FitnessSharing {
+                    distance_func: Distance::shared_edges(),
+                    sigma,
+                    alpha,
+                }),
+                initial_values: self.initial_solutions,
+                crowding_func: if self.crowding {
+                    Some(Distance::shared_edges())
+                } else {
+                    None
+                },
             }
         }
     }
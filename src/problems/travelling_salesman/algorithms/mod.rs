@@ -1,3 +1,6 @@
 pub mod genetic;
 pub mod bee_colony;
-pub mod ant_colony;
\ No newline at end of file
+pub mod ant_colony;
+pub mod simulated_annealing;
+pub mod tabu_search;
+pub mod particle_swarm;
\ No newline at end of file
@@ -0,0 +1,65 @@
+use crate::algorithms::particle_swarm::algorithm::ParticleSwarmAlgorithm;
+use crate::problems::travelling_salesman::helpers::{make_solution, make_solutions};
+use crate::problems::travelling_salesman::solution::Solution;
+use crate::problems::travelling_salesman::solver::Solver;
+use crate::problems::travelling_salesman::types::{City, RuleFitness};
+
+pub struct TSParticleSwarmAlgorithm {
+    pub algo: ParticleSwarmAlgorithm<City>,
+    // Solutions get the length of the tour and the penalties apart
+    pub rule_fitness: RuleFitness,
+}
+
+impl TSParticleSwarmAlgorithm {
+    pub fn run(&self) -> Result<Vec<Solution>, &'static str> {
+        self.run_observed(&mut |_, _| true)
+    }
+
+    pub fn run_observed(&self, observer: &mut dyn FnMut(usize, &Solution) -> bool) -> Result<Vec<Solution>, &'static str> {
+        let population = self.algo.run_observed(&mut |iter, best| {
+            make_solution(best, &self.rule_fitness).is_none_or(|solution| observer(iter, &solution))
+        })?;
+        let solutions = make_solutions(population, self.algo.solutions_count, &self.rule_fitness);
+
+        Ok(solutions)
+    }
+}
+
+impl Solver for TSParticleSwarmAlgorithm {
+    fn name(&self) -> &'static str {
+        "particle_swarm"
+    }
+
+    fn parameters(&self) -> String {
+        format!(
+            "actors_count: {}, iters_count: {}, solutions_count: {}, inertia: {}, cognitive: {}, social: {}",
+            self.algo.actors_count, self.algo.iters_count, self.algo.solutions_count,
+            self.algo.inertia, self.algo.cognitive, self.algo.social
+        )
+    }
+
+    fn run_with_observer(&self, observer: &mut dyn FnMut(usize, &Solution) -> bool) -> Result<Vec<Solution>, &'static str> {
+        self.run_observed(observer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::problems::travelling_salesman::fixtures;
+    use crate::problems::travelling_salesman::helpers::is_permutation;
+    use super::super::builder::TSParticleSwarmAlgorithmBuilder;
+
+    #[test]
+    fn solutions_are_distinct_tours() {
+        let matrix = fixtures::matrix();
+        let algorithm = TSParticleSwarmAlgorithmBuilder::new(matrix.clone()).actors_count(20).build();
+
+        let solutions = algorithm.run().unwrap();
+
+        assert!(!solutions.is_empty());
+        for (idx, solution) in solutions.iter().enumerate() {
+            assert!(is_permutation(&solution.path, matrix.len()));
+            assert!(solutions[..idx].iter().all(|other| other.path != solution.path));
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use rand::prelude::SliceRandom;
+use rand::rngs::StdRng;
+use crate::algorithms::constants::{ACTORS_COUNT, ITERS_COUNT, SOLUTIONS_COUNT};
+use crate::algorithms::genetic::types::GenerateFunc;
+use crate::algorithms::particle_swarm::algorithm::ParticleSwarmAlgorithm;
+use crate::algorithms::types::Purpose;
+use crate::problems::travelling_salesman::helpers::{make_fitness_funcs, rule_fitness};
+use crate::problems::travelling_salesman::rules::{parse_rule, parse_rule_in, Language};
+use crate::problems::travelling_salesman::types::{City, CityNames, Matrix, RuleFn, RuleStr, TimeMatrix};
+use super::algorithm::TSParticleSwarmAlgorithm;
+
+pub struct TSParticleSwarmAlgorithmBuilder {
+    matrix: Matrix,
+    time_matrix: Option<TimeMatrix>,
+    city_names: CityNames,
+    actors_count: usize,
+    iters_count: usize,
+    solutions_count: usize,
+    inertia: f64,
+    cognitive: f64,
+    social: f64,
+    rules: Vec<RuleFn>,
+}
+
+impl TSParticleSwarmAlgorithmBuilder {
+    pub fn new(matrix: Matrix) -> Self {
+        Self {
+            matrix,
+            time_matrix: None,
+            city_names: Vec::new(),
+            actors_count: ACTORS_COUNT,
+            iters_count: ITERS_COUNT,
+            solutions_count: SOLUTIONS_COUNT,
+            inertia: 0.5,
+            cognitive: 1.,
+            social: 1.,
+            rules: Vec::new(),
+        }
+    }
+
+    pub fn actors_count(mut self, actors_count: usize) -> Self {
+        self.actors_count = actors_count;
+        self
+    }
+
+    pub fn iters_count(mut self, iters_count: usize) -> Self {
+        self.iters_count = iters_count;
+        self
+    }
+
+    pub fn solutions_count(mut self, solutions_count: usize) -> Self {
+        self.solutions_count = solutions_count;
+        self
+    }
+
+    pub fn inertia(mut self, inertia: f64) -> Self {
+        if !(0. ..1.).contains(&inertia) {
+            panic!("Value must be 0 <= inertia < 1")
+        }
+
+        self.inertia = inertia;
+        self
+    }
+
+    pub fn cognitive(mut self, cognitive: f64) -> Self {
+        if cognitive < 0. {
+            panic!("Value must be cognitive >= 0")
+        }
+
+        self.cognitive = cognitive;
+        self
+    }
+
+    pub fn social(mut self, social: f64) -> Self {
+        if social < 0. {
+            panic!("Value must be social >= 0")
+        }
+
+        self.social = social;
+        self
+    }
+
+    pub fn time_matrix(mut self, time_matrix: TimeMatrix) -> Self {
+        if time_matrix.len() != self.matrix.len() {
+            panic!("Time matrix size is not equal distance matrix")
+        }
+
+        self.time_matrix = Some(time_matrix);
+        self
+    }
+
+    // Names let rules refer to cities by name, they must be set before the rules
+    pub fn city_names(mut self, city_names: CityNames) -> Self {
+        if city_names.len() != self.matrix.len() {
+            panic!("City names count is not equal distance matrix size")
+        }
+        if city_names.iter().enumerate().any(|(idx, name)| city_names[..idx].contains(name)) {
+            panic!("City names must be unique")
+        }
+
+        self.city_names = city_names;
+        self
+    }
+
+    pub fn rules(mut self, rules: Vec<RuleStr>) -> Self {
+        self.rules = rules.into_iter().map(|rule_str| {
+            parse_rule(rule_str, &self.city_names, self.matrix.clone(), self.time_matrix.clone())
+                .unwrap_or_else(|error| panic!("{}", error))
+        }).collect();
+
+        self
+    }
+
+    // Same as rules, but the language is given for the whole set instead of being detected
+    pub fn rules_in(mut self, rules: Vec<RuleStr>, language: Language) -> Self {
+        self.rules = rules.into_iter().map(|rule_str| {
+            parse_rule_in(rule_str, language, &self.city_names, self.matrix.clone(), self.time_matrix.clone())
+                .unwrap_or_else(|error| panic!("{}", error))
+        }).collect();
+
+        self
+    }
+
+    pub fn build(self) -> TSParticleSwarmAlgorithm {
+        let cities_count = self.matrix.len();
+        let rule_fitness = rule_fitness(self.matrix.clone(), self.rules);
+        let fitness_funcs = make_fitness_funcs(&rule_fitness, self.time_matrix);
+
+        let generate_func: GenerateFunc<City> = GenerateFunc(Box::new(move |rng: &mut StdRng| {
+            let mut value: Vec<usize> = (0..cities_count).collect();
+            value.shuffle(rng);
+            value
+        }));
+
+        TSParticleSwarmAlgorithm {
+            rule_fitness,
+            algo: ParticleSwarmAlgorithm {
+                fitness_funcs,
+                generate_func,
+                purpose: Purpose::Min,
+                actors_count: self.actors_count,
+                iters_count: self.iters_count,
+                solutions_count: self.solutions_count,
+                inertia: self.inertia,
+                cognitive: self.cognitive,
+                social: self.social,
+            }
+        }
+    }
+}
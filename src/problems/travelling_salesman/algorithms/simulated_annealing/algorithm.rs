@@ -0,0 +1,70 @@
+use crate::algorithms::simulated_annealing::algorithm::SimulatedAnnealingAlgorithm;
+use crate::problems::travelling_salesman::helpers::{make_solution, make_solutions};
+use crate::problems::travelling_salesman::solution::Solution;
+use crate::problems::travelling_salesman::solver::Solver;
+use crate::problems::travelling_salesman::types::{City, RuleFitness};
+
+pub struct TSSimulatedAnnealingAlgorithm {
+    pub algo: SimulatedAnnealingAlgorithm<City>,
+    // Solutions get the length of the tour and the penalties apart
+    pub rule_fitness: RuleFitness,
+}
+
+impl TSSimulatedAnnealingAlgorithm {
+    pub fn run(&self) -> Result<Vec<Solution>, &'static str> {
+        self.run_observed(&mut |_, _| true)
+    }
+
+    pub fn run_observed(&self, observer: &mut dyn FnMut(usize, &Solution) -> bool) -> Result<Vec<Solution>, &'static str> {
+        let population = self.algo.run_observed(&mut |iter, best| {
+            make_solution(best, &self.rule_fitness).is_none_or(|solution| observer(iter, &solution))
+        })?;
+        let solutions = make_solutions(population, self.algo.solutions_count, &self.rule_fitness);
+
+        Ok(solutions)
+    }
+}
+
+impl Solver for TSSimulatedAnnealingAlgorithm {
+    fn name(&self) -> &'static str {
+        "simulated_annealing"
+    }
+
+    fn parameters(&self) -> String {
+        format!(
+            "iters_count: {}, moves_count: {}, solutions_count: {}, temperature: {:?}, temperature_min: {}, cooling: {:?}",
+            self.algo.iters_count, self.algo.moves_count, self.algo.solutions_count,
+            self.algo.temperature, self.algo.temperature_min, self.algo.cooling
+        )
+    }
+
+    fn run_with_observer(&self, observer: &mut dyn FnMut(usize, &Solution) -> bool) -> Result<Vec<Solution>, &'static str> {
+        self.run_observed(observer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithms::genetic::methods::Mutate;
+    use crate::algorithms::simulated_annealing::types::Cooling;
+    use crate::problems::travelling_salesman::fixtures;
+    use crate::problems::travelling_salesman::helpers::{calculate_distance, is_permutation};
+    use super::super::builder::TSSimulatedAnnealingAlgorithmBuilder;
+
+    #[test]
+    fn solutions_are_tours_sorted_by_distance() {
+        let matrix = fixtures::matrix();
+        let algorithm = TSSimulatedAnnealingAlgorithmBuilder::new(matrix.clone(), Mutate::reverse_elements(None))
+            .cooling(Cooling::Geometric(0.9))
+            .build();
+
+        let solutions = algorithm.run().unwrap();
+
+        assert!(!solutions.is_empty());
+        for solution in solutions.iter() {
+            assert!(is_permutation(&solution.path, matrix.len()));
+            assert_eq!(solution.distance, calculate_distance(&matrix, &solution.path));
+        }
+        assert!(solutions.windows(2).all(|pair| pair[0].distance <= pair[1].distance));
+    }
+}
@@ -0,0 +1,166 @@
+use rand::prelude::SliceRandom;
+use rand::rngs::StdRng;
+use crate::algorithms::constants::{ITERS_COUNT, MOVES_COUNT, SOLUTIONS_COUNT};
+use crate::algorithms::genetic::types::{GenerateFunc, MutateFunc};
+use crate::algorithms::simulated_annealing::algorithm::SimulatedAnnealingAlgorithm;
+use crate::algorithms::simulated_annealing::types::Cooling;
+use crate::algorithms::types::Purpose;
+use crate::problems::travelling_salesman::helpers::{make_fitness_funcs, rule_fitness};
+use crate::problems::travelling_salesman::rules::{parse_rule, parse_rule_in, Language};
+use crate::problems::travelling_salesman::types::{City, CityNames, Matrix, RuleFn, RuleStr, TimeMatrix};
+use super::algorithm::TSSimulatedAnnealingAlgorithm;
+
+pub struct TSSimulatedAnnealingAlgorithmBuilder {
+    matrix: Matrix,
+    time_matrix: Option<TimeMatrix>,
+    city_names: CityNames,
+    iters_count: usize,
+    moves_count: usize,
+    solutions_count: usize,
+    temperature: Option<f64>,
+    temperature_min: f64,
+    initial_acceptance: f64,
+    cooling: Cooling,
+    neighbour_func: MutateFunc<City>,
+    rules: Vec<RuleFn>,
+}
+
+impl TSSimulatedAnnealingAlgorithmBuilder {
+    pub fn new(matrix: Matrix, neighbour_func: MutateFunc<City>) -> Self {
+        Self {
+            matrix,
+            time_matrix: None,
+            city_names: Vec::new(),
+            neighbour_func,
+            iters_count: ITERS_COUNT,
+            moves_count: MOVES_COUNT,
+            solutions_count: SOLUTIONS_COUNT,
+            temperature: None,
+            temperature_min: 1e-3,
+            initial_acceptance: 0.8,
+            cooling: Cooling::Geometric(0.95),
+            rules: Vec::new(),
+        }
+    }
+
+    pub fn iters_count(mut self, iters_count: usize) -> Self {
+        self.iters_count = iters_count;
+        self
+    }
+
+    pub fn moves_count(mut self, moves_count: usize) -> Self {
+        self.moves_count = moves_count;
+        self
+    }
+
+    pub fn solutions_count(mut self, solutions_count: usize) -> Self {
+        self.solutions_count = solutions_count;
+        self
+    }
+
+    pub fn temperature(mut self, temperature: f64) -> Self {
+        if temperature <= 0. {
+            panic!("Value must be temperature > 0")
+        }
+
+        self.temperature = Some(temperature);
+        self
+    }
+
+    pub fn temperature_min(mut self, temperature_min: f64) -> Self {
+        if temperature_min <= 0. {
+            panic!("Value must be temperature_min > 0")
+        }
+
+        self.temperature_min = temperature_min;
+        self
+    }
+
+    pub fn initial_acceptance(mut self, initial_acceptance: f64) -> Self {
+        if initial_acceptance <= 0. || initial_acceptance >= 1. {
+            panic!("Value must be 0 < initial_acceptance < 1")
+        }
+
+        self.initial_acceptance = initial_acceptance;
+        self
+    }
+
+    pub fn cooling(mut self, cooling: Cooling) -> Self {
+        if !cooling.is_valid() {
+            panic!("Cooling parameters are not correct: {:?}", cooling)
+        }
+
+        self.cooling = cooling;
+        self
+    }
+
+    pub fn time_matrix(mut self, time_matrix: TimeMatrix) -> Self {
+        if time_matrix.len() != self.matrix.len() {
+            panic!("Time matrix size is not equal distance matrix")
+        }
+
+        self.time_matrix = Some(time_matrix);
+        self
+    }
+
+    // Names let rules refer to cities by name, they must be set before the rules
+    pub fn city_names(mut self, city_names: CityNames) -> Self {
+        if city_names.len() != self.matrix.len() {
+            panic!("City names count is not equal distance matrix size")
+        }
+        if city_names.iter().enumerate().any(|(idx, name)| city_names[..idx].contains(name)) {
+            panic!("City names must be unique")
+        }
+
+        self.city_names = city_names;
+        self
+    }
+
+    pub fn rules(mut self, rules: Vec<RuleStr>) -> Self {
+        self.rules = rules.into_iter().map(|rule_str| {
+            parse_rule(rule_str, &self.city_names, self.matrix.clone(), self.time_matrix.clone())
+                .unwrap_or_else(|error| panic!("{}", error))
+        }).collect();
+
+        self
+    }
+
+    // Same as rules, but the language is given for the whole set instead of being detected
+    pub fn rules_in(mut self, rules: Vec<RuleStr>, language: Language) -> Self {
+        self.rules = rules.into_iter().map(|rule_str| {
+            parse_rule_in(rule_str, language, &self.city_names, self.matrix.clone(), self.time_matrix.clone())
+                .unwrap_or_else(|error| panic!("{}", error))
+        }).collect();
+
+        self
+    }
+
+    pub fn build(self) -> TSSimulatedAnnealingAlgorithm {
+        let cities_count = self.matrix.len();
+        let rule_fitness = rule_fitness(self.matrix.clone(), self.rules);
+        let fitness_funcs = make_fitness_funcs(&rule_fitness, self.time_matrix);
+
+        let generate_func: GenerateFunc<City> = GenerateFunc(Box::new(move |rng: &mut StdRng| {
+            let mut value: Vec<usize> = (0..cities_count).collect();
+            value.shuffle(rng);
+            value
+        }));
+
+        TSSimulatedAnnealingAlgorithm {
+            rule_fitness,
+            algo: SimulatedAnnealingAlgorithm {
+                fitness_funcs,
+                generate_func,
+                purpose: Purpose::Min,
+                iters_count: self.iters_count,
+                moves_count: self.moves_count,
+                solutions_count: self.solutions_count,
+                temperature: self.temperature,
+                temperature_min: self.temperature_min,
+                initial_acceptance: self.initial_acceptance,
+                cooling: self.cooling,
+                neighbour_func: self.neighbour_func,
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use crate::algorithms::tabu_search::algorithm::TabuSearchAlgorithm;
+use crate::problems::travelling_salesman::helpers::{make_solution, make_solutions};
+use crate::problems::travelling_salesman::solution::Solution;
+use crate::problems::travelling_salesman::solver::Solver;
+use crate::problems::travelling_salesman::types::{City, RuleFitness};
+
+pub struct TSTabuSearchAlgorithm {
+    pub algo: TabuSearchAlgorithm<City>,
+    // Solutions get the length of the tour and the penalties apart
+    pub rule_fitness: RuleFitness,
+}
+
+impl TSTabuSearchAlgorithm {
+    pub fn run(&self) -> Result<Vec<Solution>, &'static str> {
+        self.run_observed(&mut |_, _| true)
+    }
+
+    pub fn run_observed(&self, observer: &mut dyn FnMut(usize, &Solution) -> bool) -> Result<Vec<Solution>, &'static str> {
+        let population = self.algo.run_observed(&mut |iter, best| {
+            make_solution(best, &self.rule_fitness).is_none_or(|solution| observer(iter, &solution))
+        })?;
+        let solutions = make_solutions(population, self.algo.solutions_count, &self.rule_fitness);
+
+        Ok(solutions)
+    }
+}
+
+impl Solver for TSTabuSearchAlgorithm {
+    fn name(&self) -> &'static str {
+        "tabu_search"
+    }
+
+    fn parameters(&self) -> String {
+        format!(
+            "iters_count: {}, solutions_count: {}, tenure: {}, candidates_count: {:?}, diversification: {}, neighbourhood: {:?}",
+            self.algo.iters_count, self.algo.solutions_count, self.algo.tenure,
+            self.algo.candidates_count, self.algo.diversification, self.algo.neighbourhood
+        )
+    }
+
+    fn run_with_observer(&self, observer: &mut dyn FnMut(usize, &Solution) -> bool) -> Result<Vec<Solution>, &'static str> {
+        self.run_observed(observer)
+    }
+}
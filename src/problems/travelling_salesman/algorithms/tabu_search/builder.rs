@@ -0,0 +1,147 @@
+use rand::prelude::SliceRandom;
+use rand::rngs::StdRng;
+use crate::algorithms::constants::{ITERS_COUNT, SOLUTIONS_COUNT};
+use crate::algorithms::genetic::types::GenerateFunc;
+use crate::algorithms::tabu_search::algorithm::TabuSearchAlgorithm;
+use crate::algorithms::tabu_search::types::Neighbourhood;
+use crate::algorithms::types::Purpose;
+use crate::problems::travelling_salesman::helpers::{make_fitness_funcs, rule_fitness};
+use crate::problems::travelling_salesman::rules::{parse_rule, parse_rule_in, Language};
+use crate::problems::travelling_salesman::types::{City, CityNames, Matrix, RuleFn, RuleStr, TimeMatrix};
+use super::algorithm::TSTabuSearchAlgorithm;
+
+pub struct TSTabuSearchBuilder {
+    matrix: Matrix,
+    time_matrix: Option<TimeMatrix>,
+    city_names: CityNames,
+    iters_count: usize,
+    solutions_count: usize,
+    tenure: usize,
+    candidates_count: Option<usize>,
+    diversification: f64,
+    neighbourhood: Neighbourhood,
+    rules: Vec<RuleFn>,
+}
+
+impl TSTabuSearchBuilder {
+    pub fn new(matrix: Matrix) -> Self {
+        Self {
+            matrix,
+            time_matrix: None,
+            city_names: Vec::new(),
+            iters_count: ITERS_COUNT,
+            solutions_count: SOLUTIONS_COUNT,
+            tenure: 7,
+            candidates_count: None,
+            diversification: 0.,
+            neighbourhood: Neighbourhood::TwoOpt,
+            rules: Vec::new(),
+        }
+    }
+
+    pub fn iters_count(mut self, iters_count: usize) -> Self {
+        self.iters_count = iters_count;
+        self
+    }
+
+    pub fn solutions_count(mut self, solutions_count: usize) -> Self {
+        self.solutions_count = solutions_count;
+        self
+    }
+
+    pub fn tenure(mut self, tenure: usize) -> Self {
+        self.tenure = tenure;
+        self
+    }
+
+    pub fn candidates_count(mut self, candidates_count: usize) -> Self {
+        if candidates_count == 0 {
+            panic!("Value must be candidates_count > 0")
+        }
+
+        self.candidates_count = Some(candidates_count);
+        self
+    }
+
+    pub fn diversification(mut self, diversification: f64) -> Self {
+        if diversification < 0. {
+            panic!("Value must be diversification >= 0")
+        }
+
+        self.diversification = diversification;
+        self
+    }
+
+    pub fn neighbourhood(mut self, neighbourhood: Neighbourhood) -> Self {
+        self.neighbourhood = neighbourhood;
+        self
+    }
+
+    pub fn time_matrix(mut self, time_matrix: TimeMatrix) -> Self {
+        if time_matrix.len() != self.matrix.len() {
+            panic!("Time matrix size is not equal distance matrix")
+        }
+
+        self.time_matrix = Some(time_matrix);
+        self
+    }
+
+    // Names let rules refer to cities by name, they must be set before the rules
+    pub fn city_names(mut self, city_names: CityNames) -> Self {
+        if city_names.len() != self.matrix.len() {
+            panic!("City names count is not equal distance matrix size")
+        }
+        if city_names.iter().enumerate().any(|(idx, name)| city_names[..idx].contains(name)) {
+            panic!("City names must be unique")
+        }
+
+        self.city_names = city_names;
+        self
+    }
+
+    pub fn rules(mut self, rules: Vec<RuleStr>) -> Self {
+        self.rules = rules.into_iter().map(|rule_str| {
+            parse_rule(rule_str, &self.city_names, self.matrix.clone(), self.time_matrix.clone())
+                .unwrap_or_else(|error| panic!("{}", error))
+        }).collect();
+
+        self
+    }
+
+    // Same as rules, but the language is given for the whole set instead of being detected
+    pub fn rules_in(mut self, rules: Vec<RuleStr>, language: Language) -> Self {
+        self.rules = rules.into_iter().map(|rule_str| {
+            parse_rule_in(rule_str, language, &self.city_names, self.matrix.clone(), self.time_matrix.clone())
+                .unwrap_or_else(|error| panic!("{}", error))
+        }).collect();
+
+        self
+    }
+
+    pub fn build(self) -> TSTabuSearchAlgorithm {
+        let cities_count = self.matrix.len();
+        let rule_fitness = rule_fitness(self.matrix.clone(), self.rules);
+        let fitness_funcs = make_fitness_funcs(&rule_fitness, self.time_matrix);
+
+        let generate_func: GenerateFunc<City> = GenerateFunc(Box::new(move |rng: &mut StdRng| {
+            let mut value: Vec<usize> = (0..cities_count).collect();
+            value.shuffle(rng);
+            value
+        }));
+
+        TSTabuSearchAlgorithm {
+            rule_fitness,
+            algo: TabuSearchAlgorithm {
+                fitness_funcs,
+                generate_func,
+                purpose: Purpose::Min,
+                iters_count: self.iters_count,
+                solutions_count: self.solutions_count,
+                tenure: self.tenure,
+                candidates_count: self.candidates_count,
+                diversification: self.diversification,
+                neighbourhood: self.neighbourhood,
+            }
+        }
+    }
+}
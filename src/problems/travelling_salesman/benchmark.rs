@@ -0,0 +1,507 @@
+use std::fmt::Write;
+use std::time::Instant;
+use crate::algorithms::constants::RUNS_COUNT;
+use crate::algorithms::helpers::set_seed;
+use crate::algorithms::statistics::{describe, mann_whitney_u, mean, wilcoxon_signed_rank, Statistics, TestResult};
+use super::solver::{make_solver, SolverConfig};
+use super::types::{CityNames, Matrix, TimeMatrix};
+
+pub struct Instance {
+    pub name: String,
+    pub matrix: Matrix,
+    pub time_matrix: Option<TimeMatrix>,
+    // Empty when the cities have no names
+    pub city_names: CityNames,
+    pub optimum: Option<f64>,
+    // Distance for the time-to-target, the optimum when not set
+    pub target: Option<f64>,
+}
+
+impl Instance {
+    pub fn new(name: impl Into<String>, matrix: Matrix) -> Self {
+        Self {
+            name: name.into(),
+            matrix,
+            time_matrix: None,
+            city_names: Vec::new(),
+            optimum: None,
+            target: None,
+        }
+    }
+
+    pub fn time_matrix(mut self, time_matrix: TimeMatrix) -> Self {
+        self.time_matrix = Some(time_matrix);
+        self
+    }
+
+    pub fn city_names(mut self, city_names: CityNames) -> Self {
+        if city_names.len() != self.matrix.len() {
+            panic!("City names count is not equal distance matrix size")
+        }
+        self.city_names = city_names;
+        self
+    }
+
+    pub fn optimum(mut self, optimum: f64) -> Self {
+        self.optimum = Some(optimum);
+        self
+    }
+
+    pub fn target(mut self, target: f64) -> Self {
+        self.target = Some(target);
+        self
+    }
+}
+
+// Every solver runs runs_count times on every instance. Run i gets the seed seed + i for every solver,
+// so the runs of different solvers are paired
+pub struct Benchmark {
+    instances: Vec<Instance>,
+    solvers: Vec<(String, SolverConfig)>,
+    runs_count: usize,
+    seed: u64,
+}
+
+impl Benchmark {
+    pub fn new(instances: Vec<Instance>, solvers: Vec<(String, SolverConfig)>) -> Self {
+        Self {
+            instances,
+            solvers,
+            runs_count: RUNS_COUNT,
+            seed: 0,
+        }
+    }
+
+    pub fn runs_count(mut self, runs_count: usize) -> Self {
+        if runs_count == 0 {
+            panic!("Value must be greater than 0")
+        }
+        self.runs_count = runs_count;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn run(&self) -> Result<Report, String> {
+        let mut runs: Vec<Run> = Vec::with_capacity(self.instances.len() * self.solvers.len() * self.runs_count);
+
+        for instance in self.instances.iter() {
+            for (name, config) in self.solvers.iter() {
+                for run_idx in 0..self.runs_count {
+                    let seed = self.seed.wrapping_add(run_idx as u64);
+                    set_seed(Some(seed));
+                    let run = Self::run_once(instance, name, config, seed);
+                    set_seed(None);
+                    runs.push(run?);
+                }
+            }
+        }
+
+        Ok(Report { runs })
+    }
+
+    fn run_once(instance: &Instance, name: &str, config: &SolverConfig, seed: u64) -> Result<Run, String> {
+        let solver = make_solver(config, instance.matrix.clone(), instance.time_matrix.clone(), &instance.city_names)?;
+        let target = instance.target.or(instance.optimum);
+
+        let started = Instant::now();
+        let mut time_to_target: Option<f64> = None;
+        let solutions = solver.run_with_observer(&mut |_, best| {
+            if time_to_target.is_none() && target.is_some_and(|target| best.distance <= target) {
+                time_to_target = Some(started.elapsed().as_secs_f64());
+            }
+            true
+        }).map_err(|error| error.to_string())?;
+        let runtime = started.elapsed().as_secs_f64();
+
+        let best = solutions.first().ok_or("No solution is found".to_string())?;
+        if time_to_target.is_none() && target.is_some_and(|target| best.distance <= target) {
+            time_to_target = Some(runtime);
+        }
+
+        Ok(Run {
+            instance: instance.name.clone(),
+            solver: name.to_string(),
+            seed,
+            distance: best.distance,
+            cost: best.cost(),
+            time: best.time,
+            gap: instance.optimum.map(|optimum| best.gap(optimum)),
+            runtime,
+            time_to_target,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Run {
+    pub instance: String,
+    pub solver: String,
+    pub seed: u64,
+    // Length of the best tour, the gap and the target are about it
+    pub distance: f64,
+    // Distance of the best tour with the rule penalties, the value the solvers are compared by
+    pub cost: f64,
+    pub time: Option<usize>,
+    pub gap: Option<f64>,
+    // Seconds
+    pub runtime: f64,
+    pub time_to_target: Option<f64>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Summary {
+    pub instance: String,
+    pub solver: String,
+    pub runs_count: usize,
+    pub cost: Statistics,
+    pub distance: Statistics,
+    pub time: Option<Statistics>,
+    pub gap: Option<Statistics>,
+    pub runtime: Statistics,
+    // Runs that reached the target and their mean time-to-target
+    pub hits: usize,
+    pub time_to_target: Option<f64>,
+}
+
+// Costs of two solvers on the same instance
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Comparison {
+    pub instance: String,
+    pub first: String,
+    pub second: String,
+    pub wilcoxon: Option<TestResult>,
+    pub mann_whitney: Option<TestResult>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Report {
+    pub runs: Vec<Run>,
+}
+
+impl Report {
+    pub fn summaries(&self) -> Vec<Summary> {
+        self.groups()
+            .into_iter()
+            .map(|(instance, solver, runs)| {
+                let values = |func: &dyn Fn(&Run) -> Option<f64>| -> Vec<f64> { runs.iter().filter_map(|run| func(run)).collect() };
+                let times_to_target = values(&|run| run.time_to_target);
+
+                Summary {
+                    instance: instance.to_string(),
+                    solver: solver.to_string(),
+                    runs_count: runs.len(),
+                    cost: describe(&values(&|run| Some(run.cost))).unwrap(),
+                    distance: describe(&values(&|run| Some(run.distance))).unwrap(),
+                    time: describe(&values(&|run| run.time.map(|time| time as f64))),
+                    gap: describe(&values(&|run| run.gap)),
+                    runtime: describe(&values(&|run| Some(run.runtime))).unwrap(),
+                    hits: times_to_target.len(),
+                    time_to_target: (!times_to_target.is_empty()).then(|| mean(&times_to_target)),
+                }
+            })
+            .collect()
+    }
+
+    // Every pair of solvers on every instance, Wilcoxon pairs the runs by seed
+    pub fn comparisons(&self) -> Vec<Comparison> {
+        let groups = self.groups();
+        let mut comparisons: Vec<Comparison> = Vec::new();
+
+        for (idx, (instance, first, first_runs)) in groups.iter().enumerate() {
+            for (other_instance, second, second_runs) in groups[idx + 1..].iter() {
+                if instance != other_instance {
+                    continue;
+                }
+
+                let mut first_runs = first_runs.clone();
+                let mut second_runs = second_runs.clone();
+                first_runs.sort_by_key(|run| run.seed);
+                second_runs.sort_by_key(|run| run.seed);
+
+                let paired = first_runs.iter().map(|run| run.seed).eq(second_runs.iter().map(|run| run.seed));
+                let first_costs: Vec<f64> = first_runs.iter().map(|run| run.cost).collect();
+                let second_costs: Vec<f64> = second_runs.iter().map(|run| run.cost).collect();
+
+                comparisons.push(Comparison {
+                    instance: instance.to_string(),
+                    first: first.to_string(),
+                    second: second.to_string(),
+                    wilcoxon: if paired { wilcoxon_signed_rank(&first_costs, &second_costs) } else { None },
+                    mann_whitney: mann_whitney_u(&first_costs, &second_costs),
+                });
+            }
+        }
+
+        comparisons
+    }
+
+    pub fn runs_csv(&self) -> String {
+        let mut csv = String::from("instance,solver,seed,distance,cost,time,gap,runtime,time_to_target\n");
+        for run in self.runs.iter() {
+            writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{},{}",
+                csv_field(&run.instance), csv_field(&run.solver), run.seed, run.distance, run.cost,
+                optional(run.time), optional(run.gap), run.runtime, optional(run.time_to_target),
+            ).unwrap();
+        }
+        csv
+    }
+
+    pub fn summaries_csv(&self) -> String {
+        let mut csv = String::from(
+            "instance,solver,runs,best,mean,median,std_dev,distance_best,distance_mean,time_best,time_mean,time_median,\
+            time_std_dev,best_gap,mean_gap,runtime_mean,hits,time_to_target\n"
+        );
+        for summary in self.summaries() {
+            let time = summary.time.as_ref();
+            writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                csv_field(&summary.instance), csv_field(&summary.solver), summary.runs_count,
+                summary.cost.best, summary.cost.mean, summary.cost.median, summary.cost.std_dev,
+                summary.distance.best, summary.distance.mean,
+                optional(time.map(|time| time.best)), optional(time.map(|time| time.mean)),
+                optional(time.map(|time| time.median)), optional(time.map(|time| time.std_dev)),
+                optional(summary.gap.map(|gap| gap.best)), optional(summary.gap.map(|gap| gap.mean)),
+                summary.runtime.mean, summary.hits, optional(summary.time_to_target),
+            ).unwrap();
+        }
+        csv
+    }
+
+    pub fn comparisons_csv(&self) -> String {
+        let mut csv = String::from("instance,first,second,wilcoxon_w,wilcoxon_p,mann_whitney_u,mann_whitney_p\n");
+        for comparison in self.comparisons() {
+            writeln!(
+                csv,
+                "{},{},{},{},{},{},{}",
+                csv_field(&comparison.instance), csv_field(&comparison.first), csv_field(&comparison.second),
+                optional(comparison.wilcoxon.map(|test| test.statistic)), optional(comparison.wilcoxon.map(|test| test.p_value)),
+                optional(comparison.mann_whitney.map(|test| test.statistic)), optional(comparison.mann_whitney.map(|test| test.p_value)),
+            ).unwrap();
+        }
+        csv
+    }
+
+    pub fn summaries_markdown(&self) -> String {
+        let mut markdown = String::from(
+            "| Instance | Solver | Runs | Best | Mean | Median | Std dev | Mean distance | Mean time | Best gap, % | Mean gap, % \
+            | Runtime, s | Hits | Time to target, s |\n\
+            |---|---|---:|---:|---:|---:|---:|---:|---:|---:|---:|---:|---:|---:|\n"
+        );
+        for summary in self.summaries() {
+            let percent = |value: f64| format!("{:.2}", value * 100.);
+            writeln!(
+                markdown,
+                "| {} | {} | {} | {:.2} | {:.2} | {:.2} | {:.2} | {:.2} | {} | {} | {} | {:.3} | {}/{} | {} |",
+                summary.instance, summary.solver, summary.runs_count,
+                summary.cost.best, summary.cost.mean, summary.cost.median, summary.cost.std_dev, summary.distance.mean,
+                markdown_value(summary.time.map(|time| format!("{:.2}", time.mean))),
+                markdown_value(summary.gap.map(|gap| percent(gap.best))),
+                markdown_value(summary.gap.map(|gap| percent(gap.mean))),
+                summary.runtime.mean, summary.hits, summary.runs_count,
+                markdown_value(summary.time_to_target.map(|time| format!("{:.3}", time))),
+            ).unwrap();
+        }
+        markdown
+    }
+
+    pub fn comparisons_markdown(&self) -> String {
+        let mut markdown = String::from(
+            "| Instance | First | Second | Wilcoxon W | Wilcoxon p | Mann-Whitney U | Mann-Whitney p |\n\
+            |---|---|---|---:|---:|---:|---:|\n"
+        );
+        for comparison in self.comparisons() {
+            writeln!(
+                markdown,
+                "| {} | {} | {} | {} | {} | {} | {} |",
+                comparison.instance, comparison.first, comparison.second,
+                markdown_value(comparison.wilcoxon.map(|test| format!("{:.1}", test.statistic))),
+                markdown_value(comparison.wilcoxon.map(|test| format!("{:.4}", test.p_value))),
+                markdown_value(comparison.mann_whitney.map(|test| format!("{:.1}", test.statistic))),
+                markdown_value(comparison.mann_whitney.map(|test| format!("{:.4}", test.p_value))),
+            ).unwrap();
+        }
+        markdown
+    }
+
+    // Runs grouped by instance and solver in the order of the first run of each group
+    fn groups(&self) -> Vec<(&str, &str, Vec<&Run>)> {
+        let mut groups: Vec<(&str, &str, Vec<&Run>)> = Vec::new();
+        for run in self.runs.iter() {
+            match groups.iter_mut().find(|(instance, solver, _)| *instance == run.instance && *solver == run.solver) {
+                Some((_, _, runs)) => runs.push(run),
+                None => groups.push((&run.instance, &run.solver, vec![run])),
+            }
+        }
+        groups
+    }
+}
+
+fn optional<V: ToString>(value: Option<V>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+fn markdown_value(value: Option<String>) -> String {
+    value.unwrap_or_else(|| "-".to_string())
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::travelling_salesman::fixtures;
+    use crate::problems::travelling_salesman::solver::AlgorithmConfig;
+
+    // Every tour gets a bonus of 10 for the rules
+    fn run(solver: &str, seed: u64, distance: f64) -> Run {
+        Run {
+            instance: "ten_cities".to_string(),
+            solver: solver.to_string(),
+            seed,
+            distance,
+            cost: distance - 10.,
+            time: None,
+            gap: Some((distance - fixtures::OPTIMUM) / fixtures::OPTIMUM),
+            runtime: 0.5,
+            time_to_target: (distance <= 250.).then_some(0.25),
+        }
+    }
+
+    fn report() -> Report {
+        Report {
+            runs: vec![
+                run("ants", 0, 246.), run("bees", 0, 260.),
+                run("ants", 1, 248.), run("bees", 1, 270.),
+                run("ants", 2, 252.), run("bees", 2, 280.),
+            ],
+        }
+    }
+
+    fn solver(iters_count: usize) -> SolverConfig {
+        SolverConfig {
+            actors_count: Some(10),
+            iters_count: Some(iters_count),
+            ..SolverConfig::new(AlgorithmConfig::AntColony { alpha: None, beta: None, p: None, q: None })
+        }
+    }
+
+    #[test]
+    fn summaries_group_runs_by_instance_and_solver() {
+        let summaries = report().summaries();
+
+        assert_eq!(summaries.len(), 2);
+        let ants = &summaries[0];
+        assert_eq!((ants.solver.as_str(), ants.runs_count, ants.hits), ("ants", 3, 2));
+        assert_eq!((ants.distance.best, ants.distance.median), (246., 248.));
+        assert_eq!((ants.cost.best, ants.cost.median), (236., 238.));
+        assert_eq!(ants.gap.unwrap().best, 0.);
+        assert_eq!(ants.time_to_target, Some(0.25));
+        assert!(ants.time.is_none());
+        assert_eq!((summaries[1].hits, summaries[1].time_to_target), (0, None));
+    }
+
+    #[test]
+    fn comparisons_pair_runs_by_seed() {
+        let mut report = report();
+        let comparisons = report.comparisons();
+
+        assert_eq!(comparisons.len(), 1);
+        assert_eq!((comparisons[0].first.as_str(), comparisons[0].second.as_str()), ("ants", "bees"));
+        assert_eq!(comparisons[0].wilcoxon.unwrap().statistic, 0.);
+        assert_eq!(comparisons[0].mann_whitney.unwrap().statistic, 0.);
+
+        report.runs[1].seed = 5;
+        assert!(report.comparisons()[0].wilcoxon.is_none());
+    }
+
+    #[test]
+    fn tables_have_a_row_for_every_group() {
+        let mut report = report();
+        report.runs[0].solver = "ants, tuned".to_string();
+
+        let runs_csv = report.runs_csv();
+        assert_eq!(runs_csv.lines().count(), 7);
+        assert!(runs_csv.lines().nth(1).unwrap().starts_with("ten_cities,\"ants, tuned\",0,246,236,,0,0.5,0.25"));
+        assert_eq!(report.summaries_csv().lines().count(), 4);
+        assert_eq!(report.comparisons_csv().lines().count(), 4);
+        assert_eq!(report.summaries_markdown().lines().count(), 5);
+        assert_eq!(report.comparisons_markdown().lines().count(), 5);
+    }
+
+    #[test]
+    fn every_solver_runs_on_every_instance() {
+        let instances = vec![
+            Instance::new("ten_cities", fixtures::matrix()).optimum(fixtures::OPTIMUM).target(f64::MAX),
+            Instance::new("circle", fixtures::circle(6)),
+        ];
+        let solvers = vec![("short".to_string(), solver(2)), ("long".to_string(), solver(5))];
+
+        let report = Benchmark::new(instances, solvers).runs_count(2).seed(10).run().unwrap();
+
+        assert_eq!(report.runs.len(), 8);
+        assert_eq!(report.runs.iter().map(|run| run.seed).collect::<Vec<u64>>(), vec![10, 11, 10, 11, 10, 11, 10, 11]);
+        for run in report.runs.iter().filter(|run| run.instance == "ten_cities") {
+            assert!(run.gap.unwrap() >= 0.);
+            assert!(run.time_to_target.is_some());
+        }
+        assert!(report.runs.iter().filter(|run| run.instance == "circle").all(|run| run.gap.is_none()));
+    }
+
+    #[test]
+    fn runs_keep_the_distance_and_the_cost() {
+        let mut config = solver(3);
+        config.rules = vec!["edge from 0 to 8 : -100".to_string(), "edge from 8 to 0 : -100".to_string()];
+        let instance = Instance::new("ten_cities", fixtures::matrix()).optimum(fixtures::OPTIMUM);
+
+        let report = Benchmark::new(vec![instance], vec![("ants".to_string(), config)]).runs_count(2).run().unwrap();
+
+        for run in report.runs.iter() {
+            assert_eq!(run.cost, run.distance - 100.);
+            assert_eq!(run.gap, Some((run.distance - fixtures::OPTIMUM) / fixtures::OPTIMUM));
+        }
+        assert_eq!(report.summaries()[0].cost.best, report.summaries()[0].distance.best - 100.);
+    }
+
+    #[test]
+    fn seeded_benchmarks_are_repeated() {
+        let benchmark = Benchmark::new(vec![Instance::new("ten_cities", fixtures::matrix())], vec![("ants".to_string(), solver(3))])
+            .runs_count(3);
+
+        let first: Vec<f64> = benchmark.run().unwrap().runs.iter().map(|run| run.distance).collect();
+        let second: Vec<f64> = benchmark.run().unwrap().runs.iter().map(|run| run.distance).collect();
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn invalid_solvers_are_errors() {
+        let mut config = solver(3);
+        config.rules = vec!["Rome follows 0 : 10".to_string()];
+
+        let result = Benchmark::new(vec![Instance::new("ten_cities", fixtures::matrix())], vec![("ants".to_string(), config)]).run();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "greater than 0")]
+    fn runs_count_must_be_positive() {
+        Benchmark::new(Vec::new(), Vec::new()).runs_count(0);
+    }
+}
@@ -0,0 +1,382 @@
+use rand::rngs::StdRng;
+use rand::seq::IteratorRandom;
+use regex::Regex;
+use crate::algorithms::genetic::types::{CrossoverFunc, GenerateFunc, MutateFunc};
+use crate::algorithms::bee_colony::types::{GenerateFuncRaw, ResearchFunction};
+use super::rules::{parse_city, Language, Predicates};
+use super::types::{City, RuleStr};
+
+/*
+
+    Hard constraints are the rules of a single condition that exclude tours:
+
+    не City по порядку N : исключить          City is fixed at the position N
+    не City раньше City : исключить           the first city goes before the second one
+    City раньше City : исключить              the second city goes before the first one
+    ребро из City в City : исключить          the edge is forbidden
+
+    The rules still exclude the tours, the constraints keep the search away from such tours
+
+*/
+
+#[derive(Clone, Debug, Default)]
+pub struct Constraints {
+    cities_count: usize,
+    // City and its position from 0
+    fixed: Vec<(City, usize)>,
+    // The first city goes anywhere before the second one
+    precedences: Vec<(City, City)>,
+    forbidden_edges: Vec<(City, City)>,
+}
+
+impl Constraints {
+    // The language is detected for every rule when not given, rules that are not hard constraints are skipped
+    pub fn from_rules(rules: &[RuleStr], language: Option<Language>, city_names: &[String], cities_count: usize) -> Self {
+        Self::partition_rules(rules.to_vec(), language, city_names, cities_count).0
+    }
+
+    // The constraints with the rules they hold and the other rules. A fixed position in conflict
+    // with an earlier one is not a constraint, such a rule is among the other rules
+    pub fn partition_rules(
+        rules: Vec<RuleStr>,
+        language: Option<Language>,
+        city_names: &[String],
+        cities_count: usize,
+    ) -> (Self, Vec<RuleStr>, Vec<RuleStr>) {
+        let mut constraints = Self {
+            cities_count,
+            ..Self::default()
+        };
+
+        let (hard_rules, rules) = rules.into_iter().partition(|rule| {
+            let language = language.unwrap_or_else(|| Language::detect(rule));
+            constraints.add_rule(rule, language, city_names)
+        });
+
+        (constraints, hard_rules, rules)
+    }
+
+    // Whether the rule is one of the hard constraints
+    pub fn is_hard_rule(rule: &str, language: Option<Language>, city_names: &[String], cities_count: usize) -> bool {
+        let language = language.unwrap_or_else(|| Language::detect(rule));
+        let mut constraints = Self {
+            cities_count,
+            ..Self::default()
+        };
+
+        constraints.add_rule(rule, language, city_names)
+    }
+
+    // Whether the rule is added as a hard constraint, a city or a position fixed by another rule is not
+    fn add_rule(&mut self, rule: &str, language: Language, city_names: &[String]) -> bool {
+        let keywords = language.keywords();
+        let (condition, action) = match rule.split_once(':') {
+            Some(parts) => parts,
+            None => return false,
+        };
+        if action.trim() != keywords.exclude {
+            return false;
+        }
+
+        let not = Regex::new(&format!(r"^{}\s+", keywords.not)).unwrap();
+        let mut condition = condition.trim();
+        let mut negated = false;
+        while let Some(found) = not.find(condition) {
+            negated = !negated;
+            condition = &condition[found.end()..];
+        }
+
+        let predicates = Predicates::new(&keywords, true);
+        let cities_count = self.cities_count;
+        let city = |token: &str| parse_city(token, city_names, cities_count).ok();
+
+        if let Some(cap) = predicates.in_order.captures(condition) {
+            let order = cap[2].parse::<usize>().ok().filter(|order| (1..=cities_count).contains(order));
+            if let (true, Some(city), Some(order)) = (negated, city(&cap[1]), order) {
+                let position = order - 1;
+                // The same rule twice is kept once, a city or a position fixed otherwise is a conflict
+                if self.fixed.iter().any(|fixed| (fixed.0 == city) != (fixed.1 == position)) {
+                    return false;
+                }
+                if !self.fixed.contains(&(city, position)) {
+                    self.fixed.push((city, position));
+                }
+                return true;
+            }
+        } else if let Some(cap) = predicates.before.captures(condition) {
+            if let (Some(city_a), Some(city_b)) = (city(&cap[1]), city(&cap[2])) {
+                if city_a != city_b {
+                    self.precedences.push(if negated { (city_a, city_b) } else { (city_b, city_a) });
+                    return true;
+                }
+            }
+        } else if let Some(cap) = predicates.edge.captures(condition) {
+            if let (false, Some(city_from), Some(city_to)) = (negated, city(&cap[1]), city(&cap[2])) {
+                self.forbidden_edges.push((city_from, city_to));
+                return true;
+            }
+        }
+
+        false
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fixed.is_empty() && self.precedences.is_empty() && self.forbidden_edges.is_empty()
+    }
+
+    pub fn is_feasible(&self, path: &[City]) -> bool {
+        (0..path.len()).all(|idx| self.allows(&path[..idx], path[idx]))
+    }
+
+    // Whether the city may be the next one of the partial path
+    pub fn allows(&self, path: &[City], city: City) -> bool {
+        self.allows_with(path, city, true, true)
+    }
+
+    // A random city the path may start from
+    pub fn start(&self, rng: &mut StdRng) -> Option<City> {
+        (0..self.cities_count).filter(|city| self.allows(&[], *city)).choose(rng)
+    }
+
+    fn allows_with(&self, path: &[City], city: City, precedences: bool, edges: bool) -> bool {
+        let position = path.len();
+        if path.contains(&city) {
+            return false;
+        }
+        if self.fixed.iter().any(|(fixed, fixed_position)| (*fixed == city) != (*fixed_position == position)) {
+            return false;
+        }
+        if precedences && self.precedences.iter().any(|(first, second)| *second == city && !path.contains(first)) {
+            return false;
+        }
+        if edges {
+            let is_forbidden = |from: City, to: City| self.forbidden_edges.contains(&(from, to));
+            if path.last().is_some_and(|last| is_forbidden(*last, city)) {
+                return false;
+            }
+            if position + 1 == self.cities_count && path.first().is_some_and(|first| is_forbidden(city, *first)) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    // The path is rebuilt keeping the order of its cities where the constraints allow it. When no city fits,
+    // the forbidden edges and then the precedences are ignored for the position
+    pub fn repair(&self, path: Vec<City>) -> Vec<City> {
+        if self.is_empty() || self.is_feasible(&path) {
+            return path;
+        }
+
+        let mut remaining = path;
+        let mut repaired = Vec::with_capacity(remaining.len());
+        while !remaining.is_empty() {
+            let fits = |precedences: bool, edges: bool| {
+                remaining.iter().position(|city| self.allows_with(&repaired, *city, precedences, edges))
+            };
+            let idx = fits(true, true)
+                .or_else(|| fits(true, false))
+                .or_else(|| fits(false, false))
+                .unwrap_or(0);
+            repaired.push(remaining.remove(idx));
+        }
+
+        repaired
+    }
+
+    // Operators with repaired results, so the search stays among the feasible tours
+    pub fn repair_generate(&self, generate_func: GenerateFunc<City>) -> GenerateFunc<City> {
+        if self.is_empty() {
+            return generate_func;
+        }
+
+        let constraints = self.clone();
+        GenerateFunc(Box::new(move |rng| constraints.repair(generate_func.0(rng))))
+    }
+
+    pub fn repair_crossover(&self, crossover_func: CrossoverFunc<City>) -> CrossoverFunc<City> {
+        if self.is_empty() {
+            return crossover_func;
+        }
+
+        let constraints = self.clone();
+        CrossoverFunc(Box::new(move |a, b, rng| {
+            let (child_1, child_2) = crossover_func.0(a, b, rng);
+            (constraints.repair(child_1), constraints.repair(child_2))
+        }))
+    }
+
+    pub fn repair_mutate(&self, mutate_func: MutateFunc<City>) -> MutateFunc<City> {
+        if self.is_empty() {
+            return mutate_func;
+        }
+
+        let constraints = self.clone();
+        MutateFunc(Box::new(move |value, rng| constraints.repair(mutate_func.0(value, rng))))
+    }
+
+    pub fn repair_source(&self, generate_func: GenerateFuncRaw<City>) -> GenerateFuncRaw<City> {
+        if self.is_empty() {
+            return generate_func;
+        }
+
+        let constraints = self.clone();
+        Box::new(move || constraints.repair(generate_func()))
+    }
+
+    pub fn repair_research(&self, research_func: ResearchFunction<City>) -> ResearchFunction<City> {
+        if self.is_empty() {
+            return research_func;
+        }
+
+        let constraints = self.clone();
+        ResearchFunction(Box::new(move |source, rng| constraints.repair(research_func.0(source, rng))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::helpers::{make_rng, set_seed};
+
+    fn constraints(rules: &[&str]) -> Constraints {
+        let rules: Vec<RuleStr> = rules.iter().map(|rule| rule.to_string()).collect();
+        Constraints::from_rules(&rules, None, &[], 6)
+    }
+
+    #[test]
+    fn hard_rules_are_recognised() {
+        let is_hard = |rule: &str| Constraints::is_hard_rule(rule, None, &[], 6);
+
+        assert!(is_hard("не 2 по порядку 1 : исключить"));
+        assert!(is_hard("not 2 at position 1 : exclude"));
+        assert!(is_hard("не 1 раньше 3 : исключить"));
+        assert!(is_hard("3 раньше 1 : исключить"));
+        assert!(is_hard("ребро из 1 в 2 : исключить"));
+        assert!(!is_hard("2 по порядку 1 : исключить"));
+        assert!(!is_hard("не 2 по порядку [1,2] : исключить"));
+        assert!(!is_hard("не ребро из 1 в 2 : исключить"));
+        assert!(!is_hard("ребро из 1 в 2 : 100"));
+        assert!(!is_hard("1 следует за 0 и 3 раньше 1 : исключить"));
+        assert!(!is_hard("не 2 по порядку 7 : исключить"));
+    }
+
+    #[test]
+    fn partial_paths_are_checked() {
+        let constraints = constraints(&[
+            "не 2 по порядку 1 : исключить",
+            "не 1 раньше 3 : исключить",
+            "ребро из 4 в 5 : исключить",
+        ]);
+
+        assert!(constraints.allows(&[], 2));
+        assert!(!constraints.allows(&[], 0));
+        assert!(!constraints.allows(&[2], 3));
+        assert!(constraints.allows(&[2, 1], 3));
+        assert!(!constraints.allows(&[2, 4], 5));
+        assert!(constraints.is_feasible(&[2, 1, 3, 4, 0, 5]));
+        assert!(!constraints.is_feasible(&[2, 3, 1, 4, 0, 5]));
+    }
+
+    #[test]
+    fn closing_edge_is_checked() {
+        let constraints = constraints(&["ребро из 5 в 0 : исключить"]);
+
+        assert!(!constraints.is_feasible(&[0, 1, 2, 3, 4, 5]));
+        assert!(constraints.is_feasible(&[0, 1, 2, 3, 5, 4]));
+    }
+
+    #[test]
+    fn fixed_position_is_not_taken_twice() {
+        let constraints = constraints(&["не 2 по порядку 1 : исключить", "не 3 по порядку 1 : исключить"]);
+
+        assert!(constraints.is_feasible(&[2, 0, 1, 3, 4, 5]));
+        assert!(!constraints.is_feasible(&[3, 0, 1, 2, 4, 5]));
+    }
+
+    #[test]
+    fn conflicting_fixed_positions_stay_rules() {
+        let rules: Vec<RuleStr> = [
+            "не 2 по порядку 1 : исключить",
+            "не 3 по порядку 1 : исключить",
+            "не 2 по порядку 4 : исключить",
+            "не 2 по порядку 1 : исключить",
+            "не 4 по порядку 2 : исключить",
+        ]
+            .iter()
+            .map(|rule| rule.to_string())
+            .collect();
+
+        let (constraints, hard_rules, other_rules) = Constraints::partition_rules(rules.clone(), None, &[], 6);
+
+        assert_eq!(hard_rules, vec![rules[0].clone(), rules[3].clone(), rules[4].clone()]);
+        assert_eq!(other_rules, vec![rules[1].clone(), rules[2].clone()]);
+        assert_eq!(constraints.fixed, vec![(2, 0), (4, 1)]);
+    }
+
+    #[test]
+    fn start_is_allowed() {
+        let constraints = constraints(&["не 4 по порядку 1 : исключить"]);
+        set_seed(Some(1));
+        let mut rng = make_rng();
+
+        assert!((0..10).all(|_| constraints.start(&mut rng) == Some(4)));
+        set_seed(None);
+    }
+
+    #[test]
+    fn repair_keeps_the_order() {
+        let constraints = constraints(&["не 2 по порядку 1 : исключить", "не 1 раньше 3 : исключить"]);
+
+        assert_eq!(constraints.repair(vec![0, 3, 1, 2, 4, 5]), vec![2, 0, 1, 3, 4, 5]);
+        assert_eq!(constraints.repair(vec![2, 0, 1, 3, 4, 5]), vec![2, 0, 1, 3, 4, 5]);
+    }
+
+    #[test]
+    fn repaired_paths_are_feasible() {
+        let constraints = constraints(&[
+            "не 5 по порядку 6 : исключить",
+            "не 0 раньше 1 : исключить",
+            "не 1 раньше 2 : исключить",
+            "ребро из 3 в 4 : исключить",
+            "ребро из 4 в 3 : исключить",
+        ]);
+        let paths = [
+            vec![5, 4, 3, 2, 1, 0],
+            vec![3, 4, 2, 1, 0, 5],
+            vec![1, 0, 3, 2, 5, 4],
+            vec![4, 3, 5, 0, 2, 1],
+        ];
+
+        for path in paths {
+            let repaired = constraints.repair(path.clone());
+            assert!(constraints.is_feasible(&repaired), "{:?} repaired into {:?}", path, repaired);
+
+            let mut sorted = repaired.clone();
+            sorted.sort();
+            assert_eq!(sorted, (0..6).collect::<Vec<City>>());
+        }
+    }
+
+    #[test]
+    fn repair_ignores_the_edges_when_nothing_fits() {
+        // Only 0 may be first and 1 must go right after it, which the edge forbids
+        let constraints = constraints(&["не 0 по порядку 1 : исключить", "не 1 по порядку 2 : исключить", "ребро из 0 в 1 : исключить"]);
+
+        assert_eq!(constraints.repair(vec![2, 1, 0, 3, 4, 5]), vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn operators_are_repaired() {
+        let constraints = constraints(&["не 2 по порядку 1 : исключить"]);
+        let mutate = constraints.repair_mutate(MutateFunc(Box::new(|value: Vec<City>, _| value.into_iter().rev().collect())));
+        let generate = constraints.repair_source(Box::new(|| (0..6).collect()));
+        set_seed(Some(1));
+        let mut rng = make_rng();
+
+        assert_eq!(mutate.0((0..6).collect(), &mut rng), vec![2, 5, 4, 3, 1, 0]);
+        assert_eq!(generate(), vec![2, 0, 1, 3, 4, 5]);
+        set_seed(None);
+    }
+}
@@ -0,0 +1,427 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use super::types::{City, Matrix};
+
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum Construction {
+    NearestNeighbour,
+    GreedyEdge,
+    CheapestInsertion,
+    FarthestInsertion,
+    Christofides,
+    SpaceFillingCurve,
+}
+
+impl Construction {
+    pub fn build(&self, matrix: &Matrix, rng: &mut StdRng) -> Vec<City> {
+        if matrix.is_empty() {
+            return Vec::new();
+        }
+
+        let start = rng.gen_range(0..matrix.len());
+        match self {
+            Construction::NearestNeighbour => nearest_neighbour(matrix, start),
+            Construction::GreedyEdge => greedy_edge(matrix),
+            Construction::CheapestInsertion => cheapest_insertion(matrix, start),
+            Construction::FarthestInsertion => farthest_insertion(matrix, start),
+            Construction::Christofides => christofides(matrix, start),
+            Construction::SpaceFillingCurve => space_filling_curve(matrix),
+        }
+    }
+}
+
+// Random tour or, with `part` probability, a tour built by one of the constructions
+pub fn generate_tour(matrix: &Matrix, constructions: &[Construction], part: f32, rng: &mut StdRng) -> Vec<City> {
+    if !constructions.is_empty() && rng.gen::<f32>() < part {
+        if let Some(construction) = constructions.choose(rng) {
+            return construction.build(matrix, rng);
+        }
+    }
+
+    let mut value: Vec<City> = (0..matrix.len()).collect();
+    value.shuffle(rng);
+    value
+}
+
+pub fn nearest_neighbour(matrix: &Matrix, start: City) -> Vec<City> {
+    let cities_count = matrix.len();
+    let mut visited = vec![false; cities_count];
+    let mut path: Vec<City> = Vec::with_capacity(cities_count);
+    let mut city = start;
+
+    visited[city] = true;
+    path.push(city);
+    for _ in 1..cities_count {
+        city = (0..cities_count)
+            .filter(|next| !visited[*next])
+            .min_by(|a, b| matrix[city][*a].total_cmp(&matrix[city][*b]))
+            .unwrap();
+        visited[city] = true;
+        path.push(city);
+    }
+
+    path
+}
+
+// Adds the shortest edges one by one while no city gets a third edge and no subtour appears
+pub fn greedy_edge(matrix: &Matrix) -> Vec<City> {
+    let cities_count = matrix.len();
+    if cities_count < 3 {
+        return (0..cities_count).collect();
+    }
+
+    let mut edges: Vec<(City, City)> = (0..cities_count)
+        .flat_map(|a| (a + 1..cities_count).map(move |b| (a, b)))
+        .collect();
+    edges.sort_by(|x, y| matrix[x.0][x.1].total_cmp(&matrix[y.0][y.1]));
+
+    let mut components: Vec<usize> = (0..cities_count).collect();
+    let mut adjacency: Vec<Vec<City>> = vec![Vec::with_capacity(2); cities_count];
+    let mut edges_count = 0;
+
+    for (a, b) in edges {
+        if edges_count == cities_count - 1 {
+            break;
+        }
+        if adjacency[a].len() == 2 || adjacency[b].len() == 2 {
+            continue;
+        }
+
+        let (root_a, root_b) = (find_root(&mut components, a), find_root(&mut components, b));
+        if root_a == root_b {
+            continue;
+        }
+
+        components[root_a] = root_b;
+        adjacency[a].push(b);
+        adjacency[b].push(a);
+        edges_count += 1;
+    }
+
+    // The edges form a single hamiltonian path, walk it from one of its ends
+    let mut city = (0..cities_count).find(|city| adjacency[*city].len() < 2).unwrap();
+    let mut previous: Option<City> = None;
+    let mut path: Vec<City> = Vec::with_capacity(cities_count);
+    loop {
+        path.push(city);
+        match adjacency[city].iter().find(|next| Some(**next) != previous) {
+            Some(next) if path.len() < cities_count => {
+                previous = Some(city);
+                city = *next;
+            }
+            _ => break,
+        }
+    }
+
+    path
+}
+
+pub fn cheapest_insertion(matrix: &Matrix, start: City) -> Vec<City> {
+    let cities_count = matrix.len();
+    let mut path: Vec<City> = vec![start];
+    let mut visited = vec![false; cities_count];
+    visited[start] = true;
+
+    while path.len() < cities_count {
+        let (city, position) = (0..cities_count)
+            .filter(|city| !visited[*city])
+            .map(|city| (city, cheapest_position(matrix, &path, city)))
+            .min_by(|a, b| a.1.1.total_cmp(&b.1.1))
+            .map(|(city, (position, _))| (city, position))
+            .unwrap();
+
+        path.insert(position, city);
+        visited[city] = true;
+    }
+
+    path
+}
+
+// The city farthest from the tour is inserted next, at its cheapest position
+pub fn farthest_insertion(matrix: &Matrix, start: City) -> Vec<City> {
+    let cities_count = matrix.len();
+    let mut path: Vec<City> = vec![start];
+    let mut visited = vec![false; cities_count];
+    let mut distances: Vec<f64> = matrix[start].clone();
+    visited[start] = true;
+
+    while path.len() < cities_count {
+        let city = (0..cities_count)
+            .filter(|city| !visited[*city])
+            .max_by(|a, b| distances[*a].total_cmp(&distances[*b]))
+            .unwrap();
+
+        let (position, _) = cheapest_position(matrix, &path, city);
+        path.insert(position, city);
+        visited[city] = true;
+
+        for other in 0..cities_count {
+            distances[other] = distances[other].min(matrix[city][other]);
+        }
+    }
+
+    path
+}
+
+// Christofides-like: minimum spanning tree, greedy (not perfect) matching of odd cities,
+// euler circuit and shortcuts over repeated cities
+pub fn christofides(matrix: &Matrix, start: City) -> Vec<City> {
+    let cities_count = matrix.len();
+    if cities_count < 3 {
+        return (0..cities_count).collect();
+    }
+
+    let mut adjacency: Vec<Vec<City>> = vec![Vec::new(); cities_count];
+    for (a, b) in minimum_spanning_tree(matrix) {
+        adjacency[a].push(b);
+        adjacency[b].push(a);
+    }
+
+    let odd: Vec<City> = (0..cities_count).filter(|city| adjacency[*city].len() % 2 == 1).collect();
+    let mut pairs: Vec<(City, City)> = odd
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, a)| odd[idx + 1..].iter().map(move |b| (*a, *b)))
+        .collect();
+    pairs.sort_by(|x, y| matrix[x.0][x.1].total_cmp(&matrix[y.0][y.1]));
+
+    let mut matched = vec![false; cities_count];
+    for (a, b) in pairs {
+        if !matched[a] && !matched[b] {
+            matched[a] = true;
+            matched[b] = true;
+            adjacency[a].push(b);
+            adjacency[b].push(a);
+        }
+    }
+
+    // Hierholzer's algorithm
+    let mut stack: Vec<City> = vec![start];
+    let mut circuit: Vec<City> = Vec::new();
+    while let Some(city) = stack.last().copied() {
+        match adjacency[city].pop() {
+            Some(next) => {
+                let idx = adjacency[next].iter().position(|c| *c == city).unwrap();
+                adjacency[next].swap_remove(idx);
+                stack.push(next);
+            }
+            None => {
+                circuit.push(city);
+                stack.pop();
+            }
+        }
+    }
+
+    let mut visited = vec![false; cities_count];
+    circuit.into_iter().filter(|city| !std::mem::replace(&mut visited[*city], true)).collect()
+}
+
+// Cities are ordered along the Hilbert curve over coordinates recovered from the matrix by classical scaling
+pub fn space_filling_curve(matrix: &Matrix) -> Vec<City> {
+    let coordinates = coordinates_from_matrix(matrix);
+    let (min_x, max_x, min_y, max_y) = coordinates.iter().fold(
+        (f64::MAX, f64::MIN, f64::MAX, f64::MIN),
+        |(min_x, max_x, min_y, max_y), (x, y)| (min_x.min(*x), max_x.max(*x), min_y.min(*y), max_y.max(*y)),
+    );
+
+    let side: u64 = 1 << 16;
+    let scale = |value: f64, min: f64, max: f64| -> u64 {
+        if max - min <= 0. {
+            0
+        } else {
+            (((value - min) / (max - min)) * (side - 1) as f64).round() as u64
+        }
+    };
+
+    let mut path: Vec<City> = (0..matrix.len()).collect();
+    path.sort_by_key(|city| {
+        let (x, y) = coordinates[*city];
+        hilbert_index(side, scale(x, min_x, max_x), scale(y, min_y, max_y))
+    });
+    path
+}
+
+fn cheapest_position(matrix: &Matrix, path: &[City], city: City) -> (usize, f64) {
+    if path.len() == 1 {
+        return (1, matrix[path[0]][city] + matrix[city][path[0]]);
+    }
+
+    (0..path.len())
+        .map(|idx| {
+            let (a, b) = (path[idx], path[(idx + 1) % path.len()]);
+            (idx + 1, matrix[a][city] + matrix[city][b] - matrix[a][b])
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap()
+}
+
+fn find_root(components: &mut [usize], city: City) -> usize {
+    let mut root = city;
+    while components[root] != root {
+        root = components[root];
+    }
+    components[city] = root;
+    root
+}
+
+pub fn minimum_spanning_tree(matrix: &Matrix) -> Vec<(City, City)> {
+    let cities_count = matrix.len();
+    let mut in_tree = vec![false; cities_count];
+    let mut distances = vec![f64::INFINITY; cities_count];
+    let mut parents = vec![0; cities_count];
+    let mut edges: Vec<(City, City)> = Vec::with_capacity(cities_count.saturating_sub(1));
+
+    if cities_count == 0 {
+        return edges;
+    }
+
+    distances[0] = 0.;
+    for _ in 0..cities_count {
+        let city = (0..cities_count)
+            .filter(|city| !in_tree[*city])
+            .min_by(|a, b| distances[*a].total_cmp(&distances[*b]))
+            .unwrap();
+
+        in_tree[city] = true;
+        if city != 0 {
+            edges.push((parents[city], city));
+        }
+
+        for next in 0..cities_count {
+            if !in_tree[next] && matrix[city][next] < distances[next] {
+                distances[next] = matrix[city][next];
+                parents[next] = city;
+            }
+        }
+    }
+
+    edges
+}
+
+// Classical multidimensional scaling into the plane, eigenvectors are found by power iteration
+fn coordinates_from_matrix(matrix: &Matrix) -> Vec<(f64, f64)> {
+    let n = matrix.len();
+    let squared: Vec<Vec<f64>> = matrix.iter().map(|row| row.iter().map(|d| d * d).collect()).collect();
+    let row_means: Vec<f64> = squared.iter().map(|row| row.iter().sum::<f64>() / n as f64).collect();
+    let total_mean = row_means.iter().sum::<f64>() / n as f64;
+
+    let mut centered: Vec<Vec<f64>> = (0..n)
+        .map(|i| (0..n).map(|j| -0.5 * (squared[i][j] - row_means[i] - row_means[j] + total_mean)).collect())
+        .collect();
+
+    let mut axes: Vec<Vec<f64>> = Vec::with_capacity(2);
+    for axis in 0..2 {
+        let mut vector: Vec<f64> = (0..n).map(|i| 1. + ((i + axis) % 3) as f64).collect();
+        let mut eigenvalue = 0.;
+
+        for _ in 0..100 {
+            let next: Vec<f64> = centered.iter().map(|row| row.iter().zip(&vector).map(|(a, b)| a * b).sum()).collect();
+            let norm = next.iter().map(|v| v * v).sum::<f64>().sqrt();
+            if norm == 0. {
+                break;
+            }
+            eigenvalue = norm;
+            vector = next.into_iter().map(|v| v / norm).collect();
+        }
+
+        for i in 0..n {
+            for j in 0..n {
+                centered[i][j] -= eigenvalue * vector[i] * vector[j];
+            }
+        }
+        axes.push(vector.into_iter().map(|v| v * eigenvalue.max(0.).sqrt()).collect());
+    }
+
+    (0..n).map(|i| (axes[0][i], axes[1][i])).collect()
+}
+
+fn hilbert_index(side: u64, mut x: u64, mut y: u64) -> u64 {
+    let mut index = 0;
+    let mut s = side / 2;
+
+    while s > 0 {
+        let rx = u64::from(x & s > 0);
+        let ry = u64::from(y & s > 0);
+        index += s * s * ((3 * rx) ^ ry);
+
+        if ry == 0 {
+            if rx == 1 {
+                x = side - 1 - x;
+                y = side - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use crate::problems::travelling_salesman::fixtures;
+    use crate::problems::travelling_salesman::helpers::{calculate_distance, is_permutation};
+
+    const CONSTRUCTIONS: [Construction; 6] = [
+        Construction::NearestNeighbour,
+        Construction::GreedyEdge,
+        Construction::CheapestInsertion,
+        Construction::FarthestInsertion,
+        Construction::Christofides,
+        Construction::SpaceFillingCurve,
+    ];
+
+    #[test]
+    fn constructions_build_tours() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        for matrix in [fixtures::matrix(), fixtures::circle(1), fixtures::circle(2), fixtures::circle(7)] {
+            for construction in CONSTRUCTIONS {
+                let tour = construction.build(&matrix, &mut rng);
+                assert!(is_permutation(&tour, matrix.len()), "{:?} gave {:?}", construction, tour);
+            }
+        }
+        assert!(Construction::Christofides.build(&Vec::new(), &mut rng).is_empty());
+    }
+
+    #[test]
+    fn constructions_find_the_circle() {
+        let matrix = fixtures::circle(12);
+        let optimum = calculate_distance(&matrix, &(0..12).collect::<Vec<City>>());
+
+        for tour in [nearest_neighbour(&matrix, 5), greedy_edge(&matrix), cheapest_insertion(&matrix, 3)] {
+            assert!((calculate_distance(&matrix, &tour) - optimum).abs() < 1e-9);
+        }
+        // The approximation guarantee of Christofides on a metric instance
+        assert!(calculate_distance(&matrix, &christofides(&matrix, 0)) <= 1.5 * optimum);
+    }
+
+    #[test]
+    fn minimum_spanning_tree_of_the_circle_skips_one_side() {
+        let matrix = fixtures::circle(6);
+
+        let edges = minimum_spanning_tree(&matrix);
+        let weight: f64 = edges.iter().map(|(a, b)| matrix[*a][*b]).sum();
+
+        assert_eq!(edges.len(), 5);
+        assert!((weight - 5. * matrix[0][1]).abs() < 1e-9);
+    }
+
+    #[test]
+    fn generated_tours_are_random_or_constructed() {
+        let matrix = fixtures::matrix();
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let constructed = generate_tour(&matrix, &[Construction::GreedyEdge], 1., &mut rng);
+        assert_eq!(constructed, greedy_edge(&matrix));
+
+        for part in [0., 0.5] {
+            let tour = generate_tour(&matrix, &[Construction::GreedyEdge], part, &mut rng);
+            assert!(is_permutation(&tour, matrix.len()));
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use super::constructions::nearest_neighbour;
+use super::helpers::calculate_distance;
+use super::solution::Solution;
+use super::types::{City, Matrix};
+
+pub const HELD_KARP_MAX_CITIES: usize = 20;
+
+// Held-Karp dynamic programming, the tour starts at the city 0. Works for asymmetric matrices too
+pub fn held_karp(matrix: &Matrix) -> Result<Solution, &'static str> {
+    let cities_count = matrix.len();
+    if cities_count == 0 {
+        return Err("Matrix is empty");
+    }
+    if cities_count > HELD_KARP_MAX_CITIES {
+        return Err("Too many cities for Held-Karp algorithm");
+    }
+    if cities_count == 1 {
+        return Ok(make_solution(matrix, vec![0]));
+    }
+
+    // Subsets of cities 1..n are encoded as bit masks, bit (city - 1) is set if city is visited
+    let others_count = cities_count - 1;
+    let subsets_count = 1usize << others_count;
+    let mut costs = vec![f64::INFINITY; subsets_count * others_count];
+    let mut parents = vec![0u8; subsets_count * others_count];
+
+    for city in 0..others_count {
+        costs[(1 << city) * others_count + city] = matrix[0][city + 1];
+    }
+
+    for subset in 1..subsets_count {
+        for last in 0..others_count {
+            if subset & (1 << last) == 0 {
+                continue;
+            }
+            let cost = costs[subset * others_count + last];
+            if cost.is_infinite() {
+                continue;
+            }
+
+            for next in 0..others_count {
+                if subset & (1 << next) != 0 {
+                    continue;
+                }
+
+                let next_subset = subset | (1 << next);
+                let next_cost = cost + matrix[last + 1][next + 1];
+                let idx = next_subset * others_count + next;
+                if next_cost < costs[idx] {
+                    costs[idx] = next_cost;
+                    parents[idx] = last as u8;
+                }
+            }
+        }
+    }
+
+    let full = subsets_count - 1;
+    let last = (0..others_count)
+        .min_by(|a, b| {
+            let a_cost = costs[full * others_count + a] + matrix[a + 1][0];
+            let b_cost = costs[full * others_count + b] + matrix[b + 1][0];
+            a_cost.total_cmp(&b_cost)
+        })
+        .unwrap();
+
+    let mut path: Vec<City> = Vec::with_capacity(cities_count);
+    let (mut subset, mut city) = (full, last);
+    loop {
+        path.push(city + 1);
+        let previous_subset = subset & !(1 << city);
+        if previous_subset == 0 {
+            break;
+        }
+        city = parents[subset * others_count + city] as usize;
+        subset = previous_subset;
+    }
+    path.push(0);
+    path.reverse();
+
+    Ok(make_solution(matrix, path))
+}
+
+// Held-Karp lower bound: the best 1-tree over subgradient optimised node penalties. Symmetric matrices only.
+// At least one iteration is made, it gives the plain 1-tree bound
+pub fn one_tree_bound(matrix: &Matrix, iters_count: usize) -> f64 {
+    let cities_count = matrix.len();
+    if cities_count < 3 {
+        return match held_karp(matrix) {
+            Ok(solution) => solution.distance,
+            Err(_) => 0.,
+        };
+    }
+
+    let upper_bound = calculate_distance(matrix, &nearest_neighbour(matrix, 0));
+    let mut penalties = vec![0.; cities_count];
+    let mut bound = f64::MIN;
+    let mut lambda = 2.;
+    let mut stagnation = 0;
+
+    for _ in 0..iters_count.max(1) {
+        let (cost, degrees) = one_tree(matrix, &penalties);
+        let value = cost - 2. * penalties.iter().sum::<f64>();
+
+        if value > bound {
+            bound = value;
+            stagnation = 0;
+        } else {
+            stagnation += 1;
+            if stagnation >= 10 {
+                lambda /= 2.;
+                stagnation = 0;
+            }
+        }
+
+        let subgradient: Vec<f64> = degrees.iter().map(|degree| *degree as f64 - 2.).collect();
+        let norm: f64 = subgradient.iter().map(|g| g * g).sum();
+        // Every city has degree 2, the 1-tree is a tour and the bound is tight
+        if norm == 0. {
+            break;
+        }
+
+        let step = lambda * (upper_bound - value).max(0.) / norm;
+        if step == 0. {
+            break;
+        }
+        for (penalty, g) in penalties.iter_mut().zip(subgradient) {
+            *penalty += step * g;
+        }
+    }
+
+    bound
+}
+
+// Minimum spanning tree over cities 1..n (Prim) plus two cheapest edges of the city 0
+fn one_tree(matrix: &Matrix, penalties: &[f64]) -> (f64, Vec<usize>) {
+    let cities_count = matrix.len();
+    let weight = |a: City, b: City| matrix[a][b] + penalties[a] + penalties[b];
+
+    let mut degrees = vec![0; cities_count];
+    let mut in_tree = vec![false; cities_count];
+    let mut distances = vec![f64::INFINITY; cities_count];
+    let mut parents = vec![1; cities_count];
+    let mut cost = 0.;
+
+    distances[1] = 0.;
+    for _ in 1..cities_count {
+        let city = (1..cities_count)
+            .filter(|city| !in_tree[*city])
+            .min_by(|a, b| distances[*a].total_cmp(&distances[*b]))
+            .unwrap();
+
+        in_tree[city] = true;
+        if city != 1 {
+            cost += weight(city, parents[city]);
+            degrees[city] += 1;
+            degrees[parents[city]] += 1;
+        }
+
+        for next in 1..cities_count {
+            if !in_tree[next] && weight(city, next) < distances[next] {
+                distances[next] = weight(city, next);
+                parents[next] = city;
+            }
+        }
+    }
+
+    let mut edges: Vec<(f64, City)> = (1..cities_count).map(|city| (weight(0, city), city)).collect();
+    edges.sort_by(|a, b| a.0.total_cmp(&b.0));
+    for (edge_weight, city) in edges.into_iter().take(2) {
+        cost += edge_weight;
+        degrees[0] += 1;
+        degrees[city] += 1;
+    }
+
+    (cost, degrees)
+}
+
+fn make_solution(matrix: &Matrix, path: Vec<City>) -> Solution {
+    Solution {
+        distance: calculate_distance(matrix, &path),
+        path,
+        time: None,
+        penalty: 0.,
+        fitness: 0.,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::travelling_salesman::fixtures;
+    use crate::problems::travelling_salesman::helpers::is_permutation;
+
+    #[test]
+    fn held_karp_and_one_tree_agree_on_the_demo_instance() {
+        let matrix = fixtures::matrix();
+
+        let solution = held_karp(&matrix).unwrap();
+
+        assert!(is_permutation(&solution.path, matrix.len()));
+        assert_eq!(solution.path[0], 0);
+        assert_eq!(solution.distance, fixtures::OPTIMUM);
+        assert_eq!(one_tree_bound(&matrix, 100), fixtures::OPTIMUM);
+    }
+
+    #[test]
+    fn one_tree_bound_without_iterations_is_the_plain_one_tree() {
+        let matrix = fixtures::matrix();
+
+        let bound = one_tree_bound(&matrix, 0);
+
+        assert_eq!(bound, one_tree(&matrix, &vec![0.; matrix.len()]).0);
+        assert!(bound > 0. && bound <= fixtures::OPTIMUM);
+    }
+
+    #[test]
+    fn one_tree_bound_never_exceeds_the_optimum() {
+        for cities_count in [3, 5, 8] {
+            let matrix = fixtures::circle(cities_count);
+            let optimum = held_karp(&matrix).unwrap().distance;
+
+            for iters_count in [0, 1, 10, 100] {
+                assert!(one_tree_bound(&matrix, iters_count) <= optimum + 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn held_karp_handles_small_and_asymmetric_matrices() {
+        assert!(held_karp(&Vec::new()).is_err());
+        assert_eq!(held_karp(&vec![vec![0.]]).unwrap().path, vec![0]);
+
+        // Going round 0 -> 1 -> 2 is cheap only in one direction
+        let matrix = vec![vec![0., 1., 10.], vec![10., 0., 1.], vec![1., 10., 0.]];
+        let solution = held_karp(&matrix).unwrap();
+        assert_eq!(solution.path, vec![0, 1, 2]);
+        assert_eq!(solution.distance, 3.);
+    }
+}
@@ -0,0 +1,512 @@
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Instant;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use crate::algorithms::helpers::set_seed;
+use super::benchmark::{Benchmark, Instance};
+use super::report::RuleExplainer;
+use super::rule_set::RuleSet;
+use super::solution::Solution;
+use super::solver::{make_solver, SolverConfig};
+use super::tuning::{Parameter, Tuner, TuningMethod};
+use super::types::{CityNames, TimeMatrix};
+
+// The run stops at the first criterion met, iters_count of the solver is the last resort
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct StopCriteria {
+    // Seconds
+    pub time_limit: Option<f64>,
+    // Distance with the rule penalties
+    pub target_distance: Option<f64>,
+    // Iterations without improvement of the best cost
+    pub stagnation: Option<usize>,
+}
+
+// A whole run described in a TOML or JSON file. Paths are relative to the file
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Experiment {
+    pub instance: String,
+    pub time_matrix: Option<String>,
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub stop: StopCriteria,
+    #[serde(flatten)]
+    pub solver: SolverConfig,
+}
+
+impl Experiment {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let mut experiment: Self = read_config(path)?;
+
+        if let Some(dir) = path.parent() {
+            experiment.instance = resolve(dir, &experiment.instance);
+            experiment.time_matrix = experiment.time_matrix.map(|time_matrix| resolve(dir, &time_matrix));
+        }
+        load_rules_file(&mut experiment.solver, path.parent())?;
+
+        Ok(experiment)
+    }
+
+    // The rule file is relative to the current directory
+    pub fn from_toml(content: &str) -> Result<Self, String> {
+        let mut experiment: Self = toml::from_str(content).map_err(|error| error.to_string())?;
+        load_rules_file(&mut experiment.solver, None)?;
+        Ok(experiment)
+    }
+
+    pub fn from_json(content: &str) -> Result<Self, String> {
+        let mut experiment: Self = serde_json::from_str(content).map_err(|error| error.to_string())?;
+        load_rules_file(&mut experiment.solver, None)?;
+        Ok(experiment)
+    }
+
+    // Matrices with the city names if the instance has them
+    pub fn load(&self) -> Result<Instance, String> {
+        let (matrix, city_names) = read_named_matrix(&self.instance)?;
+        let mut instance = Instance::new(self.instance.clone(), matrix);
+
+        if let Some(path) = &self.time_matrix {
+            let time_matrix: TimeMatrix = read_matrix(path)?;
+            if time_matrix.len() != instance.matrix.len() {
+                return Err("Time matrix size is not equal distance matrix".to_string());
+            }
+            instance = instance.time_matrix(time_matrix);
+        }
+        if !city_names.is_empty() {
+            instance = instance.city_names(city_names);
+        }
+
+        Ok(instance)
+    }
+
+    pub fn run(&self) -> Result<Vec<Solution>, String> {
+        self.run_on(&self.load()?)
+    }
+
+    pub fn run_on(&self, instance: &Instance) -> Result<Vec<Solution>, String> {
+        self.check_rules(instance)?;
+
+        // The seed is reset on every path, a config error must not leave the thread seeded
+        set_seed(self.seed);
+        let solver = match make_solver(
+            &self.solver,
+            instance.matrix.clone(),
+            instance.time_matrix.clone(),
+            &instance.city_names,
+        ) {
+            Ok(solver) => solver,
+            Err(error) => {
+                set_seed(None);
+                return Err(error);
+            }
+        };
+
+        let started = Instant::now();
+        let mut best = f64::INFINITY;
+        let mut stagnation = 0;
+        let result = solver.run_with_observer(&mut |_, solution| {
+            if solution.cost() < best {
+                best = solution.cost();
+                stagnation = 0;
+            } else {
+                stagnation += 1;
+            }
+
+            let stop = self.stop.time_limit.is_some_and(|limit| started.elapsed().as_secs_f64() >= limit)
+                || self.stop.target_distance.is_some_and(|target| best <= target)
+                || self.stop.stagnation.is_some_and(|limit| stagnation >= limit);
+            !stop
+        });
+        set_seed(None);
+
+        result.map_err(|error| error.to_string())
+    }
+
+    // Explains the rules of the solver on the solutions of the instance
+    pub fn explainer(&self, instance: &Instance) -> Result<RuleExplainer, String> {
+        let rules = self.solver.rules.clone();
+        let (matrix, time_matrix) = (instance.matrix.clone(), instance.time_matrix.clone());
+        match self.solver.rules_language {
+            Some(language) => RuleExplainer::new_in(rules, language, &instance.city_names, matrix, time_matrix),
+            None => RuleExplainer::new(rules, &instance.city_names, matrix, time_matrix),
+        }
+    }
+
+    // Rule errors are reported before the run instead of panicking during it
+    pub fn check_rules(&self, instance: &Instance) -> Result<(), String> {
+        self.solver.check_rules(&instance.city_names, instance.matrix.len(), instance.time_matrix.is_some())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InstanceConfig {
+    pub name: String,
+    pub instance: String,
+    pub time_matrix: Option<String>,
+    pub optimum: Option<f64>,
+    pub target: Option<f64>,
+}
+
+impl InstanceConfig {
+    pub fn load(self) -> Result<Instance, String> {
+        let (matrix, city_names) = read_named_matrix(&self.instance)?;
+        let mut instance = Instance::new(self.name, matrix);
+        if let Some(path) = self.time_matrix {
+            instance = instance.time_matrix(read_matrix(&path)?);
+        }
+        if !city_names.is_empty() {
+            instance = instance.city_names(city_names);
+        }
+        if let Some(optimum) = self.optimum {
+            instance = instance.optimum(optimum);
+        }
+        if let Some(target) = self.target {
+            instance = instance.target(target);
+        }
+        Ok(instance)
+    }
+
+    fn resolve(&mut self, dir: &Path) {
+        self.instance = resolve(dir, &self.instance);
+        self.time_matrix = self.time_matrix.as_ref().map(|time_matrix| resolve(dir, time_matrix));
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NamedSolverConfig {
+    pub name: String,
+    #[serde(flatten)]
+    pub solver: SolverConfig,
+}
+
+// Benchmark described in a TOML or JSON file, instances are listed in [[instances]] and solvers in [[solvers]]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BenchmarkConfig {
+    pub runs_count: Option<usize>,
+    pub seed: Option<u64>,
+    pub instances: Vec<InstanceConfig>,
+    pub solvers: Vec<NamedSolverConfig>,
+}
+
+impl BenchmarkConfig {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let mut config: Self = read_config(path)?;
+        if let Some(dir) = path.parent() {
+            config.instances.iter_mut().for_each(|instance| instance.resolve(dir));
+        }
+        for named in config.solvers.iter_mut() {
+            load_rules_file(&mut named.solver, path.parent())?;
+        }
+        Ok(config)
+    }
+
+    pub fn into_benchmark(self) -> Result<Benchmark, String> {
+        let instances = self.instances.into_iter().map(InstanceConfig::load).collect::<Result<_, _>>()?;
+        let solvers = self.solvers.into_iter().map(|config| (config.name, config.solver)).collect();
+
+        let mut benchmark = Benchmark::new(instances, solvers);
+        if let Some(runs_count) = self.runs_count {
+            benchmark = benchmark.runs_count(runs_count);
+        }
+        if let Some(seed) = self.seed {
+            benchmark = benchmark.seed(seed);
+        }
+
+        Ok(benchmark)
+    }
+}
+
+// Tuning described in a TOML or JSON file. The solver config is the base one, [[parameters]] are tuned in it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TuningConfig {
+    pub budget: Option<usize>,
+    pub seed: Option<u64>,
+    pub method: Option<TuningMethod>,
+    pub instances: Vec<InstanceConfig>,
+    pub parameters: Vec<Parameter>,
+    #[serde(flatten)]
+    pub solver: SolverConfig,
+}
+
+impl TuningConfig {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let mut config: Self = read_config(path)?;
+        if let Some(dir) = path.parent() {
+            config.instances.iter_mut().for_each(|instance| instance.resolve(dir));
+        }
+        load_rules_file(&mut config.solver, path.parent())?;
+        Ok(config)
+    }
+
+    pub fn into_tuner(self) -> Result<Tuner, String> {
+        let instances = self.instances.into_iter().map(InstanceConfig::load).collect::<Result<_, _>>()?;
+
+        let mut tuner = Tuner::new(self.solver, self.parameters, instances)?;
+        if let Some(budget) = self.budget {
+            tuner = tuner.budget(budget);
+        }
+        if let Some(seed) = self.seed {
+            tuner = tuner.seed(seed);
+        }
+        if let Some(method) = self.method {
+            tuner = tuner.method(method);
+        }
+
+        Ok(tuner)
+    }
+}
+
+// JSON by the extension, TOML otherwise
+fn read_config<C: DeserializeOwned>(path: &Path) -> Result<C, String> {
+    let content = fs::read_to_string(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+
+    if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_str(&content).map_err(|error| format!("{}: {}", path.display(), error))
+    } else {
+        toml::from_str(&content).map_err(|error| format!("{}: {}", path.display(), error))
+    }
+}
+
+pub fn read_matrix<V: FromStr + DeserializeOwned>(path: impl AsRef<Path>) -> Result<Vec<Vec<V>>, String> {
+    read_named_matrix(path).map(|(matrix, _)| matrix)
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MatrixFile<V> {
+    Plain(Vec<Vec<V>>),
+    Named { names: CityNames, matrix: Vec<Vec<V>> },
+}
+
+// JSON array of rows or an object with names and matrix fields. Otherwise rows of numbers separated by spaces
+// or commas, the first row may hold the city names instead; lines starting with # are skipped
+pub fn read_named_matrix<V: FromStr + DeserializeOwned>(path: impl AsRef<Path>) -> Result<(Vec<Vec<V>>, CityNames), String> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+
+    let (rows, names): (Vec<Vec<V>>, CityNames) = if path.extension().is_some_and(|ext| ext == "json") {
+        match serde_json::from_str(&content).map_err(|error| format!("{}: {}", path.display(), error))? {
+            MatrixFile::Plain(rows) => (rows, Vec::new()),
+            MatrixFile::Named { names, matrix } => (matrix, names),
+        }
+    } else {
+        let mut lines = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| line.split(|c: char| c.is_whitespace() || c == ',').filter(|value| !value.is_empty()).collect::<Vec<&str>>())
+            .peekable();
+
+        let names = match lines.peek() {
+            Some(header) if header.iter().any(|value| value.parse::<V>().is_err()) => {
+                lines.next().unwrap().into_iter().map(str::to_string).collect()
+            }
+            _ => Vec::new(),
+        };
+
+        let rows = lines
+            .map(|values| {
+                values
+                    .into_iter()
+                    .map(|value| value.parse().map_err(|_| format!("{}: invalid value `{}`", path.display(), value)))
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+        (rows, names)
+    };
+
+    if rows.iter().any(|row| row.len() != rows.len()) {
+        return Err(format!("{}: matrix is not squared", path.display()));
+    }
+    if !names.is_empty() && names.len() != rows.len() {
+        return Err(format!("{}: {} city names for {} cities", path.display(), names.len(), rows.len()));
+    }
+    if let Some((idx, name)) = names.iter().enumerate().find(|(idx, name)| names[..*idx].contains(name)) {
+        return Err(format!("{}: city name `{}` is repeated at {}", path.display(), name, idx));
+    }
+
+    Ok((rows, names))
+}
+
+// Rules of the file go after the inline ones. The file is taken out, so the rules are not added twice
+fn load_rules_file(solver: &mut SolverConfig, dir: Option<&Path>) -> Result<(), String> {
+    if let Some(path) = solver.rules_file.take() {
+        let path = match dir {
+            Some(dir) => resolve(dir, &path),
+            None => path,
+        };
+        solver.rules.extend(RuleSet::from_file(path)?.rules());
+    }
+    Ok(())
+}
+
+fn resolve(dir: &Path, path: &str) -> String {
+    if Path::new(path).is_absolute() {
+        path.to_string()
+    } else {
+        dir.join(path).to_string_lossy().into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use rand::Rng;
+    use super::*;
+    use crate::algorithms::helpers::make_rng;
+    use crate::problems::travelling_salesman::fixtures;
+    use crate::problems::travelling_salesman::solver::AlgorithmConfig;
+
+    fn experiments_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("experiments")
+    }
+
+    // Fresh directory for the files of a test
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("phd_cand_experiment_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    const EXPERIMENT: &str = r#"
+        instance = "matrix.txt"
+        seed = 7
+        iters_count = 50
+        rules = ["8 follows 0 : -300"]
+
+        [algorithm]
+        type = "simulated_annealing"
+        neighbour = "two_opt"
+        cooling = { geometric = 0.9 }
+
+        [stop]
+        stagnation = 20
+    "#;
+
+    #[test]
+    fn experiment_file_is_read_relative_to_its_directory() {
+        let experiment = Experiment::from_file(experiments_dir().join("genetic.toml")).unwrap();
+
+        assert_eq!(PathBuf::from(&experiment.instance), experiments_dir().join("matrix.txt"));
+        assert_eq!(experiment.time_matrix.map(PathBuf::from), Some(experiments_dir().join("time_matrix.txt")));
+        assert_eq!(experiment.seed, Some(42));
+        assert_eq!(experiment.stop.stagnation, Some(200));
+        // The rules of the included file come first
+        assert_eq!(experiment.solver.rules, vec!["2 следует за 0 : 100", "8 следует за 0 : -300", "3 следует за 0 : -300"]);
+        assert!(experiment.solver.rules_file.is_none());
+        assert!(matches!(experiment.solver.algorithm, AlgorithmConfig::Genetic { p_mutation: Some(p), .. } if p == 0.3));
+    }
+
+    #[test]
+    fn toml_and_json_experiments_are_the_same() {
+        let from_toml = Experiment::from_toml(EXPERIMENT).unwrap();
+        let from_json = Experiment::from_json(&serde_json::to_string(&from_toml).unwrap()).unwrap();
+
+        assert_eq!(format!("{:?}", from_json), format!("{:?}", from_toml));
+        assert_eq!(from_toml.solver.iters_count, Some(50));
+        assert_eq!(from_toml.stop.time_limit, None);
+    }
+
+    #[test]
+    fn malformed_experiments_are_errors() {
+        assert!(Experiment::from_toml("seed = 1").is_err());
+        assert!(Experiment::from_toml(&EXPERIMENT.replace("two_opt", "three_opt")).unwrap_err().contains("three_opt"));
+        assert!(Experiment::from_file(experiments_dir().join("missing.toml")).is_err());
+    }
+
+    #[test]
+    fn seeded_runs_are_repeated() {
+        let experiment = Experiment::from_toml(EXPERIMENT).unwrap();
+        let instance = Instance::new("ten_cities", fixtures::matrix());
+
+        let first = experiment.run_on(&instance).unwrap();
+        let second = experiment.run_on(&instance).unwrap();
+
+        assert_eq!(first[0].path, second[0].path);
+        assert_eq!(first[0].cost(), second[0].cost());
+    }
+
+    #[test]
+    fn config_errors_leave_the_thread_unseeded() {
+        let mut experiment = Experiment::from_toml(EXPERIMENT).unwrap();
+        experiment.solver.actors_count = Some(0);
+
+        assert!(experiment.run_on(&Instance::new("ten_cities", fixtures::matrix())).is_err());
+        let first: u64 = make_rng().gen();
+        let second: u64 = make_rng().gen();
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn target_distance_stops_the_run() {
+        let mut experiment = Experiment::from_toml(EXPERIMENT).unwrap();
+        experiment.solver.iters_count = Some(100_000);
+        experiment.stop = StopCriteria { target_distance: Some(f64::MAX), ..StopCriteria::default() };
+        let instance = Instance::new("ten_cities", fixtures::matrix());
+
+        let started = Instant::now();
+        experiment.run_on(&instance).unwrap();
+
+        assert!(started.elapsed().as_secs_f64() < 5.);
+    }
+
+    #[test]
+    fn rule_errors_are_reported_before_the_run() {
+        let experiment = Experiment::from_toml(&EXPERIMENT.replace("8 follows", "Rome follows")).unwrap();
+
+        let error = experiment.run_on(&Instance::new("ten_cities", fixtures::matrix())).unwrap_err();
+
+        assert!(error.contains("Rome"));
+    }
+
+    #[test]
+    fn tuning_file_makes_a_tuner() {
+        let mut config = TuningConfig::from_file(experiments_dir().join("tuning.toml")).unwrap();
+        assert_eq!(config.parameters.len(), 4);
+        assert!(config.clone().into_tuner().is_ok());
+
+        config.parameters.push(Parameter::real("temperature", 1., 10.));
+        assert!(config.into_tuner().err().unwrap().starts_with("temperature"));
+    }
+
+    #[test]
+    fn matrices_are_read_with_names() {
+        let dir = temp_dir("matrices");
+        fs::write(dir.join("plain.txt"), "# comment\n0 1\n1, 0\n").unwrap();
+        fs::write(dir.join("named.txt"), "a b\n0 1\n1 0\n").unwrap();
+        fs::write(dir.join("named.json"), r#"{"names": ["a", "b"], "matrix": [[0, 1], [1, 0]]}"#).unwrap();
+
+        assert_eq!(read_named_matrix::<f64>(dir.join("plain.txt")).unwrap(), (vec![vec![0., 1.], vec![1., 0.]], Vec::new()));
+        for file in ["named.txt", "named.json"] {
+            let (matrix, names) = read_named_matrix::<usize>(dir.join(file)).unwrap();
+            assert_eq!(matrix, vec![vec![0, 1], vec![1, 0]]);
+            assert_eq!(names, vec!["a", "b"]);
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn malformed_matrices_are_errors() {
+        let dir = temp_dir("malformed");
+        fs::write(dir.join("not_squared.txt"), "0 1\n1\n").unwrap();
+        fs::write(dir.join("names_count.txt"), "a b c\n0 1\n1 0\n").unwrap();
+        fs::write(dir.join("repeated.txt"), "a a\n0 1\n1 0\n").unwrap();
+        fs::write(dir.join("value.txt"), "0 1\n1 x0\n").unwrap();
+
+        for (file, error) in [
+            ("not_squared.txt", "not squared"),
+            ("names_count.txt", "3 city names for 2 cities"),
+            ("repeated.txt", "`a` is repeated"),
+            ("value.txt", "invalid value `x0`"),
+        ] {
+            assert!(read_matrix::<f64>(dir.join(file)).unwrap_err().contains(error), "{}", file);
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
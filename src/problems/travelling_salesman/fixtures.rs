@@ -0,0 +1,52 @@
+use super::types::{Matrix, TimeMatrix};
+
+// Length of the optimal tour of the matrix
+pub const OPTIMUM: f64 = 246.;
+
+// The demo instance of main.rs
+pub fn matrix() -> Matrix {
+    vec![
+        vec![0.0, 29.0, 20.0, 21.0, 16.0, 31.0, 100.0, 12.0, 4.0, 31.0],
+        vec![29.0, 0.0, 15.0, 29.0, 28.0, 40.0, 72.0, 21.0, 29.0, 41.0],
+        vec![20.0, 15.0, 0.0, 15.0, 14.0, 25.0, 81.0, 9.0, 23.0, 27.0],
+        vec![21.0, 29.0, 15.0, 0.0, 4.0, 12.0, 92.0, 12.0, 25.0, 13.0],
+        vec![16.0, 28.0, 14.0, 4.0, 0.0, 16.0, 94.0, 9.0, 20.0, 16.0],
+        vec![31.0, 40.0, 25.0, 12.0, 16.0, 0.0, 95.0, 24.0, 36.0, 3.0],
+        vec![100.0, 72.0, 81.0, 92.0, 94.0, 95.0, 0.0, 90.0, 101.0, 99.0],
+        vec![12.0, 21.0, 9.0, 12.0, 9.0, 24.0, 90.0, 0.0, 15.0, 25.0],
+        vec![4.0, 29.0, 23.0, 25.0, 20.0, 36.0, 101.0, 15.0, 0.0, 35.0],
+        vec![31.0, 41.0, 27.0, 13.0, 16.0, 3.0, 99.0, 25.0, 35.0, 0.0],
+    ]
+}
+
+// The demo time matrix of main.rs
+
+pub fn time_matrix() -> TimeMatrix {
+    vec![
+        vec![0, 45, 30, 12, 18, 67, 23, 56, 41, 39],
+        vec![45, 0, 50, 28, 37, 52, 19, 33, 60, 25],
+        vec![30, 50, 0, 15, 63, 42, 71, 44, 22, 58],
+        vec![12, 28, 15, 0, 36, 54, 20, 47, 65, 31],
+        vec![18, 37, 63, 36, 0, 29, 48, 59, 11, 70],
+        vec![67, 52, 42, 54, 29, 0, 35, 16, 62, 40],
+        vec![23, 19, 71, 20, 48, 35, 0, 53, 26, 64],
+        vec![56, 33, 44, 47, 59, 16, 53, 0, 38, 21],
+        vec![41, 60, 22, 65, 11, 62, 26, 38, 0, 57],
+        vec![39, 25, 58, 31, 70, 40, 64, 21, 57, 0],
+    ]
+}
+
+// Symmetric matrix of points on a circle, the optimum visits them in order
+pub fn circle(cities_count: usize) -> Matrix {
+    let point = |city: usize| {
+        let angle = 2. * std::f64::consts::PI * city as f64 / cities_count as f64;
+        (angle.cos(), angle.sin())
+    };
+
+    (0..cities_count)
+        .map(|a| (0..cities_count).map(|b| {
+            let ((xa, ya), (xb, yb)) = (point(a), point(b));
+            ((xa - xb).powi(2) + (ya - yb).powi(2)).sqrt()
+        }).collect())
+        .collect()
+}
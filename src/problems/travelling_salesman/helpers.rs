@@ -1,8 +1,12 @@
-use crate::algorithms::types::{FitnessFuncRaw, FitnessFuncs, Population};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use crate::algorithms::individual::Individual;
+use crate::algorithms::types::{FitnessFuncRaw, FitnessFuncs, FitnessRaw, Population};
 use crate::problems::travelling_salesman::solution::Solution;
-use super::types::{Matrix, City, TimeMatrix, RuleFn};
+use super::types::{Matrix, City, TimeMatrix, RuleFn, RuleFitness};
 
-pub fn calculate_distance(matrix: &Matrix, cities: &Vec<City>) -> f64 {
+pub fn calculate_distance(matrix: &Matrix, cities: &[City]) -> f64 {
     let mut sum: f64 = 0.;
     let cities_count = cities.len();
     for i in 0..cities_count {
@@ -17,7 +21,13 @@ pub fn calculate_distance(matrix: &Matrix, cities: &Vec<City>) -> f64 {
     sum
 }
 
-pub fn calculate_time(time_matrix: &TimeMatrix, cities: &Vec<City>) -> usize {
+pub fn is_permutation(cities: &[City], cities_count: usize) -> bool {
+    let mut visited = vec![false; cities_count];
+    cities.len() == cities_count
+        && cities.iter().all(|city| *city < cities_count && !std::mem::replace(&mut visited[*city], true))
+}
+
+pub fn calculate_time(time_matrix: &TimeMatrix, cities: &[City]) -> usize {
     let mut sum: usize = 0;
     let cities_count = cities.len();
     for i in 0..cities_count {
@@ -32,22 +42,67 @@ pub fn calculate_time(time_matrix: &TimeMatrix, cities: &Vec<City>) -> usize {
     sum
 }
 
-pub fn calculate_distance_with_rules(matrix: Matrix, rules: Vec<RuleFn>) -> FitnessFuncRaw<City> {
-    Box::new(move |cities: &Vec<City>| {
-        let penalty = if rules.is_empty() {
-            0.
-        } else {
-            let mut p = 0;
-            for evaluate in rules.iter() {
-                match evaluate(cities) {
-                    Some(pen) => p += pen,
-                    None => return None
+type Evaluation = (f64, Option<f64>);
+
+// Tours evaluated lately. When the current generation is full it becomes the previous one, the tours
+// in use, e.g. the best one reported every iteration, are moved back to the current one on a hit
+#[derive(Default)]
+struct Evaluations {
+    current: HashMap<Vec<City>, Evaluation>,
+    previous: HashMap<Vec<City>, Evaluation>,
+}
+
+impl Evaluations {
+    const CAPACITY: usize = 4096;
+
+    fn get(&mut self, cities: &Vec<City>) -> Option<Evaluation> {
+        if let Some(evaluation) = self.current.get(cities) {
+            return Some(*evaluation);
+        }
+        let evaluation = self.previous.remove(cities)?;
+        self.insert(cities.clone(), evaluation);
+        Some(evaluation)
+    }
+
+    fn insert(&mut self, cities: Vec<City>, evaluation: Evaluation) {
+        if self.current.len() >= Self::CAPACITY {
+            self.previous = std::mem::take(&mut self.current);
+        }
+        self.current.insert(cities, evaluation);
+    }
+}
+
+// The rules are evaluated once for a tour, the fitness and the solutions get the distance and the penalty
+// from here. The solutions of the tours the fitness funcs have just evaluated are taken from the cache
+pub fn rule_fitness(matrix: Matrix, rules: Vec<RuleFn>) -> RuleFitness {
+    let evaluations = RefCell::new(Evaluations::default());
+
+    Rc::new(move |cities: &Vec<City>| {
+        if let Some(evaluation) = evaluations.borrow_mut().get(cities) {
+            return evaluation;
+        }
+
+        let mut penalty = Some(0);
+        for evaluate in rules.iter() {
+            match evaluate(cities) {
+                Some(pen) => penalty = penalty.map(|penalty| penalty + pen),
+                None => {
+                    penalty = None;
+                    break;
                 }
             }
-            p as f64
-        };
+        }
+        let evaluation = (calculate_distance(&matrix, cities), penalty.map(|penalty| penalty as f64));
 
-        Some(calculate_distance(&matrix, &cities) + penalty)
+        evaluations.borrow_mut().insert(cities.clone(), evaluation);
+        evaluation
+    })
+}
+
+pub fn calculate_distance_with_rules(rule_fitness: RuleFitness) -> FitnessFuncRaw<City> {
+    Box::new(move |cities: &Vec<City>| {
+        let (distance, penalty) = rule_fitness(cities);
+        penalty.map(|penalty| distance + penalty)
     })
 }
 
@@ -57,23 +112,41 @@ pub fn time_fitness(time_matrix: TimeMatrix) -> FitnessFuncRaw<City> {
     })
 }
 
-pub fn make_solutions(population: Population<City>, solutions_count: usize, fitness_funcs: &FitnessFuncs<City>) -> Vec<Solution> {
+pub fn make_fitness_funcs(rule_fitness: &RuleFitness, time_matrix: Option<TimeMatrix>) -> FitnessFuncs<City> {
+    let mut fitness_funcs = vec![
+        Box::new(calculate_distance_with_rules(rule_fitness.clone())) as FitnessFuncRaw<City>,
+    ];
+
+    if let Some(time_matrix) = time_matrix {
+        fitness_funcs.push(time_fitness(time_matrix))
+    }
+
+    fitness_funcs
+}
+
+// Fitnesses come from make_fitness_funcs, the time is the second one. The distance and the penalty
+// of the evaluation made for the fitness are taken from the cache of the rule fitness. None for an excluded tour
+fn split_fitnesses(rule_fitness: &RuleFitness, path: &Vec<City>, fitnesses: &[FitnessRaw]) -> Option<(f64, Option<usize>, f64)> {
+    let (distance, penalty) = rule_fitness(path);
+    let time = fitnesses.get(1).copied().flatten().map(|time| time as usize);
+
+    Some((distance, time, penalty?))
+}
+
+pub fn make_solutions(population: Population<City>, solutions_count: usize, rule_fitness: &RuleFitness) -> Vec<Solution> {
     let mut solutions: Vec<Solution> = Vec::new();
 
     for ind in population.into_iter() {
-        if let Some(fitness) = ind.fitness {
-            let distance = ind.fitnesses[0].unwrap();
-            let time = if fitness_funcs.len() > 1 {
-                Some(ind.fitnesses[1].unwrap() as usize)
-            } else {
-                None
-            };
+        if let (Some(fitness), Some((distance, time, penalty))) =
+            (ind.fitness, split_fitnesses(rule_fitness, &ind.value, &ind.fitnesses))
+        {
 
             solutions.push(Solution {
                 path: ind.value,
                 fitness,
                 distance,
                 time,
+                penalty,
             });
         }
 
@@ -83,4 +156,143 @@ pub fn make_solutions(population: Population<City>, solutions_count: usize, fitn
     }
 
     solutions
-}
\ No newline at end of file
+}
+
+// Solution of a single individual, used to report progress during the run. Excluded tours have no solution
+pub fn make_solution(individual: &Individual<City>, rule_fitness: &RuleFitness) -> Option<Solution> {
+    let (distance, time, penalty) = split_fitnesses(rule_fitness, &individual.value, &individual.fitnesses)?;
+
+    Some(Solution {
+        path: individual.value.clone(),
+        distance,
+        time,
+        penalty,
+        fitness: individual.fitness.unwrap_or(0.),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::travelling_salesman::fixtures;
+    use crate::problems::travelling_salesman::rules::parse_rule;
+
+    fn fitness(rules: &[&str]) -> RuleFitness {
+        let rules = rules
+            .iter()
+            .map(|rule| parse_rule(rule.to_string(), &[], fixtures::matrix(), None).unwrap())
+            .collect();
+        rule_fitness(fixtures::matrix(), rules)
+    }
+
+    fn individual(value: Vec<City>, fitness_funcs: &FitnessFuncs<City>) -> Individual<City> {
+        Individual { fitness: Some(0.), ..Individual::with_fitnesses(value, fitness_funcs) }
+    }
+
+    #[test]
+    fn rule_fitness_keeps_the_penalty_apart() {
+        let rule_fitness = fitness(&["1 следует за 0 : -300", "2 следует за 1 : 7", "ребро из 9 в 0 : исключить"]);
+        let path: Vec<City> = (0..10).collect();
+        let distance = calculate_distance(&fixtures::matrix(), &path);
+
+        assert_eq!(rule_fitness(&path), (distance, None));
+        let path: Vec<City> = vec![8, 7, 6, 5, 4, 3, 2, 1, 0, 9];
+        assert_eq!(rule_fitness(&path), (calculate_distance(&fixtures::matrix(), &path), Some(0.)));
+        let path: Vec<City> = vec![3, 0, 1, 2, 4, 5, 6, 7, 8, 9];
+        assert_eq!(rule_fitness(&path), (calculate_distance(&fixtures::matrix(), &path), Some(-293.)));
+        assert_eq!(calculate_distance_with_rules(rule_fitness)(&path), Some(calculate_distance(&fixtures::matrix(), &path) - 293.));
+    }
+
+    #[test]
+    fn solutions_get_the_distance_the_penalty_and_the_time() {
+        let rule_fitness = fitness(&["1 следует за 0 : -300"]);
+        let fitness_funcs = make_fitness_funcs(&rule_fitness, Some(fixtures::time_matrix()));
+        let paths: Vec<Vec<City>> = vec![(0..10).collect(), (0..10).rev().collect()];
+        let population: Population<City> = paths.iter().map(|path| individual(path.clone(), &fitness_funcs)).collect();
+
+        let solutions = make_solutions(population, 10, &rule_fitness);
+
+        assert_eq!(solutions.len(), 2);
+        for (solution, path) in solutions.iter().zip(&paths) {
+            assert_eq!(solution.distance, calculate_distance(&fixtures::matrix(), path));
+            assert_eq!(solution.time, Some(calculate_time(&fixtures::time_matrix(), path)));
+        }
+        assert_eq!((solutions[0].penalty, solutions[1].penalty), (-300., 0.));
+        assert_eq!(solutions[0].cost(), solutions[0].distance - 300.);
+    }
+
+    #[test]
+    fn excluded_individuals_make_no_solution() {
+        let rule_fitness = fitness(&["1 следует за 0 : исключить"]);
+        let fitness_funcs = make_fitness_funcs(&rule_fitness, None);
+        let excluded: Vec<City> = (0..10).collect();
+        let kept: Vec<City> = (0..10).rev().collect();
+        // Both fitnesses are set, so only the exclusion drops the tour
+        let population = vec![individual(excluded.clone(), &fitness_funcs), individual(kept.clone(), &fitness_funcs)];
+
+        assert!(make_solution(&population[0], &rule_fitness).is_none());
+        let solutions = make_solutions(population, 10, &rule_fitness);
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].path, kept);
+    }
+
+    #[test]
+    fn rules_run_once_for_a_reported_tour() {
+        let evaluations = Rc::new(std::cell::Cell::new(0));
+        let counter = Rc::clone(&evaluations);
+        let counting: RuleFn = Box::new(move |_| {
+            counter.set(counter.get() + 1);
+            Some(5)
+        });
+        let rule_fitness = rule_fitness(fixtures::matrix(), vec![counting]);
+        let fitness_funcs = make_fitness_funcs(&rule_fitness, Some(fixtures::time_matrix()));
+        let paths: Vec<Vec<City>> = vec![(0..10).collect(), (0..10).rev().collect()];
+        let population: Population<City> = paths.iter().map(|path| individual(path.clone(), &fitness_funcs)).collect();
+
+        let best = make_solution(&population[0], &rule_fitness).unwrap();
+        let solutions = make_solutions(population, 10, &rule_fitness);
+
+        assert_eq!(evaluations.get(), 2);
+        assert_eq!(best.penalty, 5.);
+        assert_eq!(solutions.iter().map(|solution| solution.penalty).collect::<Vec<f64>>(), vec![5., 5.]);
+    }
+
+    #[test]
+    fn evaluations_in_use_are_kept() {
+        let mut evaluations = Evaluations::default();
+        let kept: Vec<City> = vec![0, 1];
+        evaluations.insert(kept.clone(), (1., Some(0.)));
+
+        for city in 0..3 * Evaluations::CAPACITY {
+            evaluations.insert(vec![city], (1., None));
+            assert_eq!(evaluations.get(&kept), Some((1., Some(0.))));
+        }
+        assert!(evaluations.current.len() + evaluations.previous.len() <= 2 * Evaluations::CAPACITY);
+        assert_eq!(evaluations.get(&vec![0]), None);
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn solutions_of_a_population_with_excluded_tours_round_trip_through_json() {
+        let rule_fitness = fitness(&["1 следует за 0 : исключить", "9 следует за 0 : 10"]);
+        let fitness_funcs = make_fitness_funcs(&rule_fitness, None);
+        let paths: Vec<Vec<City>> = vec![(0..10).collect(), vec![0, 9, 8, 7, 6, 5, 4, 3, 2, 1]];
+        let population: Population<City> = paths.iter().map(|path| individual(path.clone(), &fitness_funcs)).collect();
+
+        let solutions = make_solutions(population, 10, &rule_fitness);
+        let parsed: Vec<Solution> = serde_json::from_str(&serde_json::to_string(&solutions).unwrap()).unwrap();
+
+        assert_eq!(parsed.len(), 1);
+        assert_eq!((parsed[0].distance, parsed[0].penalty), (solutions[0].distance, 10.));
+        assert_eq!(parsed[0].cost(), solutions[0].cost());
+    }
+
+    #[test]
+    fn solutions_count_is_kept() {
+        let rule_fitness = fitness(&[]);
+        let fitness_funcs = make_fitness_funcs(&rule_fitness, None);
+        let population: Population<City> = (0..5).map(|_| individual((0..10).collect(), &fitness_funcs)).collect();
+
+        assert_eq!(make_solutions(population, 3, &rule_fitness).len(), 3);
+    }
+}
@@ -1,5 +1,19 @@
 pub mod types;
 pub mod algorithms;
+pub mod benchmark;
+pub mod constraints;
+pub mod constructions;
+pub mod exact;
+#[cfg(test)]
+mod fixtures;
+#[cfg(feature = "config")]
+pub mod experiment;
 pub mod helpers;
+pub mod pipeline;
+pub mod report;
+pub mod rule_set;
 pub mod rules;
-pub mod solution;
\ No newline at end of file
+pub mod solution;
+pub mod solver;
+pub mod tuning;
+pub mod validation;
\ No newline at end of file
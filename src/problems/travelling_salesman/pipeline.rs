@@ -0,0 +1,257 @@
+use crate::algorithms::constants::SOLUTIONS_COUNT;
+use crate::algorithms::helpers::is_same_tour;
+use crate::algorithms::operators::best_two_opt_move;
+use super::algorithms::ant_colony::algorithm::TSAntColonyAlgorithm;
+use super::algorithms::bee_colony::algorithm::TSBeeColonyAlgorithm;
+use super::algorithms::genetic::algorithm::TSGeneticAlgorithm;
+use super::helpers::{calculate_time, rule_fitness};
+use super::rules::parse_rule;
+use super::solution::Solution;
+use super::types::{City, Matrix, RuleFitness, RuleStr, TimeMatrix};
+
+pub enum Stage {
+    Genetic(TSGeneticAlgorithm),
+    BeeColony(TSBeeColonyAlgorithm),
+    AntColony(TSAntColonyAlgorithm),
+    TwoOpt(TwoOptPolish),
+}
+
+impl Stage {
+    pub fn run_with(&self, initial_paths: Vec<Vec<City>>) -> Result<Vec<Solution>, &'static str> {
+        match self {
+            Stage::Genetic(algorithm) => algorithm.run_with(initial_paths),
+            Stage::BeeColony(algorithm) => algorithm.run_with(initial_paths),
+            Stage::AntColony(algorithm) => algorithm.run_with(&initial_paths),
+            Stage::TwoOpt(polish) => Ok(polish.run_with(initial_paths)),
+        }
+    }
+}
+
+// Local search stage, applies improving 2-opt moves to every given path until none is left
+pub struct TwoOptPolish {
+    matrix: Matrix,
+    time_matrix: Option<TimeMatrix>,
+    rule_fitness: RuleFitness,
+}
+
+impl TwoOptPolish {
+    pub fn new(matrix: Matrix, time_matrix: Option<TimeMatrix>, city_names: &[String], rules: Vec<RuleStr>) -> Self {
+        let rules = rules.into_iter().map(|rule_str| {
+            parse_rule(rule_str, city_names, matrix.clone(), time_matrix.clone())
+                .unwrap_or_else(|error| panic!("{}", error))
+        }).collect();
+
+        Self {
+            rule_fitness: rule_fitness(matrix.clone(), rules),
+            matrix,
+            time_matrix,
+        }
+    }
+
+    pub fn run_with(&self, initial_paths: Vec<Vec<City>>) -> Vec<Solution> {
+        initial_paths.into_iter().filter_map(|path| self.polish(path)).collect()
+    }
+
+    fn polish(&self, mut path: Vec<City>) -> Option<Solution> {
+        let (mut distance, penalty) = (self.rule_fitness)(&path);
+        let mut penalty = penalty?;
+
+        while let Some((i, j)) = best_two_opt_move(&path, &|a: &City, b: &City| self.matrix[*a][*b]) {
+            // The move shortens the tour, but rules may still make it worse
+            let mut candidate = path.clone();
+            candidate[i + 1..=j].reverse();
+
+            match (self.rule_fitness)(&candidate) {
+                (candidate_distance, Some(candidate_penalty)) if candidate_distance + candidate_penalty < distance + penalty => {
+                    path = candidate;
+                    distance = candidate_distance;
+                    penalty = candidate_penalty;
+                }
+                _ => break,
+            }
+        }
+
+        let time = self.time_matrix.as_ref().map(|time_matrix| calculate_time(time_matrix, &path));
+        Some(Solution { path, distance, time, penalty, fitness: 0. })
+    }
+}
+
+pub struct TSPipeline {
+    pub stages: Vec<Stage>,
+    pub solutions_count: usize,
+}
+
+impl TSPipeline {
+    pub fn new(stages: Vec<Stage>) -> Self {
+        Self {
+            stages,
+            solutions_count: SOLUTIONS_COUNT,
+        }
+    }
+
+    pub fn solutions_count(mut self, solutions_count: usize) -> Self {
+        self.solutions_count = solutions_count;
+        self
+    }
+
+    // Stages run in sequence, each one starts from the solutions of the previous one
+    pub fn run(&self) -> Result<Vec<Solution>, &'static str> {
+        let mut paths: Vec<Vec<City>> = Vec::new();
+        let mut solutions: Vec<Solution> = Vec::new();
+
+        for stage in self.stages.iter() {
+            let stage_solutions = stage.run_with(paths)?;
+            paths = stage_solutions.iter().map(|solution| solution.path.clone()).collect();
+            solutions.extend(stage_solutions);
+        }
+
+        Ok(aggregate_solutions(solutions, self.solutions_count))
+    }
+
+    // Stages run independently from scratch, the best of their solutions are kept
+    pub fn run_portfolio(&self) -> Result<Vec<Solution>, &'static str> {
+        let mut solutions: Vec<Solution> = Vec::new();
+
+        for stage in self.stages.iter() {
+            solutions.extend(stage.run_with(Vec::new())?);
+        }
+
+        Ok(aggregate_solutions(solutions, self.solutions_count))
+    }
+}
+
+// Fitnesses of different stages are not comparable, so they are normalized again over all solutions
+fn aggregate_solutions(solutions: Vec<Solution>, solutions_count: usize) -> Vec<Solution> {
+    let mut unique: Vec<Solution> = Vec::with_capacity(solutions.len());
+    for solution in solutions {
+        if !unique.iter().any(|other| is_same_tour(&other.path, &solution.path)) {
+            unique.push(solution);
+        }
+    }
+
+    let normalize = |values: Vec<f64>| -> Vec<f32> {
+        let min = values.iter().cloned().fold(f64::MAX, f64::min);
+        let max = values.iter().cloned().fold(f64::MIN, f64::max);
+        values.into_iter().map(|value| if max > min { ((value - min) / (max - min)) as f32 } else { 0. }).collect()
+    };
+
+    let distances = normalize(unique.iter().map(|solution| solution.cost()).collect());
+    let times = normalize(unique.iter().map(|solution| solution.time.unwrap_or(0) as f64).collect());
+    for (solution, (distance, time)) in unique.iter_mut().zip(distances.into_iter().zip(times)) {
+        solution.fitness = distance + time;
+    }
+
+    unique.sort_by(|a, b| a.fitness.total_cmp(&b.fitness));
+    unique.truncate(solutions_count);
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::helpers::set_seed;
+    use crate::problems::travelling_salesman::algorithms::ant_colony::builder::TSAntColonyAlgorithmBuilder;
+    use crate::problems::travelling_salesman::fixtures;
+    use crate::problems::travelling_salesman::helpers::{calculate_distance, is_permutation};
+
+    fn solution(path: Vec<City>, distance: f64, time: Option<usize>) -> Solution {
+        Solution { path, distance, time, penalty: 0., fitness: 0. }
+    }
+
+    #[test]
+    fn polish_untangles_a_circle() {
+        let matrix = fixtures::circle(8);
+        let polish = TwoOptPolish::new(matrix.clone(), None, &[], Vec::new());
+
+        let solutions = polish.run_with(vec![vec![0, 4, 1, 5, 2, 6, 3, 7]]);
+
+        assert_eq!(solutions.len(), 1);
+        assert!(is_same_tour(&solutions[0].path, &(0..8).collect::<Vec<City>>()));
+        assert_eq!(solutions[0].distance, calculate_distance(&matrix, &solutions[0].path));
+        assert_eq!(solutions[0].penalty, 0.);
+    }
+
+    #[test]
+    fn polish_keeps_the_penalties_apart() {
+        let matrix = fixtures::circle(8);
+        let rules = vec!["edge from 0 to 4 : -1000".to_string()];
+        let polish = TwoOptPolish::new(matrix.clone(), None, &[], rules);
+
+        let solutions = polish.run_with(vec![vec![0, 4, 1, 5, 2, 6, 3, 7]]);
+
+        assert_eq!(solutions[0].penalty, -1000.);
+        assert_eq!(solutions[0].distance, calculate_distance(&matrix, &solutions[0].path));
+        assert!(solutions[0].path.windows(2).any(|edge| edge == [0, 4]));
+    }
+
+    #[test]
+    fn polish_gets_the_time() {
+        let polish = TwoOptPolish::new(fixtures::matrix(), Some(fixtures::time_matrix()), &[], Vec::new());
+
+        let solutions = polish.run_with(vec![(0..10).collect()]);
+
+        assert_eq!(solutions[0].time, Some(calculate_time(&fixtures::time_matrix(), &solutions[0].path)));
+    }
+
+    #[test]
+    fn aggregation_removes_equivalent_tours() {
+        let solutions = vec![
+            solution(vec![0, 1, 2, 3], 10., None),
+            solution(vec![2, 3, 0, 1], 10., None),
+            solution(vec![3, 2, 1, 0], 10., None),
+            solution(vec![0, 2, 1, 3], 12., None),
+        ];
+
+        let aggregated = aggregate_solutions(solutions, 10);
+
+        assert_eq!(aggregated.len(), 2);
+    }
+
+    #[test]
+    fn aggregation_sorts_by_distance_and_time() {
+        let solutions = vec![
+            solution(vec![0, 2, 1, 3], 12., Some(5)),
+            solution(vec![0, 1, 2, 3], 10., Some(5)),
+            solution(vec![0, 1, 3, 2], 10., Some(1)),
+        ];
+
+        let aggregated = aggregate_solutions(solutions, 2);
+
+        assert_eq!(aggregated.len(), 2);
+        assert_eq!(aggregated[0].path, vec![0, 1, 3, 2]);
+        assert_eq!(aggregated[0].fitness, 0.);
+        assert_eq!(aggregated[1].path, vec![0, 1, 2, 3]);
+        assert_eq!(aggregated[1].fitness, 1.);
+    }
+
+    #[test]
+    fn stages_run_in_sequence() {
+        set_seed(Some(7));
+        let matrix = fixtures::matrix();
+        let pipeline = TSPipeline::new(vec![
+            Stage::AntColony(TSAntColonyAlgorithmBuilder::new(matrix.clone()).actors_count(10).iters_count(5).build()),
+            Stage::TwoOpt(TwoOptPolish::new(matrix.clone(), None, &[], Vec::new())),
+        ]).solutions_count(3);
+
+        let solutions = pipeline.run().unwrap();
+
+        assert!(!solutions.is_empty() && solutions.len() <= 3);
+        for solution in solutions.iter() {
+            assert!(is_permutation(&solution.path, matrix.len()));
+        }
+        assert!(solutions.windows(2).all(|pair| pair[0].fitness <= pair[1].fitness));
+    }
+
+    #[test]
+    fn portfolio_stages_start_from_scratch() {
+        let matrix = fixtures::matrix();
+        // The polish has nothing to start from, so only the colony contributes
+        let pipeline = TSPipeline::new(vec![
+            Stage::TwoOpt(TwoOptPolish::new(matrix.clone(), None, &[], Vec::new())),
+            Stage::AntColony(TSAntColonyAlgorithmBuilder::new(matrix.clone()).actors_count(10).iters_count(5).build()),
+        ]);
+
+        assert!(pipeline.stages[0].run_with(Vec::new()).unwrap().is_empty());
+        assert!(!pipeline.run_portfolio().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,261 @@
+use std::fmt::{Display, Formatter};
+use super::helpers::{calculate_distance, calculate_time};
+use super::rules::{parse_condition_in, Action, Language};
+use super::types::{City, ConditionFn, Matrix, RuleStr, TimeMatrix};
+
+pub struct RuleReport {
+    pub rule: RuleStr,
+    // None when the condition cannot be evaluated, e.g. a time condition without the time matrix
+    pub held: Option<bool>,
+    pub action: Action,
+    // Added to the distance, None when the rule excludes the tour
+    pub penalty: Option<i64>,
+}
+
+pub struct EvaluationReport {
+    pub path: Vec<City>,
+    pub rules: Vec<RuleReport>,
+    pub raw_distance: f64,
+    pub penalty: i64,
+    // Raw distance with the penalties, None when a rule excludes the tour
+    pub distance: Option<f64>,
+    pub time: Option<usize>,
+}
+
+impl EvaluationReport {
+    pub fn is_excluded(&self) -> bool {
+        self.distance.is_none()
+    }
+
+    pub fn fired(&self) -> impl Iterator<Item = &RuleReport> {
+        self.rules.iter().filter(|report| report.held == Some(true))
+    }
+}
+
+impl Display for EvaluationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.distance {
+            Some(distance) => write!(f, "distance {} (raw {}, penalty {:+})", distance, self.raw_distance, self.penalty)?,
+            None => write!(f, "excluded (raw distance {}, penalty {:+})", self.raw_distance, self.penalty)?,
+        }
+        if let Some(time) = self.time {
+            write!(f, ", time {}", time)?;
+        }
+
+        for report in &self.rules {
+            let held = match report.held {
+                Some(true) => "held",
+                Some(false) => "not held",
+                None => "not evaluated",
+            };
+            let effect = match report.penalty {
+                Some(penalty) => format!("{:+}", penalty),
+                None => "excluded".to_string(),
+            };
+            write!(f, "\n  {:<13} {:>9}  {}", held, effect, report.rule)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Evaluates the rules one by one, so a solution shows why it was scored that way
+pub struct RuleExplainer {
+    rules: Vec<(RuleStr, ConditionFn, Action)>,
+    matrix: Matrix,
+    time_matrix: Option<TimeMatrix>,
+}
+
+impl RuleExplainer {
+    // The language is detected for every rule, like in parse_rule
+    pub fn new(rules: Vec<RuleStr>, city_names: &[String], matrix: Matrix, time_matrix: Option<TimeMatrix>) -> Result<Self, String> {
+        let rules = rules
+            .into_iter()
+            .map(|rule| {
+                let language = Language::detect(&rule);
+                Self::compile(rule, language, city_names, &matrix, &time_matrix)
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { rules, matrix, time_matrix })
+    }
+
+    pub fn new_in(
+        rules: Vec<RuleStr>,
+        language: Language,
+        city_names: &[String],
+        matrix: Matrix,
+        time_matrix: Option<TimeMatrix>,
+    ) -> Result<Self, String> {
+        let rules = rules
+            .into_iter()
+            .map(|rule| Self::compile(rule, language, city_names, &matrix, &time_matrix))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { rules, matrix, time_matrix })
+    }
+
+    fn compile(
+        rule: RuleStr,
+        language: Language,
+        city_names: &[String],
+        matrix: &Matrix,
+        time_matrix: &Option<TimeMatrix>,
+    ) -> Result<(RuleStr, ConditionFn, Action), String> {
+        let (condition, action) = parse_condition_in(rule.clone(), language, city_names, matrix.clone(), time_matrix.clone())?;
+        Ok((rule, condition, action))
+    }
+
+    // The penalised distance is the one calculate_distance_with_rules gives
+    pub fn explain(&self, path: &[City]) -> EvaluationReport {
+        let cities = path.to_vec();
+        let rules: Vec<RuleReport> = self.rules
+            .iter()
+            .map(|(rule, condition, action)| {
+                let held = condition(&cities);
+                let penalty = match (held, action) {
+                    (Some(true), Action::Exclude) => None,
+                    (Some(true), Action::Penalty(penalty)) => Some(*penalty),
+                    _ => Some(0),
+                };
+
+                RuleReport { rule: rule.clone(), held, action: *action, penalty }
+            })
+            .collect();
+
+        let raw_distance = calculate_distance(&self.matrix, path);
+        let penalty = rules.iter().filter_map(|report| report.penalty).sum();
+        let excluded = rules.iter().any(|report| report.penalty.is_none());
+
+        EvaluationReport {
+            path: cities,
+            rules,
+            raw_distance,
+            penalty,
+            distance: if excluded { None } else { Some(raw_distance + penalty as f64) },
+            time: self.time_matrix.as_ref().map(|time_matrix| calculate_time(time_matrix, path)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::travelling_salesman::fixtures;
+    use crate::problems::travelling_salesman::helpers::{calculate_distance_with_rules, rule_fitness};
+    use crate::problems::travelling_salesman::rules::parse_rule;
+
+    fn rules(rules: &[&str]) -> Vec<RuleStr> {
+        rules.iter().map(|rule| rule.to_string()).collect()
+    }
+
+    #[test]
+    fn rules_are_explained_one_by_one() {
+        let explainer = RuleExplainer::new(
+            rules(&["1 следует за 0 : 10", "0 следует за 1 : 20", "8 follows 7 : -5"]),
+            &[],
+            fixtures::matrix(),
+            None,
+        ).unwrap();
+        let path: Vec<City> = (0..10).collect();
+
+        let report = explainer.explain(&path);
+
+        let held: Vec<Option<bool>> = report.rules.iter().map(|rule| rule.held).collect();
+        let penalties: Vec<Option<i64>> = report.rules.iter().map(|rule| rule.penalty).collect();
+        assert_eq!(held, vec![Some(true), Some(false), Some(true)]);
+        assert_eq!(penalties, vec![Some(10), Some(0), Some(-5)]);
+        assert_eq!(report.fired().count(), 2);
+        assert_eq!(report.raw_distance, calculate_distance(&fixtures::matrix(), &path));
+        assert_eq!(report.penalty, 5);
+        assert_eq!(report.distance, Some(report.raw_distance + 5.));
+        assert!(!report.is_excluded());
+        assert_eq!(report.time, None);
+    }
+
+    #[test]
+    fn distance_is_the_one_of_the_fitness() {
+        let rule_strs = rules(&["1 следует за 0 : 10", "3 раньше 8 : 7", "{4, 5, 6} подряд : -3"]);
+        let explainer = RuleExplainer::new(rule_strs.clone(), &[], fixtures::matrix(), None).unwrap();
+        let rule_fns = rule_strs
+            .into_iter()
+            .map(|rule| parse_rule(rule, &[], fixtures::matrix(), None).unwrap())
+            .collect();
+        let fitness = calculate_distance_with_rules(rule_fitness(fixtures::matrix(), rule_fns));
+
+        for path in [(0..10).collect(), (0..10).rev().collect(), vec![3, 0, 8, 1, 2, 9, 5, 4, 6, 7]] {
+            assert_eq!(explainer.explain(&path).distance, fitness(&path));
+        }
+    }
+
+    #[test]
+    fn excluded_tours_keep_the_penalties() {
+        let explainer = RuleExplainer::new(
+            rules(&["ребро из 9 в 0 : исключить", "1 следует за 0 : 10"]),
+            &[],
+            fixtures::matrix(),
+            None,
+        ).unwrap();
+
+        let report = explainer.explain(&(0..10).collect::<Vec<City>>());
+
+        assert!(report.is_excluded());
+        assert_eq!(report.rules[0].penalty, None);
+        assert_eq!(report.rules[0].action, Action::Exclude);
+        assert_eq!(report.penalty, 10);
+    }
+
+    #[test]
+    fn time_rules_need_the_time_matrix() {
+        let rule_strs = rules(&["5 на времени [1,] : 10"]);
+        let path: Vec<City> = (0..10).collect();
+
+        let without_time = RuleExplainer::new(rule_strs.clone(), &[], fixtures::matrix(), None).unwrap().explain(&path);
+        let with_time = RuleExplainer::new(rule_strs, &[], fixtures::matrix(), Some(fixtures::time_matrix()))
+            .unwrap()
+            .explain(&path);
+
+        assert_eq!(without_time.rules[0].held, None);
+        assert_eq!(without_time.penalty, 0);
+        assert_eq!(with_time.rules[0].held, Some(true));
+        assert_eq!(with_time.time, Some(calculate_time(&fixtures::time_matrix(), &path)));
+    }
+
+    #[test]
+    fn language_may_be_given() {
+        let explainer = RuleExplainer::new_in(rules(&["1 follows 0 : 10"]), Language::English, &[], fixtures::matrix(), None)
+            .unwrap();
+
+        assert_eq!(explainer.explain(&(0..10).collect::<Vec<City>>()).penalty, 10);
+    }
+
+    #[test]
+    fn malformed_rules_are_errors() {
+        let error = RuleExplainer::new(rules(&["1 следует за Париж : 10"]), &[], fixtures::matrix(), None).err().unwrap();
+
+        assert_eq!(error, "Unknown city `Париж` in rule `1 следует за Париж : 10`");
+    }
+
+    #[test]
+    fn report_is_displayed() {
+        let explainer = RuleExplainer::new(
+            rules(&["1 следует за 0 : 10", "0 следует за 1 : 20", "ребро из 9 в 0 : исключить"]),
+            &[],
+            fixtures::matrix(),
+            None,
+        ).unwrap();
+        let path: Vec<City> = (0..10).collect();
+        let raw_distance = calculate_distance(&fixtures::matrix(), &path);
+
+        assert_eq!(
+            explainer.explain(&path).to_string(),
+            format!(
+                "excluded (raw distance {}, penalty +10)\n  \
+                held                +10  1 следует за 0 : 10\n  \
+                not held             +0  0 следует за 1 : 20\n  \
+                held           excluded  ребро из 9 в 0 : исключить",
+                raw_distance,
+            )
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use regex::Regex;
+use super::rules::{parse_rule, parse_rule_in, Language};
+use super::types::{CityNames, Matrix, RuleFn, RuleStr, TimeMatrix};
+use super::validation::{validate_rules, validate_rules_in, Diagnostic};
+
+/*
+
+    Rule file, one rule per line:
+
+    # Comments start with # and run to the end of the line
+    include common.rules
+
+    [depot]
+    8 follows 0 : -300
+    3 follows 0 : -300
+
+    [late * 2]
+    Paris at time [100,] : 50
+
+    Rules before the first group belong to no group. The weight of a group multiplies
+    the penalties of its rules, exclusions are kept as they are. Included files are
+    relative to the including one and keep their own groups and weights. A file included
+    more than once is loaded the first time only, an include of a file being loaded is a
+    cycle and an error.
+
+*/
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleEntry {
+    // As written in the file, without the weight
+    pub rule: RuleStr,
+    pub group: Option<String>,
+    pub weight: f64,
+    // File and line of the rule
+    pub source: String,
+}
+
+impl RuleEntry {
+    // The rule with the penalty multiplied by the weight of the group
+    pub fn weighted(&self) -> RuleStr {
+        if self.weight == 1. {
+            return self.rule.clone();
+        }
+
+        match self.rule.split_once(':') {
+            Some((condition, action)) => match action.trim().parse::<f64>() {
+                Ok(penalty) => format!("{}: {}", condition, (penalty * self.weight).round() as i64),
+                Err(_) => self.rule.clone(),
+            },
+            None => self.rule.clone(),
+        }
+    }
+}
+
+// Files being loaded, to find the cycles, and all the loaded files, to skip them
+#[derive(Default)]
+struct Includes {
+    stack: Vec<PathBuf>,
+    loaded: HashSet<PathBuf>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RuleSet {
+    pub entries: Vec<RuleEntry>,
+}
+
+impl RuleSet {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, String> {
+        let mut rule_set = Self::default();
+        rule_set.load(path.as_ref(), &mut Includes::default())?;
+        Ok(rule_set)
+    }
+
+    // Included files are relative to the current directory
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut rule_set = Self::default();
+        rule_set.parse_from(content, Path::new(""), "<rules>", &mut Includes::default())?;
+        Ok(rule_set)
+    }
+
+    fn load(&mut self, path: &Path, includes: &mut Includes) -> Result<(), String> {
+        let canonical = path.canonicalize().map_err(|error| format!("{}: {}", path.display(), error))?;
+        if includes.stack.contains(&canonical) {
+            return Err(format!("{}: include cycle", path.display()));
+        }
+        if !includes.loaded.insert(canonical.clone()) {
+            return Ok(());
+        }
+        let content = fs::read_to_string(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+
+        includes.stack.push(canonical);
+        let dir = path.parent().unwrap_or(Path::new(""));
+        self.parse_from(&content, dir, &path.display().to_string(), includes)?;
+        includes.stack.pop();
+
+        Ok(())
+    }
+
+    fn parse_from(&mut self, content: &str, dir: &Path, name: &str, includes: &mut Includes) -> Result<(), String> {
+        let group_re = Regex::new(r"^\[\s*([^\]*]*?)\s*(?:\*\s*([^\]]*?)\s*)?\]$").unwrap();
+        let include_re = Regex::new(r"^include\s+(.+)$").unwrap();
+
+        let mut group: Option<String> = None;
+        let mut weight = 1.;
+        for (idx, line) in content.lines().enumerate() {
+            let source = format!("{}:{}", name, idx + 1);
+            let line = match line.split_once('#') {
+                Some((line, _)) => line.trim(),
+                None => line.trim(),
+            };
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(cap) = include_re.captures(line) {
+                self.load(&dir.join(&cap[1]), includes).map_err(|error| format!("{}: {}", source, error))?;
+            } else if let Some(cap) = group_re.captures(line) {
+                if cap[1].is_empty() {
+                    return Err(format!("{}: group has no name", source));
+                }
+                weight = match cap.get(2) {
+                    Some(value) => match value.as_str().parse::<f64>() {
+                        Ok(value) if value.is_finite() && value >= 0. => value,
+                        _ => return Err(format!("{}: weight must be a number >= 0, got `{}`", source, value.as_str())),
+                    },
+                    None => 1.,
+                };
+                group = Some(cap[1].to_string());
+            } else if line.starts_with('[') {
+                return Err(format!("{}: malformed group `{}`", source, line));
+            } else {
+                self.entries.push(RuleEntry { rule: line.to_string(), group: group.clone(), weight, source });
+            }
+        }
+
+        Ok(())
+    }
+
+    // Names of the groups in the order they appear
+    pub fn groups(&self) -> Vec<&str> {
+        let mut groups: Vec<&str> = Vec::new();
+        for group in self.entries.iter().filter_map(|entry| entry.group.as_deref()) {
+            if !groups.contains(&group) {
+                groups.push(group);
+            }
+        }
+        groups
+    }
+
+    // Rules of the given groups only, the rules of no group are left out too
+    pub fn select(&self, groups: &[&str]) -> Self {
+        Self {
+            entries: self.entries
+                .iter()
+                .filter(|entry| entry.group.as_deref().is_some_and(|group| groups.contains(&group)))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    // Weighted rules to pass to the rules of the builders
+    pub fn rules(&self) -> Vec<RuleStr> {
+        self.entries.iter().map(RuleEntry::weighted).collect()
+    }
+
+    // The rules are validated first, the errors are reported with the file and line of
+    // their rule. The language is detected for every rule, like in parse_rule
+    pub fn compile(&self, city_names: &CityNames, matrix: Matrix, time_matrix: Option<TimeMatrix>) -> Result<Vec<RuleFn>, String> {
+        let rules = self.rules();
+        self.check(&rules, validate_rules(&rules, city_names, matrix.len(), time_matrix.is_some()))?;
+        rules
+            .into_iter()
+            .map(|rule| parse_rule(rule, city_names, matrix.clone(), time_matrix.clone()))
+            .collect()
+    }
+
+    pub fn compile_in(
+        &self,
+        language: Language,
+        city_names: &CityNames,
+        matrix: Matrix,
+        time_matrix: Option<TimeMatrix>,
+    ) -> Result<Vec<RuleFn>, String> {
+        let rules = self.rules();
+        self.check(&rules, validate_rules_in(&rules, language, city_names, matrix.len(), time_matrix.is_some()))?;
+        rules
+            .into_iter()
+            .map(|rule| parse_rule_in(rule, language, city_names, matrix.clone(), time_matrix.clone()))
+            .collect()
+    }
+
+    fn check(&self, rules: &[RuleStr], diagnostics: Vec<Diagnostic>) -> Result<(), String> {
+        let errors: Vec<String> = diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.is_error())
+            .map(|diagnostic| format!("{}: {}", self.entries[diagnostic.rule].source, diagnostic.render(&rules[diagnostic.rule])))
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::travelling_salesman::fixtures::{matrix, time_matrix};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("phd_cand_rule_set_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn groups_weights_and_comments() {
+        let rule_set = RuleSet::parse("
+            # Depot
+            1 before 2 : 5
+            [depot]
+            8 follows 0 : -300  # Close to the depot
+            [late * 2.5]
+            3 follows 4 : 10
+            5 follows 6 : exclude
+        ").unwrap();
+
+        assert_eq!(rule_set.groups(), vec!["depot", "late"]);
+        assert_eq!(rule_set.entries[0].group, None);
+        assert_eq!(rule_set.entries[1].rule, "8 follows 0 : -300");
+        assert_eq!(rule_set.entries[1].source, "<rules>:5");
+        assert_eq!(rule_set.rules(), vec!["1 before 2 : 5", "8 follows 0 : -300", "3 follows 4 : 25", "5 follows 6 : exclude"]);
+    }
+
+    #[test]
+    fn malformed_groups_are_errors() {
+        assert!(RuleSet::parse("[]").unwrap_err().contains("group has no name"));
+        assert!(RuleSet::parse("[late * -1]").unwrap_err().contains("weight must be a number >= 0"));
+        assert!(RuleSet::parse("[late").unwrap_err().contains("malformed group"));
+    }
+
+    #[test]
+    fn included_files_keep_their_groups_and_weights() {
+        let dir = temp_dir("include");
+        fs::write(dir.join("common.rules"), "[common * 2]\n1 before 2 : 5\n").unwrap();
+        fs::write(dir.join("main.rules"), "[main]\ninclude common.rules\n3 before 4 : 7\n").unwrap();
+
+        let rule_set = RuleSet::from_file(dir.join("main.rules")).unwrap();
+        assert_eq!(rule_set.rules(), vec!["1 before 2 : 10", "3 before 4 : 7"]);
+        assert_eq!(rule_set.groups(), vec!["common", "main"]);
+        assert!(rule_set.entries[0].source.ends_with("common.rules:2"));
+    }
+
+    #[test]
+    fn a_file_included_twice_is_loaded_once() {
+        let dir = temp_dir("diamond");
+        fs::write(dir.join("common.rules"), "1 before 2 : 5\n").unwrap();
+        fs::write(dir.join("left.rules"), "include common.rules\n3 before 4 : 7\n").unwrap();
+        fs::write(dir.join("right.rules"), "include common.rules\n5 before 6 : 9\n").unwrap();
+        fs::write(dir.join("main.rules"), "include left.rules\ninclude right.rules\n").unwrap();
+
+        let rule_set = RuleSet::from_file(dir.join("main.rules")).unwrap();
+        assert_eq!(rule_set.rules(), vec!["1 before 2 : 5", "3 before 4 : 7", "5 before 6 : 9"]);
+    }
+
+    #[test]
+    fn include_cycles_are_errors() {
+        let dir = temp_dir("cycle");
+        fs::write(dir.join("a.rules"), "include b.rules\n").unwrap();
+        fs::write(dir.join("b.rules"), "include a.rules\n").unwrap();
+
+        let error = RuleSet::from_file(dir.join("a.rules")).unwrap_err();
+        assert!(error.contains("include cycle"), "{}", error);
+        assert!(RuleSet::from_file(dir.join("missing.rules")).is_err());
+    }
+
+    #[test]
+    fn select_leaves_out_the_other_groups_and_the_ungrouped_rules() {
+        let rule_set = RuleSet::parse("1 before 2 : 5\n[a]\n3 before 4 : 7\n[b]\n5 before 6 : 9\n").unwrap();
+        assert_eq!(rule_set.select(&["b"]).rules(), vec!["5 before 6 : 9"]);
+        assert!(rule_set.select(&[]).entries.is_empty());
+    }
+
+    #[test]
+    fn compile_reports_the_diagnostics_with_their_source() {
+        let rule_set = RuleSet::parse("1 before 2 : 5\n[late]\n12 before 2 : 5\n3 at time [100,] : 10\n").unwrap();
+
+        let error = rule_set.compile(&vec![], matrix(), None).err().unwrap();
+        assert!(error.contains("<rules>:3: "), "{}", error);
+        assert!(error.contains("<rules>:4: "), "{}", error);
+        assert!(!error.contains("<rules>:1: "), "{}", error);
+
+        let rule_set = RuleSet::parse("1 before 2 : 5\n3 at time [100,] : 10\n").unwrap();
+        assert_eq!(rule_set.compile(&vec![], matrix(), Some(time_matrix())).unwrap().len(), 2);
+        assert_eq!(rule_set.compile_in(Language::English, &vec![], matrix(), Some(time_matrix())).unwrap().len(), 2);
+    }
+}
@@ -1,43 +1,146 @@
 use std::ops::{RangeInclusive};
+use std::rc::Rc;
 use eval::eval;
 use regex::Regex;
-use crate::problems::travelling_salesman::types::{City, Matrix, RuleFn, RuleStr, TimeMatrix};
+use crate::problems::travelling_salesman::types::{City, ConditionFn, Matrix, RuleFn, RuleStr, TimeMatrix};
 use crate::problems::travelling_salesman::helpers::{calculate_distance, calculate_time};
 
 /*
 
-    1) City следует за City
-    2) City по порядку Value
-    3) City на дистанции Value
-    4) City на времени Value
-    5) City на дистанции от City Value
-    6) City на дистанции от City Value
+    1)  City следует за City             City follows City
+    2)  City по порядку Value            City at position Value
+    3)  City на дистанции Value          City at distance Value
+    4)  City на времени Value            City at time Value
+    5)  City на дистанции от City Value  City at distance from City Value
+    6)  City на времени от City Value    City at time from City Value
+    7)  City раньше City                 City before City
+    8)  City рядом с City                City adjacent to City
+    9)  ребро из City в City             edge from City to City
+    10) {City, City, ...} подряд         {City, City, ...} consecutive
+
+    Conditions are joined with и / and, или / or and negated with не / not,
+    the action is a penalty or исключить / exclude.
+    City is an index or a name from the city names, names are single words, hyphens are allowed.
+    Value is a number or a range [from,to], [from,] or [,to], positions are counted from 1.
+    Before, adjacent and consecutive are about the order of the path, the edge may be the one
+    returning to the first city. A forbidden edge is `ребро из City в City : исключить`
 
 */
 
-enum Range {
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum Language {
+    Russian,
+    English,
+}
+
+pub(super) struct Keywords {
+    pub follows: &'static str,
+    pub in_order: &'static str,
+    pub on_distance: &'static str,
+    pub on_time: &'static str,
+    pub from: &'static str,
+    pub before: &'static str,
+    pub adjacent: &'static str,
+    pub edge: &'static str,
+    pub edge_to: &'static str,
+    pub consecutive: &'static str,
+    pub and: &'static str,
+    pub or: &'static str,
+    pub not: &'static str,
+    pub exclude: &'static str,
+}
+
+impl Language {
+    pub(super) fn keywords(&self) -> Keywords {
+        match self {
+            Language::Russian => Keywords {
+                follows: "следует за",
+                in_order: "по порядку",
+                on_distance: "на дистанции",
+                on_time: "на времени",
+                from: "от",
+                before: "раньше",
+                adjacent: "рядом с",
+                edge: "ребро из",
+                edge_to: "в",
+                consecutive: "подряд",
+                and: "и",
+                or: "или",
+                not: "не",
+                exclude: "исключить",
+            },
+            Language::English => Keywords {
+                follows: "follows",
+                in_order: "at position",
+                on_distance: "at distance",
+                on_time: "at time",
+                from: "from",
+                before: "before",
+                adjacent: "adjacent to",
+                edge: "edge from",
+                edge_to: "to",
+                consecutive: "consecutive",
+                and: "and",
+                or: "or",
+                not: "not",
+                exclude: "exclude",
+            },
+        }
+    }
+
+    // English if the rule has English keywords and no Russian ones
+    pub fn detect(s: &str) -> Language {
+        let has_keywords = |language: Language| {
+            let keywords = language.keywords();
+            [
+                keywords.follows,
+                keywords.in_order,
+                keywords.on_distance,
+                keywords.on_time,
+                keywords.before,
+                keywords.adjacent,
+                keywords.edge,
+                keywords.consecutive,
+                keywords.exclude,
+            ]
+                .iter()
+                .any(|keyword| s.contains(keyword))
+        };
+
+        if has_keywords(Language::English) && !has_keywords(Language::Russian) {
+            Language::English
+        } else {
+            Language::Russian
+        }
+    }
+}
+
+pub(super) enum Range {
     Single(i32),
     FromTo(i32, i32),
     From(i32),
     To(i32),
 }
 
-fn parse_range(s: String) -> Result<Range, String> {
+pub(super) fn parse_range(s: String) -> Result<Range, String> {
     let range_re = Regex::new(r"^\[(\d*),(\d*)\]$").unwrap();
 
     if let Ok(num) = s.parse::<i32>() {
         // Если строка - целое число
         Ok(Range::Single(num))
-    } else if let Some(captures) = range_re.captures(&*s) {
+    } else if let Some(captures) = range_re.captures(&s) {
         // Если строка соответствует формату диапазона
         let start = captures.get(1).map(|m| m.as_str()).unwrap_or("");
         let end = captures.get(2).map(|m| m.as_str()).unwrap_or("");
 
+        // Границы, не помещающиеся в i32, - ошибка формата
+        let bound = |s: &str| s.parse::<i32>().map_err(|_| format!("Граница `{}` вне допустимого диапазона", s));
         match (start, end) {
             ("", "") => Err("Пустой диапазон".to_string()),
-            (s, "") => Ok(Range::From(s.parse().unwrap())),
-            ("", e) => Ok(Range::To(e.parse().unwrap())),
-            (s, e) => Ok(Range::FromTo(s.parse().unwrap(), e.parse().unwrap())),
+            (s, "") => Ok(Range::From(bound(s)?)),
+            ("", e) => Ok(Range::To(bound(e)?)),
+            (s, e) => Ok(Range::FromTo(bound(s)?, bound(e)?)),
         }
     } else {
         Err("Неправильный формат".to_string())
@@ -50,7 +153,7 @@ fn index_cb(city: City) -> impl Fn(&City) -> bool {
     }
 }
 
-fn follows(city_a: City, city_b: City, cities: &Vec<City>) -> bool {
+fn follows(city_a: City, city_b: City, cities: &[City]) -> bool {
     let city_a_idx = cities.iter().position(index_cb(city_a));
     let city_b_idx = cities.iter().position(index_cb(city_b));
     if let Some(a_idx) = city_a_idx {
@@ -64,46 +167,74 @@ fn follows(city_a: City, city_b: City, cities: &Vec<City>) -> bool {
     }
 }
 
-fn in_order(city: City, order: usize, cities: &Vec<City>) -> bool {
+fn in_order(city: City, order_raw: String, cities: &[City]) -> bool {
     let city_idx = cities.iter().position(index_cb(city));
     if let Some(idx) = city_idx {
-        idx + 1 == order
+        is_value_in_range((idx + 1) as f64, order_raw)
     } else {
         false
     }
 }
 
-fn is_distance_in_range(range: RangeInclusive<usize>, distance_raw: String, cities: &Vec<City>, matrix: &Matrix) -> bool {
-    let distance = calculate_distance(matrix, &cities[range].to_vec());
-
-    match parse_range(distance_raw) {
-        Ok(Range::Single(val)) => distance == val as f64,
-        Ok(Range::From(val)) => distance >= val as f64,
-        Ok(Range::To(val)) => distance <= val as f64,
-        Ok(Range::FromTo(from, to)) => distance >= from as f64 && distance <= to as f64,
+fn before(city_a: City, city_b: City, cities: &[City]) -> bool {
+    let city_a_idx = cities.iter().position(index_cb(city_a));
+    let city_b_idx = cities.iter().position(index_cb(city_b));
+    match (city_a_idx, city_b_idx) {
+        (Some(a_idx), Some(b_idx)) => a_idx < b_idx,
         _ => false
     }
 }
 
-fn is_time_in_range(range: RangeInclusive<usize>, time_raw: String, cities: &Vec<City>, time_matrix: &TimeMatrix) -> bool {
-    let time = calculate_time(time_matrix, &cities[range].to_vec()) as i32;
+fn adjacent(city_a: City, city_b: City, cities: &[City]) -> bool {
+    follows(city_a, city_b, cities) || follows(city_b, city_a, cities)
+}
 
-    match parse_range(time_raw) {
-        Ok(Range::Single(val)) => time == val,
-        Ok(Range::From(val)) => time >= val,
-        Ok(Range::To(val)) => time <= val,
-        Ok(Range::FromTo(from, to)) => time >= from && time <= to,
+fn has_edge(city_from: City, city_to: City, cities: &[City]) -> bool {
+    let cities_count = cities.len();
+    (0..cities_count).any(|i| cities[i] == city_from && cities[(i + 1) % cities_count] == city_to)
+}
+
+fn consecutive(group: &[City], cities: &[City]) -> bool {
+    let positions: Option<Vec<usize>> = group.iter().map(|city| cities.iter().position(index_cb(*city))).collect();
+    match positions {
+        Some(positions) if !positions.is_empty() => {
+            let first = positions.iter().min().unwrap();
+            let last = positions.iter().max().unwrap();
+            last - first + 1 == group.len()
+        }
         _ => false
     }
 }
 
-fn on_distance(city: City, distance_raw: String, cities: &Vec<City>, matrix: &Matrix) -> bool {
+fn is_value_in_range(value: f64, value_raw: String) -> bool {
+    match parse_range(value_raw) {
+        Ok(Range::Single(val)) => value == val as f64,
+        Ok(Range::From(val)) => value >= val as f64,
+        Ok(Range::To(val)) => value <= val as f64,
+        Ok(Range::FromTo(from, to)) => value >= from as f64 && value <= to as f64,
+        _ => false
+    }
+}
+
+fn is_distance_in_range(range: RangeInclusive<usize>, distance_raw: String, cities: &[City], matrix: &Matrix) -> bool {
+    let distance = calculate_distance(matrix, &cities[range]);
+
+    is_value_in_range(distance, distance_raw)
+}
+
+fn is_time_in_range(range: RangeInclusive<usize>, time_raw: String, cities: &[City], time_matrix: &TimeMatrix) -> bool {
+    let time = calculate_time(time_matrix, &cities[range]);
+
+    is_value_in_range(time as f64, time_raw)
+}
+
+fn on_distance(city: City, distance_raw: String, cities: &[City], matrix: &Matrix) -> bool {
     let city_idx = cities.iter().position(index_cb(city)).unwrap();
 
     is_distance_in_range(0..=city_idx, distance_raw, cities, matrix)
 }
 
-fn on_distance_from_city(city_to: City, city_from: City, distance_raw: String, cities: &Vec<City>, matrix: &Matrix) -> bool {
+fn on_distance_from_city(city_to: City, city_from: City, distance_raw: String, cities: &[City], matrix: &Matrix) -> bool {
     let city_to_idx = cities.iter().position(index_cb(city_to)).unwrap();
     let city_from_idx = cities.iter().position(index_cb(city_from)).unwrap();
     let cities_range = if city_from_idx > city_to_idx {
@@ -115,13 +246,13 @@ fn on_distance_from_city(city_to: City, city_from: City, distance_raw: String, c
     is_distance_in_range(cities_range, distance_raw, cities, matrix)
 }
 
-fn on_time(city: City, time_raw: String, cities: &Vec<City>, time_matrix: &TimeMatrix) -> bool {
+fn on_time(city: City, time_raw: String, cities: &[City], time_matrix: &TimeMatrix) -> bool {
     let city_idx = cities.iter().position(index_cb(city)).unwrap();
 
     is_time_in_range(0..=city_idx, time_raw, cities, time_matrix)
 }
 
-fn on_time_from_city(city_to: City, city_from: City, time_raw: String, cities: &Vec<City>, time_matrix: &TimeMatrix) -> bool {
+fn on_time_from_city(city_to: City, city_from: City, time_raw: String, cities: &[City], time_matrix: &TimeMatrix) -> bool {
     let city_to_idx = cities.iter().position(index_cb(city_to)).unwrap();
     let city_from_idx = cities.iter().position(index_cb(city_from)).unwrap();
     let cities_range = if city_from_idx > city_to_idx {
@@ -133,82 +264,463 @@ fn on_time_from_city(city_to: City, city_from: City, time_raw: String, cities: &
     is_time_in_range(cities_range, time_raw, cities, time_matrix)
 }
 
-pub fn parse_rule(s: RuleStr, matrix: Matrix, time_matrix: Option<TimeMatrix>) -> RuleFn {
+const CITY: &str = r"(\w[\w-]*)";
+const VALUE: &str = r"(\d+|\[.*?\])";
+const CITY_GROUP: &str = r"\{([^{}]*)\}";
+
+// Cities of a group, empty tokens are left for the validation to report
+pub(super) fn split_group(group: &str) -> impl Iterator<Item = &str> {
+    group.split(',').map(str::trim)
+}
+
+pub(super) struct Predicates {
+    pub follows: Regex,
+    pub in_order: Regex,
+    pub on_distance: Regex,
+    pub on_distance_from_city: Regex,
+    pub on_time: Regex,
+    pub on_time_from_city: Regex,
+    pub before: Regex,
+    pub adjacent: Regex,
+    pub edge: Regex,
+    pub consecutive: Regex,
+}
+
+impl Predicates {
+    // Anchored patterns match a whole condition part, otherwise they are searched in the condition
+    pub(super) fn new(keywords: &Keywords, anchored: bool) -> Self {
+        let regex = |pattern: String| {
+            if anchored {
+                Regex::new(&format!("^{}$", pattern)).unwrap()
+            } else {
+                Regex::new(&pattern).unwrap()
+            }
+        };
+
+        Self {
+            follows: regex(format!(r"{CITY}\s+{}\s+{CITY}", keywords.follows)),
+            in_order: regex(format!(r"{CITY}\s+{}\s+{VALUE}", keywords.in_order)),
+            on_distance: regex(format!(r"{CITY}\s+{}\s+{VALUE}", keywords.on_distance)),
+            on_distance_from_city: regex(
+                format!(r"{CITY}\s+{}\s+{}\s+{CITY}\s+{VALUE}", keywords.on_distance, keywords.from)
+            ),
+            on_time: regex(format!(r"{CITY}\s+{}\s+{VALUE}", keywords.on_time)),
+            on_time_from_city: regex(
+                format!(r"{CITY}\s+{}\s+{}\s+{CITY}\s+{VALUE}", keywords.on_time, keywords.from)
+            ),
+            before: regex(format!(r"{CITY}\s+{}\s+{CITY}", keywords.before)),
+            adjacent: regex(format!(r"{CITY}\s+{}\s+{CITY}", keywords.adjacent)),
+            edge: regex(format!(r"{}\s+{CITY}\s+{}\s+{CITY}", keywords.edge, keywords.edge_to)),
+            consecutive: regex(format!(r"{CITY_GROUP}\s+{}", keywords.consecutive)),
+        }
+    }
+}
+
+// Names take precedence over indices
+pub fn parse_city(token: &str, city_names: &[String], cities_count: usize) -> Result<City, String> {
+    if let Some(city) = city_names.iter().position(|name| name == token) {
+        return Ok(city);
+    }
+
+    match token.parse::<City>() {
+        Ok(city) if city < cities_count => Ok(city),
+        Ok(city) => Err(format!("City index {} is out of range, there are {} cities", city, cities_count)),
+        Err(_) => Err(format!("Unknown city `{}`", token)),
+    }
+}
+
+// The language is detected by the keywords of the rule
+pub fn parse_rule(s: RuleStr, city_names: &[String], matrix: Matrix, time_matrix: Option<TimeMatrix>) -> Result<RuleFn, String> {
+    let language = Language::detect(&s);
+    parse_rule_in(s, language, city_names, matrix, time_matrix)
+}
+
+pub fn parse_rule_in(
+    s: RuleStr,
+    language: Language,
+    city_names: &[String],
+    matrix: Matrix,
+    time_matrix: Option<TimeMatrix>,
+) -> Result<RuleFn, String> {
+    let (condition, action) = parse_condition_in(s, language, city_names, matrix, time_matrix)?;
+
+    Ok(Box::new(move |cities: &Vec<City>| -> Option<i64> {
+        match (condition(cities), action) {
+            (Some(true), Action::Exclude) => None,
+            (Some(true), Action::Penalty(penalty)) => Some(penalty),
+            _ => Some(0),
+        }
+    }))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    Penalty(i64),
+    Exclude,
+}
+
+// Evaluates one predicate of a condition, None when it cannot be evaluated
+type PredicateFn = Box<dyn Fn(&[City]) -> Option<bool>>;
+
+// Finds the predicate of a condition part, the cities are resolved here so unknown ones are reported once
+fn parse_predicate(
+    part: &str,
+    predicates: &Predicates,
+    city_names: &[String],
+    matrix: &Rc<Matrix>,
+    time_matrix: &Option<Rc<TimeMatrix>>,
+) -> Result<(String, PredicateFn), String> {
+    let city = |token: &str| parse_city(token, city_names, matrix.len());
+    // A malformed range never matches, so it is reported like in validate_rules
+    let range = |value: &str| match parse_range(value.to_string()) {
+        Ok(_) => Ok(value.to_string()),
+        Err(_) => Err(format!("Malformed range `{}`", value)),
+    };
+
+    let predicate: (String, PredicateFn) = if let Some(cap) = predicates.follows.captures(part) {
+        let (city_a, city_b) = (city(&cap[1])?, city(&cap[2])?);
+        (cap[0].to_string(), Box::new(move |cities| Some(follows(city_a, city_b, cities))))
+    } else if let Some(cap) = predicates.in_order.captures(part) {
+        let (city, order_raw) = (city(&cap[1])?, range(&cap[2])?);
+        (cap[0].to_string(), Box::new(move |cities| Some(in_order(city, order_raw.clone(), cities))))
+    } else if let Some(cap) = predicates.on_distance_from_city.captures(part) {
+        let (city_a, city_b, distance_raw) = (city(&cap[1])?, city(&cap[2])?, range(&cap[3])?);
+        let matrix = matrix.clone();
+        (cap[0].to_string(), Box::new(move |cities| {
+            Some(on_distance_from_city(city_a, city_b, distance_raw.clone(), cities, &matrix))
+        }))
+    } else if let Some(cap) = predicates.on_distance.captures(part) {
+        let (city, distance_raw) = (city(&cap[1])?, range(&cap[2])?);
+        let matrix = matrix.clone();
+        (cap[0].to_string(), Box::new(move |cities| Some(on_distance(city, distance_raw.clone(), cities, &matrix))))
+    } else if let Some(cap) = predicates.on_time_from_city.captures(part) {
+        let (city_a, city_b, time_raw) = (city(&cap[1])?, city(&cap[2])?, range(&cap[3])?);
+        let time_matrix = time_matrix.clone();
+        (cap[0].to_string(), Box::new(move |cities| {
+            let time_matrix = time_matrix.as_ref()?;
+            Some(on_time_from_city(city_a, city_b, time_raw.clone(), cities, time_matrix))
+        }))
+    } else if let Some(cap) = predicates.on_time.captures(part) {
+        let (city, time_raw) = (city(&cap[1])?, range(&cap[2])?);
+        let time_matrix = time_matrix.clone();
+        (cap[0].to_string(), Box::new(move |cities| {
+            let time_matrix = time_matrix.as_ref()?;
+            Some(on_time(city, time_raw.clone(), cities, time_matrix))
+        }))
+    } else if let Some(cap) = predicates.before.captures(part) {
+        let (city_a, city_b) = (city(&cap[1])?, city(&cap[2])?);
+        (cap[0].to_string(), Box::new(move |cities| Some(before(city_a, city_b, cities))))
+    } else if let Some(cap) = predicates.adjacent.captures(part) {
+        let (city_a, city_b) = (city(&cap[1])?, city(&cap[2])?);
+        (cap[0].to_string(), Box::new(move |cities| Some(adjacent(city_a, city_b, cities))))
+    } else if let Some(cap) = predicates.edge.captures(part) {
+        let (city_from, city_to) = (city(&cap[1])?, city(&cap[2])?);
+        (cap[0].to_string(), Box::new(move |cities| Some(has_edge(city_from, city_to, cities))))
+    } else if let Some(cap) = predicates.consecutive.captures(part) {
+        let group = split_group(&cap[1]).map(city).collect::<Result<Vec<City>, String>>()?;
+        (cap[0].to_string(), Box::new(move |cities| Some(consecutive(&group, cities))))
+    } else {
+        return Err(format!("Unknown condition `{}`", part.trim()));
+    };
+
+    Ok(predicate)
+}
+
+// The condition is None when it cannot be evaluated, e.g. a time condition without the time matrix
+pub fn parse_condition_in(
+    s: RuleStr,
+    language: Language,
+    city_names: &[String],
+    matrix: Matrix,
+    time_matrix: Option<TimeMatrix>,
+) -> Result<(ConditionFn, Action), String> {
+    let keywords = language.keywords();
+
     // Создаем клонированные строки и регулярные выражения
     let whitespaces_pattern = Regex::new(r"\s{2,}").unwrap();
     let s_cloned = whitespaces_pattern.replace_all(&s, " ").to_string();
 
-    let operators_pattern = Regex::new(r"\s+(и|или)\s+").unwrap();
-    let follows_re = Regex::new(r"(\w+)\s+следует за\s+(\w+)").unwrap();
-    let in_order_re = Regex::new(r"(\w+)\s+по порядку\s+(\d+)").unwrap();
-    let on_distance_re = Regex::new(r"(\w+)\s+на дистанции\s+(\d+|\[.*?\])").unwrap();
-    let on_distance_from_city_re = Regex::new(r"(\w+)\s+на дистанции от\s+(\w+)\s+(\d+|\[.*?\])").unwrap();
-    let on_time_re = Regex::new(r"(\w+)\s+на времени\s+(\d+|\[.*?\])").unwrap();
-    let on_time_from_city_re = Regex::new(r"(\w+)\s+на времени от\s+(\w+)\s+(\d+|\[.*?\])").unwrap();
+    let operators_pattern = Regex::new(&format!(r"\s+({}|{})\s+", keywords.and, keywords.or)).unwrap();
+    let and_pattern = Regex::new(&format!(r"\s+{}\s+", keywords.and)).unwrap();
+    let or_pattern = Regex::new(&format!(r"\s+{}\s+", keywords.or)).unwrap();
+    let not_pattern = Regex::new(&format!(r"\b{}\s+", keywords.not)).unwrap();
+    let leading_not_pattern = Regex::new(&format!(r"^{}\s+", keywords.not)).unwrap();
+    // Every part is a whole predicate, like in validate_rules, so no text is left around the predicates
+    let predicates = Predicates::new(&keywords, true);
 
-    // let action = s_cloned.split(':').last().unwrap().trim().to_string();
+    let (condition_raw, action) = match s_cloned.split_once(':') {
+        Some((_, action)) if action.contains(':') => return Err(format!("Rule `{}` has more than one `:`", s)),
+        Some((condition, action)) => (condition, action.trim()),
+        None => return Err(format!("Rule `{}` has no action", s)),
+    };
+    if condition_raw.trim().is_empty() {
+        return Err(format!("Rule `{}` has no condition", s));
+    }
+    let mut depth = 0;
+    for char in condition_raw.chars() {
+        depth += match char {
+            '(' => 1,
+            ')' => -1,
+            _ => 0,
+        };
+        if depth < 0 {
+            break;
+        }
+    }
+    if depth != 0 {
+        return Err(format!("Unbalanced parentheses in rule `{}`", s));
+    }
+    let action = if action == keywords.exclude {
+        Action::Exclude
+    } else {
+        match action.parse() {
+            Ok(penalty) => Action::Penalty(penalty),
+            Err(_) => return Err(format!("Penalty `{}` is not an integer in rule `{}`", action, s)),
+        }
+    };
+
+    let binding = and_pattern.replace_all(condition_raw, " && ");
+    let binding = or_pattern.replace_all(&binding, " || ");
+    // eval rejects `!` after a binary operator, comparing with false negates the same way
+    let template = not_pattern.replace_all(&binding, "false == ").to_string();
+
+    // The template is cut at the predicates in the order of the parts, so a predicate
+    // is never found inside another one, e.g. `1 follows 0` inside `11 follows 0`
+    let matrix = Rc::new(matrix);
+    let time_matrix = time_matrix.map(Rc::new);
+    let mut texts = vec![];
+    let mut predicate_fns: Vec<PredicateFn> = vec![];
+    let mut rest = template.as_str();
+    for part in operators_pattern.split(condition_raw) {
+        // Parentheses are balanced, so they are dropped from the parts with the negations
+        let trim = |part: &str| part.trim_start_matches(|char: char| char == '(' || char.is_whitespace())
+            .trim_end_matches(|char: char| char == ')' || char.is_whitespace()).to_string();
+        let mut part = trim(part);
+        while let Some(found) = leading_not_pattern.find(&part) {
+            part = trim(&part[found.end()..]);
+        }
+        if part.is_empty() {
+            return Err(format!("Condition is missing in rule `{}`", s));
+        }
+
+        let (predicate, predicate_fn) = parse_predicate(&part, &predicates, city_names, &matrix, &time_matrix)
+            .map_err(|error| format!("{} in rule `{}`", error, s))?;
+        // Only operators are left between the predicates
+        let start = rest.find(&predicate).unwrap();
+        texts.push(rest[..start].to_string());
+        predicate_fns.push(predicate_fn);
+        rest = &rest[start + predicate.len()..];
+    }
+    let tail = rest.to_string();
 
     // Возвращаем замыкание
-    Box::new(move |cities: &Vec<City>| -> Option<i64> {
-        let binding = s_cloned.replace(" и ", " && ").replace(" или ", " || ");
-        let mut splitted = binding.split(":");
-        let mut condition = splitted.next().unwrap().to_string();
-        let action = splitted.next().unwrap().trim();
-
-        for part in operators_pattern.replace_all(&s_cloned, "#").split('#') {
-            if let Some(cap) = follows_re.captures(part) {
-                let city_a = cap[1].parse::<City>().unwrap();
-                let city_b = cap[2].parse::<City>().unwrap();
-                let result = follows(city_a, city_b, cities).to_string();
-                condition = condition.replace(&cap[0], &result);
-            } else if let Some(cap) = in_order_re.captures(part) {
-                let city = cap[1].parse::<City>().unwrap();
-                let order = cap[2].parse::<usize>().unwrap();
-                let result = in_order(city, order, cities).to_string();
-                condition = condition.replace(&cap[0], &result);
-            } else if let Some(cap) = on_distance_from_city_re.captures(part) {
-                let city_a = cap[1].parse::<City>().unwrap();
-                let city_b = cap[2].parse::<City>().unwrap();
-                let distance_raw = cap[3].to_string();
-                let result = on_distance_from_city(city_a, city_b, distance_raw, cities, &matrix).to_string();
-                condition = condition.replace(&cap[0], &result);
-            } else if let Some(cap) = on_distance_re.captures(part) {
-                let city = cap[1].parse::<City>().unwrap();
-                let distance_raw = cap[2].to_string();
-                let result = on_distance(city, distance_raw, cities, &matrix).to_string();
-                condition = condition.replace(&cap[0], &result);
-            } else if let Some(cap) = on_time_from_city_re.captures(part) {
-                let city_a = cap[1].parse::<City>().unwrap();
-                let city_b = cap[2].parse::<City>().unwrap();
-                let time_raw = cap[3].to_string();
-                let matrix_t = match &time_matrix {
-                    Some(matrix) => matrix,
-                    None => return Some(0)
-                };
-                let result = on_time_from_city(city_a, city_b, time_raw, cities, matrix_t).to_string();
-                condition = condition.replace(&cap[0], &result);
-            } else if let Some(cap) = on_time_re.captures(part) {
-                let city = cap[1].parse::<City>().unwrap();
-                let time_raw = cap[2].to_string();
-                let matrix_t = match &time_matrix {
-                    Some(matrix) => matrix,
-                    None => return Some(0)
-                };
-                let result = on_time(city, time_raw, cities, matrix_t).to_string();
-                condition = condition.replace(&cap[0], &result);
-            }
+    let condition_fn: ConditionFn = Box::new(move |cities: &Vec<City>| -> Option<bool> {
+        let mut condition = String::new();
+        for (text, predicate_fn) in texts.iter().zip(&predicate_fns) {
+            condition += text;
+            condition += &predicate_fn(cities)?.to_string();
         }
+        condition += &tail;
+
+        eval(&condition).ok().and_then(|value| value.as_bool())
+    });
+
+    Ok((condition_fn, action))
+}
+
 
-        match eval(&condition) {
-            Ok(value) if value.as_bool().unwrap_or(false) => {
-                if action == "исключить" {
-                    None
-                } else {
-                    Some(action.parse().unwrap_or(0))
-                }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::travelling_salesman::fixtures;
+
+    fn rule(s: &str) -> RuleFn {
+        parse_rule(s.to_string(), &[], fixtures::matrix(), Some(fixtures::time_matrix())).unwrap()
+    }
+
+    fn error(s: &str, city_names: &[String]) -> String {
+        parse_rule(s.to_string(), city_names, fixtures::matrix(), None).err().unwrap()
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn tours() -> Vec<Vec<City>> {
+        vec![
+            (0..10).collect(),
+            (0..10).rev().collect(),
+            vec![0, 8, 4, 3, 9, 5, 6, 1, 2, 7],
+            vec![3, 0, 8, 1, 2, 9, 5, 4, 6, 7],
+        ]
+    }
+
+    #[test]
+    fn language_is_detected_by_the_keywords() {
+        assert_eq!(Language::detect("8 follows 0 : -300"), Language::English);
+        assert_eq!(Language::detect("edge from 1 to 2 : exclude"), Language::English);
+        assert_eq!(Language::detect("8 следует за 0 : -300"), Language::Russian);
+        assert_eq!(Language::detect("8 follows 0 и 3 следует за 0 : 10"), Language::Russian);
+        assert_eq!(Language::detect("8 : 10"), Language::Russian);
+    }
+
+    #[test]
+    fn english_rules_match_russian_ones() {
+        let pairs = [
+            ("1 follows 0 : 10", "1 следует за 0 : 10"),
+            ("5 at position [2,6] : 10", "5 по порядку [2,6] : 10"),
+            ("5 at distance [,100] : 10", "5 на дистанции [,100] : 10"),
+            ("5 at time [100,] : 10", "5 на времени [100,] : 10"),
+            ("5 at distance from 2 [50,] : 10", "5 на дистанции от 2 [50,] : 10"),
+            ("5 at time from 2 [,150] : 10", "5 на времени от 2 [,150] : 10"),
+            ("3 before 8 : 10", "3 раньше 8 : 10"),
+            ("4 adjacent to 3 : 10", "4 рядом с 3 : 10"),
+            ("edge from 7 to 0 : exclude", "ребро из 7 в 0 : исключить"),
+            ("{1, 2, 3} consecutive : 10", "{1, 2, 3} подряд : 10"),
+            ("1 follows 0 and not 8 before 3 : 10", "1 следует за 0 и не 8 раньше 3 : 10"),
+            ("1 follows 0 or 4 adjacent to 3 : 10", "1 следует за 0 или 4 рядом с 3 : 10"),
+        ];
+
+        for (english, russian) in pairs {
+            let (english_rule, russian_rule) = (rule(english), rule(russian));
+            let results: Vec<Option<i64>> = tours().iter().map(&english_rule).collect();
+
+            assert_eq!(results, tours().iter().map(&russian_rule).collect::<Vec<_>>(), "{}", english);
+            assert!(results.iter().any(|result| *result != Some(0)), "{} never applies", english);
+        }
+    }
+
+    #[test]
+    fn english_operators() {
+        let tour: Vec<City> = (0..10).collect();
+
+        assert_eq!(rule("1 follows 0 and 2 follows 1 : 7")(&tour), Some(7));
+        assert_eq!(rule("1 follows 0 and 0 follows 1 : 7")(&tour), Some(0));
+        assert_eq!(rule("0 follows 1 or 2 follows 1 : 7")(&tour), Some(7));
+        assert_eq!(rule("not 0 follows 1 : 7")(&tour), Some(7));
+        assert_eq!(rule("1 follows 0 : exclude")(&tour), None);
+    }
+
+    #[test]
+    fn language_may_be_given() {
+        let rule = parse_rule_in("1 follows 0 : 5".to_string(), Language::English, &[], fixtures::matrix(), None).unwrap();
+
+        assert_eq!(rule(&(0..10).collect()), Some(5));
+    }
+
+    #[test]
+    fn names_may_contain_keywords() {
+        let names = names(&["Andover", "Notting-Hill", "Oregon", "Frome", "Toronto", "E", "F", "G", "H", "I"]);
+        let rule = parse_rule("Notting-Hill follows Andover and not Oregon before Frome : 5".to_string(), &names, fixtures::matrix(), None)
+            .unwrap();
+
+        assert_eq!(rule(&(0..10).collect()), Some(0));
+        assert_eq!(rule(&vec![0, 1, 3, 2, 4, 5, 6, 7, 8, 9]), Some(5));
+    }
+
+    #[test]
+    fn predicates_are_not_replaced_inside_other_ones() {
+        let names = names(&["A", "AA", "B", "C", "D", "E", "F", "G", "H", "I"]);
+        let rule = parse_rule("A follows B and not AA follows B : 5".to_string(), &names, fixtures::matrix(), None).unwrap();
+
+        assert_eq!(rule(&vec![2, 0, 1, 3, 4, 5, 6, 7, 8, 9]), Some(5));
+        assert_eq!(rule(&vec![2, 1, 0, 3, 4, 5, 6, 7, 8, 9]), Some(0));
+    }
+
+    #[test]
+    fn predicates_keep_their_operators() {
+        let tour: Vec<City> = (0..10).collect();
+
+        assert_eq!(rule("(1 follows 0 or 0 follows 1) and not 3 follows 2 : 7")(&tour), Some(0));
+        assert_eq!(rule("(0 follows 1 or 2 follows 1) and not 2 follows 3 : 7")(&tour), Some(7));
+    }
+
+    #[test]
+    fn malformed_rules_are_errors() {
+        assert_eq!(error("Paris follows 0 : 5", &[]), "Unknown city `Paris` in rule `Paris follows 0 : 5`");
+        assert!(error("10 follows 0 : 5", &[]).starts_with("City index 10 is out of range"));
+        assert_eq!(error("1 follows 0", &[]), "Rule `1 follows 0` has no action");
+        assert_eq!(error("1 follows 0 : 1O", &[]), "Penalty `1O` is not an integer in rule `1 follows 0 : 1O`");
+        assert_eq!(error("1 follows 0 : ", &[]), "Penalty `` is not an integer in rule `1 follows 0 : `");
+        assert_eq!(error("1 likes 0 : 5", &[]), "Unknown condition `1 likes 0` in rule `1 likes 0 : 5`");
+        assert!(error("{1, X} consecutive : 5", &[]).starts_with("Unknown city `X`"));
+        assert_eq!(error("junk 1 следует за 0 : 5", &[]), "Unknown condition `junk 1 следует за 0` in rule `junk 1 следует за 0 : 5`");
+        assert_eq!(error("1 follows 0 : 5 : 6", &[]), "Rule `1 follows 0 : 5 : 6` has more than one `:`");
+        assert_eq!(error("(1 follows 0 : 5", &[]), "Unbalanced parentheses in rule `(1 follows 0 : 5`");
+        assert_eq!(error("1 follows 0 and : 5", &[]), "Condition is missing in rule `1 follows 0 and : 5`");
+        assert_eq!(error("1 at distance [,99999999999] : 5", &[]), "Malformed range `[,99999999999]` in rule `1 at distance [,99999999999] : 5`");
+    }
+
+    #[test]
+    fn time_conditions_need_the_time_matrix() {
+        let rule = parse_rule("5 at time [100,] : 10".to_string(), &[], fixtures::matrix(), None).unwrap();
+
+        assert_eq!(rule(&(0..10).collect()), Some(0));
+    }
+
+    #[test]
+    fn before_is_anywhere_in_the_tour() {
+        let rule = rule("3 раньше 8 : 10");
+
+        assert_eq!(rule(&(0..10).collect()), Some(10));
+        assert_eq!(rule(&vec![3, 0, 1, 2, 4, 5, 6, 7, 9, 8]), Some(10));
+        assert_eq!(rule(&(0..10).rev().collect()), Some(0));
+    }
+
+    #[test]
+    fn adjacent_is_either_direction() {
+        let rule = rule("4 рядом с 3 : 10");
+
+        assert_eq!(rule(&(0..10).collect()), Some(10));
+        assert_eq!(rule(&(0..10).rev().collect()), Some(10));
+        assert_eq!(rule(&vec![4, 0, 1, 2, 3, 5, 6, 7, 8, 9]), Some(0));
+    }
+
+    #[test]
+    fn edge_includes_the_return_to_the_start() {
+        let rule = rule("ребро из 9 в 0 : исключить");
+
+        assert_eq!(rule(&(0..10).collect()), None);
+        assert_eq!(rule(&vec![9, 0, 1, 2, 3, 4, 5, 6, 7, 8]), None);
+        assert_eq!(rule(&(0..10).rev().collect()), Some(0));
+    }
+
+    #[test]
+    fn group_is_consecutive_in_any_order() {
+        let rule = rule("{1, 2, 3} подряд : 10");
+
+        assert_eq!(rule(&(0..10).collect()), Some(10));
+        assert_eq!(rule(&vec![0, 3, 1, 2, 4, 5, 6, 7, 8, 9]), Some(10));
+        assert_eq!(rule(&vec![0, 1, 2, 4, 3, 5, 6, 7, 8, 9]), Some(0));
+    }
+
+    #[test]
+    fn position_may_be_a_range() {
+        let tours: Vec<Vec<City>> = (0..10).map(|shift| (0..10).map(|city| (city + shift) % 10).collect()).collect();
+        // Position of city 5 in the tours
+        let positions: Vec<usize> = tours.iter().map(|tour| tour.iter().position(|city| *city == 5).unwrap() + 1).collect();
+
+        for (s, range) in [
+            ("5 по порядку 3 : 1", 3..=3),
+            ("5 по порядку [2,6] : 1", 2..=6),
+            ("5 по порядку [7,] : 1", 7..=10),
+            ("5 по порядку [,4] : 1", 1..=4),
+        ] {
+            let rule = rule(s);
+            for (tour, position) in tours.iter().zip(&positions) {
+                assert_eq!(rule(tour), Some(i64::from(range.contains(position))), "{} on {:?}", s, tour);
             }
-            _ => Some(0),
         }
-    })
-}
+    }
+
+    #[test]
+    fn not_negates_a_predicate() {
+        let tour: Vec<City> = (0..10).collect();
 
+        assert_eq!(rule("не 1 следует за 0 : 5")(&tour), Some(0));
+        assert_eq!(rule("не 0 следует за 1 : 5")(&tour), Some(5));
+        assert_eq!(rule("не {1, 2, 3} подряд : 5")(&tour), Some(0));
+        assert_eq!(rule("1 следует за 0 и не 2 раньше 3 : 5")(&tour), Some(0));
+        assert_eq!(rule("1 следует за 0 и не 8 раньше 3 : 5")(&tour), Some(5));
+    }
+}
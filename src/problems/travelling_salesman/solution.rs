@@ -1,13 +1,43 @@
 use std::fmt::{Debug, Formatter};
 use super::types::City;
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Solution {
     pub path: Vec<City>,
+    // Length of the tour, the penalties are not included
     pub distance: f64,
     pub time: Option<usize>,
+    // Sum of the rule penalties, negative for bonuses
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub penalty: f64,
     pub fitness: f32,
 }
 
+impl Solution {
+    // Distance with the penalties, the value the algorithms minimise
+    pub fn cost(&self) -> f64 {
+        self.distance + self.penalty
+    }
+
+    // Relative length of the tour from the optimum or its lower bound, both leave the penalties out
+    pub fn gap(&self, reference: f64) -> f64 {
+        if reference == 0. {
+            return 0.;
+        }
+
+        (self.distance - reference) / reference.abs()
+    }
+
+    // Path with city names, indices are kept when there are no names
+    pub fn format_path(&self, city_names: &[String]) -> String {
+        self.path
+            .iter()
+            .map(|city| city_names.get(*city).cloned().unwrap_or_else(|| city.to_string()))
+            .collect::<Vec<String>>()
+            .join(" -> ")
+    }
+}
+
 impl Debug for Solution {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let mut binding = f.debug_struct("TSSolution");
@@ -18,7 +48,45 @@ impl Debug for Solution {
         if let Some(time) = self.time {
             ds = ds.field("time", &time);
         }
+        if self.penalty != 0. {
+            ds = ds.field("penalty", &self.penalty);
+        }
 
         ds.finish()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Solution;
+
+    fn solution(distance: f64, penalty: f64) -> Solution {
+        Solution { path: vec![0, 1, 2], distance, time: None, penalty, fitness: 0. }
+    }
+
+    #[test]
+    fn cost_includes_the_penalty() {
+        assert_eq!(solution(250., -20.).cost(), 230.);
+        assert_eq!(solution(250., 0.).cost(), 250.);
+    }
+
+    #[test]
+    fn gap_leaves_the_penalty_out() {
+        assert_eq!(solution(250., -20.).gap(200.), 0.25);
+        assert_eq!(solution(250., 100.).gap(200.), 0.25);
+        assert_eq!(solution(250., 0.).gap(0.), 0.);
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn solutions_round_trip_through_json() {
+        let solution = Solution { path: vec![0, 2, 1], distance: 12., time: Some(7), penalty: -3., fitness: 0.5 };
+
+        let parsed: Solution = serde_json::from_str(&serde_json::to_string(&solution).unwrap()).unwrap();
+        let legacy: Solution = serde_json::from_str(r#"{"path": [0, 1], "distance": 2.0, "time": null, "fitness": 0.0}"#).unwrap();
+
+        assert_eq!(parsed.path, solution.path);
+        assert_eq!((parsed.distance, parsed.time, parsed.penalty, parsed.fitness), (12., Some(7), -3., 0.5));
+        assert_eq!(legacy.penalty, 0.);
+    }
+}
@@ -0,0 +1,1053 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use crate::algorithms::bee_colony::types::ResearchFunction;
+use crate::algorithms::genetic::methods::{Distance, Mate, Replace, Select};
+use crate::algorithms::genetic::types::{MateFunc, MutateFunc, ReplaceFunc, SelectFunc};
+use crate::algorithms::operators::Operator;
+use crate::algorithms::simulated_annealing::types::Cooling;
+use crate::algorithms::tabu_search::types::Neighbourhood;
+use crate::algorithms::types::Scaling;
+use super::algorithms::ant_colony::builder::TSAntColonyAlgorithmBuilder;
+use super::algorithms::bee_colony::builder::TSBeeColonyAlgorithmBuilder;
+use super::algorithms::genetic::builder::TSGeneticAlgorithmBuilder;
+use super::algorithms::particle_swarm::builder::TSParticleSwarmAlgorithmBuilder;
+use super::algorithms::simulated_annealing::builder::TSSimulatedAnnealingAlgorithmBuilder;
+use super::algorithms::tabu_search::builder::TSTabuSearchBuilder;
+use super::constructions::Construction;
+use super::rules::Language;
+use super::solution::Solution;
+use super::types::{City, Matrix, RuleStr, TimeMatrix};
+use super::validation::{validate_rules, validate_rules_in};
+
+// Common interface of the travelling salesman algorithms
+pub trait Solver {
+    fn name(&self) -> &'static str;
+
+    fn parameters(&self) -> String;
+
+    // The observer gets the iteration number and the best solution found so far,
+    // returning false stops the run
+    fn run_with_observer(&self, observer: &mut dyn FnMut(usize, &Solution) -> bool) -> Result<Vec<Solution>, &'static str>;
+
+    fn run(&self) -> Result<Vec<Solution>, &'static str> {
+        self.run_with_observer(&mut |_, _| true)
+    }
+}
+
+// Operators are written as calls of their constructors, e.g. "swap_indexes(3)" or "tournament(5, 0.7)".
+// Trailing optional arguments may be omitted
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "String", into = "String"))]
+pub enum OperatorKind {
+    SwapIndexes { offset: Option<usize> },
+    ReverseElements { offset: Option<usize> },
+    Insertion { offset: Option<usize> },
+    Scramble { offset: Option<usize> },
+    Displacement { offset: Option<usize> },
+    TwoOpt,
+}
+
+impl OperatorKind {
+    pub fn make(&self, matrix: &Matrix) -> Operator<City> {
+        match *self {
+            OperatorKind::SwapIndexes { offset } => Operator::swap_indexes(offset),
+            OperatorKind::ReverseElements { offset } => Operator::reverse_elements(offset),
+            OperatorKind::Insertion { offset } => Operator::insertion(offset),
+            OperatorKind::Scramble { offset } => Operator::scramble(offset),
+            OperatorKind::Displacement { offset } => Operator::displacement(offset),
+            OperatorKind::TwoOpt => {
+                let matrix = matrix.clone();
+                Operator::two_opt(move |a: &City, b: &City| matrix[*a][*b])
+            }
+        }
+    }
+}
+
+impl FromStr for OperatorKind {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let call = Call::parse(spec)?;
+        let kind = match call.name {
+            "swap_indexes" => OperatorKind::SwapIndexes { offset: call.optional(0)? },
+            "reverse_elements" => OperatorKind::ReverseElements { offset: call.optional(0)? },
+            "insertion" => OperatorKind::Insertion { offset: call.optional(0)? },
+            "scramble" => OperatorKind::Scramble { offset: call.optional(0)? },
+            "displacement" => OperatorKind::Displacement { offset: call.optional(0)? },
+            "two_opt" => OperatorKind::TwoOpt,
+            _ => return Err(format!("Unknown operator `{}`", call.name)),
+        };
+
+        call.check_count(1)?;
+        Ok(kind)
+    }
+}
+
+impl Display for OperatorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OperatorKind::SwapIndexes { offset } => write_call(f, "swap_indexes", &[arg(offset)]),
+            OperatorKind::ReverseElements { offset } => write_call(f, "reverse_elements", &[arg(offset)]),
+            OperatorKind::Insertion { offset } => write_call(f, "insertion", &[arg(offset)]),
+            OperatorKind::Scramble { offset } => write_call(f, "scramble", &[arg(offset)]),
+            OperatorKind::Displacement { offset } => write_call(f, "displacement", &[arg(offset)]),
+            OperatorKind::TwoOpt => write_call(f, "two_opt", &[]),
+        }
+    }
+}
+
+// `rate` is the share of the population that is selected
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "String", into = "String"))]
+pub enum SelectionKind {
+    Roulette { rate: Option<f32> },
+    Sus { rate: Option<f32> },
+    Stochastic { rate: Option<f32> },
+    BestN { rate: Option<f32> },
+    Tournament { size: usize, rate: Option<f32> },
+    LinearRanking { pressure: f32, rate: Option<f32> },
+    ExponentialRanking { base: f32, rate: Option<f32> },
+    Truncation { threshold: f32, elitism: usize, rate: Option<f32> },
+    Boltzmann { temperature: f32, cooling: f32, temperature_min: f32, rate: Option<f32> },
+}
+
+impl SelectionKind {
+    pub fn make(&self) -> SelectFunc<City> {
+        match *self {
+            SelectionKind::Roulette { rate } => Select::roulette(rate),
+            SelectionKind::Sus { rate } => Select::sus(Scaling::Windowing, rate),
+            SelectionKind::Stochastic { rate } => Select::stochastic(rate),
+            SelectionKind::BestN { rate } => Select::best_n(rate),
+            SelectionKind::Tournament { size, rate } => Select::tournament(size, rate),
+            SelectionKind::LinearRanking { pressure, rate } => Select::linear_ranking(pressure, rate),
+            SelectionKind::ExponentialRanking { base, rate } => Select::exponential_ranking(base, rate),
+            SelectionKind::Truncation { threshold, elitism, rate } => Select::truncation(threshold, elitism, rate),
+            SelectionKind::Boltzmann { temperature, cooling, temperature_min, rate } => {
+                Select::boltzmann(temperature, cooling, temperature_min, rate)
+            }
+        }
+    }
+
+    // Parameters that would only fail or misbehave during the run
+    pub fn check(&self) -> Result<(), String> {
+        let check = |is_correct: bool, message: &str| if is_correct { Ok(()) } else { Err(message.to_string()) };
+
+        match *self {
+            SelectionKind::Tournament { size, .. } => check(size > 0, "Value must be tournament size > 0"),
+            SelectionKind::LinearRanking { pressure, .. } => {
+                check((1. ..=2.).contains(&pressure), "Value must be 1 <= linear_ranking pressure <= 2")
+            }
+            SelectionKind::ExponentialRanking { base, .. } => {
+                check(base > 0. && base < 1., "Value must be 0 < exponential_ranking base < 1")
+            }
+            SelectionKind::Truncation { threshold, .. } => {
+                check(threshold > 0. && threshold <= 1., "Value must be 0 < truncation threshold <= 1")
+            }
+            SelectionKind::Boltzmann { temperature, cooling, temperature_min, .. } => {
+                check(temperature > 0., "Value must be boltzmann temperature > 0")?;
+                check(temperature_min > 0., "Value must be boltzmann temperature_min > 0")?;
+                check(cooling > 0. && cooling <= 1., "Value must be 0 < boltzmann cooling <= 1")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl FromStr for SelectionKind {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let call = Call::parse(spec)?;
+        let (kind, count) = match call.name {
+            "roulette" => (SelectionKind::Roulette { rate: call.optional(0)? }, 1),
+            "sus" => (SelectionKind::Sus { rate: call.optional(0)? }, 1),
+            "stochastic" => (SelectionKind::Stochastic { rate: call.optional(0)? }, 1),
+            "best_n" => (SelectionKind::BestN { rate: call.optional(0)? }, 1),
+            "tournament" => (SelectionKind::Tournament { size: call.required(0)?, rate: call.optional(1)? }, 2),
+            "linear_ranking" => (SelectionKind::LinearRanking { pressure: call.required(0)?, rate: call.optional(1)? }, 2),
+            "exponential_ranking" => (SelectionKind::ExponentialRanking { base: call.required(0)?, rate: call.optional(1)? }, 2),
+            "truncation" => (
+                SelectionKind::Truncation {
+                    threshold: call.required(0)?,
+                    elitism: call.required(1)?,
+                    rate: call.optional(2)?,
+                },
+                3,
+            ),
+            "boltzmann" => (
+                SelectionKind::Boltzmann {
+                    temperature: call.required(0)?,
+                    cooling: call.required(1)?,
+                    temperature_min: call.required(2)?,
+                    rate: call.optional(3)?,
+                },
+                4,
+            ),
+            _ => return Err(format!("Unknown selection `{}`", call.name)),
+        };
+
+        call.check_count(count)?;
+        Ok(kind)
+    }
+}
+
+impl Display for SelectionKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SelectionKind::Roulette { rate } => write_call(f, "roulette", &[arg(rate)]),
+            SelectionKind::Sus { rate } => write_call(f, "sus", &[arg(rate)]),
+            SelectionKind::Stochastic { rate } => write_call(f, "stochastic", &[arg(rate)]),
+            SelectionKind::BestN { rate } => write_call(f, "best_n", &[arg(rate)]),
+            SelectionKind::Tournament { size, rate } => write_call(f, "tournament", &[Some(size.to_string()), arg(rate)]),
+            SelectionKind::LinearRanking { pressure, rate } => {
+                write_call(f, "linear_ranking", &[Some(pressure.to_string()), arg(rate)])
+            }
+            SelectionKind::ExponentialRanking { base, rate } => {
+                write_call(f, "exponential_ranking", &[Some(base.to_string()), arg(rate)])
+            }
+            SelectionKind::Truncation { threshold, elitism, rate } => {
+                write_call(f, "truncation", &[Some(threshold.to_string()), Some(elitism.to_string()), arg(rate)])
+            }
+            SelectionKind::Boltzmann { temperature, cooling, temperature_min, rate } => write_call(f, "boltzmann", &[
+                Some(temperature.to_string()),
+                Some(cooling.to_string()),
+                Some(temperature_min.to_string()),
+                arg(rate),
+            ]),
+        }
+    }
+}
+
+// Distance based mating uses the share of different edges
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "String", into = "String"))]
+pub enum MateKind {
+    Random,
+    FitnessProportional,
+    Assortative { candidates: usize },
+    Disassortative { candidates: usize },
+    IncestPrevention { threshold: f64, attempts: usize },
+}
+
+impl MateKind {
+    pub fn make(&self) -> MateFunc<City> {
+        match *self {
+            MateKind::Random => Mate::random(),
+            MateKind::FitnessProportional => Mate::fitness_proportional(),
+            MateKind::Assortative { candidates } => Mate::assortative(Distance::shared_edges(), candidates),
+            MateKind::Disassortative { candidates } => Mate::disassortative(Distance::shared_edges(), candidates),
+            MateKind::IncestPrevention { threshold, attempts } => {
+                Mate::incest_prevention(Distance::shared_edges(), threshold, attempts)
+            }
+        }
+    }
+}
+
+impl FromStr for MateKind {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let call = Call::parse(spec)?;
+        let (kind, count) = match call.name {
+            "random" => (MateKind::Random, 0),
+            "fitness_proportional" => (MateKind::FitnessProportional, 0),
+            "assortative" => (MateKind::Assortative { candidates: call.required(0)? }, 1),
+            "disassortative" => (MateKind::Disassortative { candidates: call.required(0)? }, 1),
+            "incest_prevention" => (
+                MateKind::IncestPrevention { threshold: call.required(0)?, attempts: call.required(1)? },
+                2,
+            ),
+            _ => return Err(format!("Unknown mating `{}`", call.name)),
+        };
+
+        call.check_count(count)?;
+        Ok(kind)
+    }
+}
+
+impl Display for MateKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MateKind::Random => write_call(f, "random", &[]),
+            MateKind::FitnessProportional => write_call(f, "fitness_proportional", &[]),
+            MateKind::Assortative { candidates } => write_call(f, "assortative", &[Some(candidates.to_string())]),
+            MateKind::Disassortative { candidates } => write_call(f, "disassortative", &[Some(candidates.to_string())]),
+            MateKind::IncestPrevention { threshold, attempts } => {
+                write_call(f, "incest_prevention", &[Some(threshold.to_string()), Some(attempts.to_string())])
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "String", into = "String"))]
+pub enum ReplaceKind {
+    Plus,
+    Comma,
+    Generational { elitism: usize },
+    Worst { count: usize },
+    Oldest { count: usize },
+    Random { count: usize },
+}
+
+impl ReplaceKind {
+    pub fn make(&self) -> ReplaceFunc<City> {
+        match *self {
+            ReplaceKind::Plus => Replace::plus(),
+            ReplaceKind::Comma => Replace::comma(),
+            ReplaceKind::Generational { elitism } => Replace::generational(elitism),
+            ReplaceKind::Worst { count } => Replace::replace_worst(count),
+            ReplaceKind::Oldest { count } => Replace::replace_oldest(count),
+            ReplaceKind::Random { count } => Replace::replace_random(count),
+        }
+    }
+}
+
+impl FromStr for ReplaceKind {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let call = Call::parse(spec)?;
+        let (kind, count) = match call.name {
+            "plus" => (ReplaceKind::Plus, 0),
+            "comma" => (ReplaceKind::Comma, 0),
+            "generational" => (ReplaceKind::Generational { elitism: call.required(0)? }, 1),
+            "replace_worst" => (ReplaceKind::Worst { count: call.required(0)? }, 1),
+            "replace_oldest" => (ReplaceKind::Oldest { count: call.required(0)? }, 1),
+            "replace_random" => (ReplaceKind::Random { count: call.required(0)? }, 1),
+            _ => return Err(format!("Unknown replacement `{}`", call.name)),
+        };
+
+        call.check_count(count)?;
+        Ok(kind)
+    }
+}
+
+impl Display for ReplaceKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplaceKind::Plus => write_call(f, "plus", &[]),
+            ReplaceKind::Comma => write_call(f, "comma", &[]),
+            ReplaceKind::Generational { elitism } => write_call(f, "generational", &[Some(elitism.to_string())]),
+            ReplaceKind::Worst { count } => write_call(f, "replace_worst", &[Some(count.to_string())]),
+            ReplaceKind::Oldest { count } => write_call(f, "replace_oldest", &[Some(count.to_string())]),
+            ReplaceKind::Random { count } => write_call(f, "replace_random", &[Some(count.to_string())]),
+        }
+    }
+}
+
+// Serde goes through the call strings
+impl TryFrom<String> for OperatorKind {
+    type Error = String;
+
+    fn try_from(spec: String) -> Result<Self, Self::Error> {
+        spec.parse()
+    }
+}
+
+impl From<OperatorKind> for String {
+    fn from(kind: OperatorKind) -> Self {
+        kind.to_string()
+    }
+}
+
+impl TryFrom<String> for SelectionKind {
+    type Error = String;
+
+    fn try_from(spec: String) -> Result<Self, Self::Error> {
+        spec.parse()
+    }
+}
+
+impl From<SelectionKind> for String {
+    fn from(kind: SelectionKind) -> Self {
+        kind.to_string()
+    }
+}
+
+impl TryFrom<String> for MateKind {
+    type Error = String;
+
+    fn try_from(spec: String) -> Result<Self, Self::Error> {
+        spec.parse()
+    }
+}
+
+impl From<MateKind> for String {
+    fn from(kind: MateKind) -> Self {
+        kind.to_string()
+    }
+}
+
+impl TryFrom<String> for ReplaceKind {
+    type Error = String;
+
+    fn try_from(spec: String) -> Result<Self, Self::Error> {
+        spec.parse()
+    }
+}
+
+impl From<ReplaceKind> for String {
+    fn from(kind: ReplaceKind) -> Self {
+        kind.to_string()
+    }
+}
+
+struct Call<'a> {
+    spec: &'a str,
+    name: &'a str,
+    args: Vec<&'a str>,
+}
+
+impl<'a> Call<'a> {
+    fn parse(spec: &'a str) -> Result<Self, String> {
+        let spec = spec.trim();
+        let (name, args) = match spec.find('(') {
+            Some(idx) => {
+                let args = spec[idx + 1..]
+                    .strip_suffix(')')
+                    .ok_or_else(|| format!("Missing `)` in `{}`", spec))?;
+                let args = args.split(',').map(str::trim).filter(|arg| !arg.is_empty()).collect();
+                (spec[..idx].trim(), args)
+            }
+            None => (spec, Vec::new()),
+        };
+
+        Ok(Self { spec, name, args })
+    }
+
+    fn required<V: FromStr>(&self, idx: usize) -> Result<V, String> {
+        self.optional(idx)?
+            .ok_or_else(|| format!("Missing argument {} in `{}`", idx + 1, self.spec))
+    }
+
+    fn optional<V: FromStr>(&self, idx: usize) -> Result<Option<V>, String> {
+        match self.args.get(idx) {
+            None | Some(&"_") => Ok(None),
+            Some(value) => value
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("Invalid argument `{}` in `{}`", value, self.spec)),
+        }
+    }
+
+    fn check_count(&self, count: usize) -> Result<(), String> {
+        if self.args.len() > count {
+            Err(format!("Too many arguments in `{}`", self.spec))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn arg<V: ToString>(value: &Option<V>) -> Option<String> {
+    value.as_ref().map(|value| value.to_string())
+}
+
+// Trailing missing arguments are omitted
+fn write_call(f: &mut Formatter<'_>, name: &str, args: &[Option<String>]) -> std::fmt::Result {
+    let count = args.iter().rposition(|arg| arg.is_some()).map_or(0, |idx| idx + 1);
+    if count == 0 {
+        return write!(f, "{}", name);
+    }
+
+    let args: Vec<&str> = args[..count].iter().map(|arg| arg.as_deref().unwrap_or("_")).collect();
+    write!(f, "{}({})", name, args.join(", "))
+}
+
+// Plain data counterparts of the builders, missing values keep the builder defaults
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(tag = "type", rename_all = "snake_case"))]
+pub enum AlgorithmConfig {
+    Genetic {
+        mutation: OperatorKind,
+        selection: SelectionKind,
+        p_mutation: Option<f32>,
+        mate: Option<MateKind>,
+        replace: Option<ReplaceKind>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        eliminate_duplicates: bool,
+        // (sigma, alpha)
+        fitness_sharing: Option<(f64, f64)>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        crowding: bool,
+    },
+    BeeColony {
+        research: OperatorKind,
+        workers_part: Option<f32>,
+    },
+    AntColony {
+        alpha: Option<f64>,
+        beta: Option<f64>,
+        p: Option<f64>,
+        q: Option<f64>,
+    },
+    SimulatedAnnealing {
+        neighbour: OperatorKind,
+        moves_count: Option<usize>,
+        cooling: Option<Cooling>,
+        temperature: Option<f64>,
+        temperature_min: Option<f64>,
+        initial_acceptance: Option<f64>,
+    },
+    TabuSearch {
+        neighbourhood: Option<Neighbourhood>,
+        tenure: Option<usize>,
+        candidates_count: Option<usize>,
+        diversification: Option<f64>,
+    },
+    ParticleSwarm {
+        inertia: Option<f64>,
+        cognitive: Option<f64>,
+        social: Option<f64>,
+    },
+}
+
+// Counts that do not apply to the algorithm are ignored, constructions are used by
+// the genetic algorithm, the bee and the ant colonies
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SolverConfig {
+    pub algorithm: AlgorithmConfig,
+    pub actors_count: Option<usize>,
+    pub iters_count: Option<usize>,
+    pub solutions_count: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rules: Vec<RuleStr>,
+    // Rule file, its weighted rules are added to the rules when the config is read
+    pub rules_file: Option<String>,
+    // Detected for every rule when not set
+    pub rules_language: Option<Language>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub constructions: Vec<Construction>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub constructions_part: f32,
+}
+
+impl SolverConfig {
+    pub fn new(algorithm: AlgorithmConfig) -> Self {
+        Self {
+            algorithm,
+            actors_count: None,
+            iters_count: None,
+            solutions_count: None,
+            rules: Vec::new(),
+            rules_file: None,
+            rules_language: None,
+            constructions: Vec::new(),
+            constructions_part: 0.,
+        }
+    }
+
+    // Values the builders would panic on or the algorithms cannot run with are reported as errors
+    pub fn check(&self) -> Result<(), String> {
+        let check = |is_correct: bool, message: &str| if is_correct { Ok(()) } else { Err(message.to_string()) };
+
+        check(self.actors_count != Some(0), "Value must be actors_count > 0")?;
+        check(self.solutions_count != Some(0), "Value must be solutions_count > 0")?;
+        check((0. ..=1.).contains(&self.constructions_part), "Value must be 0 <= constructions_part <= 1")?;
+        match &self.algorithm {
+            AlgorithmConfig::Genetic { selection, p_mutation, fitness_sharing, .. } => {
+                selection.check()?;
+                check(p_mutation.is_none_or(|p| (0. ..1.).contains(&p)), "Value must be 0 <= p_mutation < 1")?;
+                if let Some((sigma, alpha)) = fitness_sharing {
+                    check(*sigma > 0. && *sigma <= 1., "Value must be 0 < sigma <= 1")?;
+                    check(*alpha > 0., "Value must be alpha > 0")?;
+                }
+            }
+            AlgorithmConfig::BeeColony { workers_part, .. } => {
+                check(workers_part.is_none_or(|part| part > 0. && part < 1.), "Value must be 0 < workers_part < 1")?;
+            }
+            AlgorithmConfig::AntColony { p, .. } => {
+                check(p.is_none_or(|p| (0. ..=1.).contains(&p)), "Value must be 0 <= p <= 1")?;
+            }
+            AlgorithmConfig::SimulatedAnnealing { cooling, temperature, temperature_min, initial_acceptance, .. } => {
+                check(cooling.is_none_or(|cooling| cooling.is_valid()), "Cooling parameters are not correct")?;
+                check(temperature.is_none_or(|t| t > 0.), "Value must be temperature > 0")?;
+                check(temperature_min.is_none_or(|t| t > 0.), "Value must be temperature_min > 0")?;
+                check(
+                    initial_acceptance.is_none_or(|a| a > 0. && a < 1.),
+                    "Value must be 0 < initial_acceptance < 1",
+                )?;
+            }
+            AlgorithmConfig::TabuSearch { candidates_count, diversification, .. } => {
+                check(*candidates_count != Some(0), "Value must be candidates_count > 0")?;
+                check(diversification.is_none_or(|d| d >= 0.), "Value must be diversification >= 0")?;
+            }
+            AlgorithmConfig::ParticleSwarm { inertia, cognitive, social } => {
+                check(inertia.is_none_or(|i| (0. ..1.).contains(&i)), "Value must be 0 <= inertia < 1")?;
+                check(cognitive.is_none_or(|c| c >= 0.), "Value must be cognitive >= 0")?;
+                check(social.is_none_or(|s| s >= 0.), "Value must be social >= 0")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Rule errors are reported before the run instead of panicking during it
+    pub fn check_rules(&self, city_names: &[String], cities_count: usize, has_time_matrix: bool) -> Result<(), String> {
+        let diagnostics = match self.rules_language {
+            Some(language) => validate_rules_in(&self.rules, language, city_names, cities_count, has_time_matrix),
+            None => validate_rules(&self.rules, city_names, cities_count, has_time_matrix),
+        };
+
+        let errors: Vec<String> = diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.is_error())
+            .map(|diagnostic| diagnostic.render(&self.rules[diagnostic.rule]))
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+}
+
+// City names are optional, rules may use them instead of indices
+pub fn make_solver(
+    config: &SolverConfig,
+    matrix: Matrix,
+    time_matrix: Option<TimeMatrix>,
+    city_names: &[String],
+) -> Result<Box<dyn Solver>, String> {
+    let cities_count = matrix.len();
+    if cities_count == 0 {
+        return Err("Matrix is empty".to_string());
+    }
+    if matrix.iter().any(|row| row.len() != cities_count) {
+        return Err("Matrix is not squared".to_string());
+    }
+    if time_matrix.as_ref().is_some_and(|time_matrix| time_matrix.len() != cities_count) {
+        return Err("Time matrix size is not equal distance matrix".to_string());
+    }
+    if !city_names.is_empty() && city_names.len() != cities_count {
+        return Err("City names count is not equal distance matrix size".to_string());
+    }
+    if city_names.iter().enumerate().any(|(idx, name)| city_names[..idx].contains(name)) {
+        return Err("City names must be unique".to_string());
+    }
+    config.check()?;
+    config.check_rules(city_names, cities_count, time_matrix.is_some())?;
+
+    let rules = config.rules.clone();
+    let constructions = config.constructions.clone();
+
+    match &config.algorithm {
+        AlgorithmConfig::Genetic {
+            mutation, selection, p_mutation, mate, replace, eliminate_duplicates, fitness_sharing, crowding,
+        } => {
+            let mutate_func: MutateFunc<City> = mutation.make(&matrix).into();
+            let mut builder = TSGeneticAlgorithmBuilder::new(matrix, mutate_func, selection.make())
+                .eliminate_duplicates(*eliminate_duplicates)
+                .crowding(*crowding);
+            if let Some(actors_count) = config.actors_count {
+                builder = builder.actors_count(actors_count);
+            }
+            if let Some(iters_count) = config.iters_count {
+                builder = builder.iters_count(iters_count);
+            }
+            if let Some(solutions_count) = config.solutions_count {
+                builder = builder.solutions_count(solutions_count);
+            }
+            if let Some(p_mutation) = p_mutation {
+                builder = builder.p_mutation(*p_mutation);
+            }
+            if let Some(mate) = mate {
+                builder = builder.mate_func(mate.make());
+            }
+            if let Some(replace) = replace {
+                builder = builder.replace_func(replace.make());
+            }
+            if let Some((sigma, alpha)) = fitness_sharing {
+                builder = builder.fitness_sharing(*sigma, *alpha);
+            }
+            if let Some(time_matrix) = time_matrix {
+                builder = builder.time_matrix(time_matrix);
+            }
+            if !city_names.is_empty() {
+                builder = builder.city_names(city_names.to_vec());
+            }
+            builder = match config.rules_language {
+                Some(language) => builder.rules_in(rules, language),
+                None => builder.rules(rules),
+            };
+            Ok(Box::new(builder.constructions(constructions, config.constructions_part).build()))
+        }
+        AlgorithmConfig::BeeColony { research, workers_part } => {
+            let research_func: ResearchFunction<City> = research.make(&matrix).into();
+            let mut builder = TSBeeColonyAlgorithmBuilder::new(matrix, research_func);
+            if let Some(actors_count) = config.actors_count {
+                builder = builder.actors_count(actors_count);
+            }
+            if let Some(iters_count) = config.iters_count {
+                builder = builder.iters_count(iters_count);
+            }
+            if let Some(solutions_count) = config.solutions_count {
+                builder = builder.solutions_count(solutions_count);
+            }
+            if let Some(workers_part) = workers_part {
+                builder = builder.workers_part(*workers_part);
+            }
+            if let Some(time_matrix) = time_matrix {
+                builder = builder.time_matrix(time_matrix);
+            }
+            if !city_names.is_empty() {
+                builder = builder.city_names(city_names.to_vec());
+            }
+            builder = match config.rules_language {
+                Some(language) => builder.rules_in(rules, language),
+                None => builder.rules(rules),
+            };
+            Ok(Box::new(builder.constructions(constructions, config.constructions_part).build()))
+        }
+        AlgorithmConfig::AntColony { alpha, beta, p, q } => {
+            let mut builder = TSAntColonyAlgorithmBuilder::new(matrix);
+            if let Some(actors_count) = config.actors_count {
+                builder = builder.actors_count(actors_count);
+            }
+            if let Some(iters_count) = config.iters_count {
+                builder = builder.iters_count(iters_count);
+            }
+            if let Some(solutions_count) = config.solutions_count {
+                builder = builder.solutions_count(solutions_count);
+            }
+            if let Some(alpha) = alpha {
+                builder = builder.alpha(*alpha);
+            }
+            if let Some(beta) = beta {
+                builder = builder.beta(*beta);
+            }
+            if let Some(p) = p {
+                builder = builder.p(*p);
+            }
+            if let Some(q) = q {
+                builder = builder.q(*q);
+            }
+            if let Some(time_matrix) = time_matrix {
+                builder = builder.time_matrix(time_matrix);
+            }
+            if !city_names.is_empty() {
+                builder = builder.city_names(city_names.to_vec());
+            }
+            builder = match config.rules_language {
+                Some(language) => builder.rules_in(rules, language),
+                None => builder.rules(rules),
+            };
+            Ok(Box::new(builder.constructions(constructions).build()))
+        }
+        AlgorithmConfig::SimulatedAnnealing {
+            neighbour, moves_count, cooling, temperature, temperature_min, initial_acceptance,
+        } => {
+            let neighbour_func: MutateFunc<City> = neighbour.make(&matrix).into();
+            let mut builder = TSSimulatedAnnealingAlgorithmBuilder::new(matrix, neighbour_func);
+            if let Some(iters_count) = config.iters_count {
+                builder = builder.iters_count(iters_count);
+            }
+            if let Some(solutions_count) = config.solutions_count {
+                builder = builder.solutions_count(solutions_count);
+            }
+            if let Some(moves_count) = moves_count {
+                builder = builder.moves_count(*moves_count);
+            }
+            if let Some(cooling) = cooling {
+                builder = builder.cooling(*cooling);
+            }
+            if let Some(temperature) = temperature {
+                builder = builder.temperature(*temperature);
+            }
+            if let Some(temperature_min) = temperature_min {
+                builder = builder.temperature_min(*temperature_min);
+            }
+            if let Some(initial_acceptance) = initial_acceptance {
+                builder = builder.initial_acceptance(*initial_acceptance);
+            }
+            if let Some(time_matrix) = time_matrix {
+                builder = builder.time_matrix(time_matrix);
+            }
+            if !city_names.is_empty() {
+                builder = builder.city_names(city_names.to_vec());
+            }
+            builder = match config.rules_language {
+                Some(language) => builder.rules_in(rules, language),
+                None => builder.rules(rules),
+            };
+            Ok(Box::new(builder.build()))
+        }
+        AlgorithmConfig::TabuSearch { neighbourhood, tenure, candidates_count, diversification } => {
+            let mut builder = TSTabuSearchBuilder::new(matrix);
+            if let Some(iters_count) = config.iters_count {
+                builder = builder.iters_count(iters_count);
+            }
+            if let Some(solutions_count) = config.solutions_count {
+                builder = builder.solutions_count(solutions_count);
+            }
+            if let Some(neighbourhood) = neighbourhood {
+                builder = builder.neighbourhood(*neighbourhood);
+            }
+            if let Some(tenure) = tenure {
+                builder = builder.tenure(*tenure);
+            }
+            if let Some(candidates_count) = candidates_count {
+                builder = builder.candidates_count(*candidates_count);
+            }
+            if let Some(diversification) = diversification {
+                builder = builder.diversification(*diversification);
+            }
+            if let Some(time_matrix) = time_matrix {
+                builder = builder.time_matrix(time_matrix);
+            }
+            if !city_names.is_empty() {
+                builder = builder.city_names(city_names.to_vec());
+            }
+            builder = match config.rules_language {
+                Some(language) => builder.rules_in(rules, language),
+                None => builder.rules(rules),
+            };
+            Ok(Box::new(builder.build()))
+        }
+        AlgorithmConfig::ParticleSwarm { inertia, cognitive, social } => {
+            let mut builder = TSParticleSwarmAlgorithmBuilder::new(matrix);
+            if let Some(actors_count) = config.actors_count {
+                builder = builder.actors_count(actors_count);
+            }
+            if let Some(iters_count) = config.iters_count {
+                builder = builder.iters_count(iters_count);
+            }
+            if let Some(solutions_count) = config.solutions_count {
+                builder = builder.solutions_count(solutions_count);
+            }
+            if let Some(inertia) = inertia {
+                builder = builder.inertia(*inertia);
+            }
+            if let Some(cognitive) = cognitive {
+                builder = builder.cognitive(*cognitive);
+            }
+            if let Some(social) = social {
+                builder = builder.social(*social);
+            }
+            if let Some(time_matrix) = time_matrix {
+                builder = builder.time_matrix(time_matrix);
+            }
+            if !city_names.is_empty() {
+                builder = builder.city_names(city_names.to_vec());
+            }
+            builder = match config.rules_language {
+                Some(language) => builder.rules_in(rules, language),
+                None => builder.rules(rules),
+            };
+            Ok(Box::new(builder.build()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::travelling_salesman::fixtures;
+    use crate::problems::travelling_salesman::helpers::is_permutation;
+
+    fn algorithms() -> Vec<AlgorithmConfig> {
+        vec![
+            AlgorithmConfig::Genetic {
+                mutation: "swap_indexes".parse().unwrap(),
+                selection: "tournament(3)".parse().unwrap(),
+                p_mutation: None,
+                mate: None,
+                replace: None,
+                eliminate_duplicates: false,
+                fitness_sharing: None,
+                crowding: false,
+            },
+            AlgorithmConfig::BeeColony { research: "two_opt".parse().unwrap(), workers_part: None },
+            AlgorithmConfig::AntColony { alpha: None, beta: None, p: None, q: None },
+            AlgorithmConfig::SimulatedAnnealing {
+                neighbour: "reverse_elements".parse().unwrap(),
+                moves_count: None,
+                cooling: None,
+                temperature: None,
+                temperature_min: None,
+                initial_acceptance: None,
+            },
+            AlgorithmConfig::TabuSearch { neighbourhood: None, tenure: None, candidates_count: None, diversification: None },
+            AlgorithmConfig::ParticleSwarm { inertia: None, cognitive: None, social: None },
+        ]
+    }
+
+    fn config(algorithm: AlgorithmConfig) -> SolverConfig {
+        SolverConfig { actors_count: Some(10), iters_count: Some(5), ..SolverConfig::new(algorithm) }
+    }
+
+    fn error(config: &SolverConfig) -> String {
+        make_solver(config, fixtures::matrix(), None, &[]).err().unwrap()
+    }
+
+    #[test]
+    fn operator_specs_round_trip() {
+        for spec in ["swap_indexes", "insertion(2)", "two_opt"] {
+            assert_eq!(spec.parse::<OperatorKind>().unwrap().to_string(), spec);
+        }
+        for spec in ["tournament(5, 0.7)", "truncation(0.5, 2)", "boltzmann(10, 0.9, 1)"] {
+            assert_eq!(spec.parse::<SelectionKind>().unwrap().to_string(), spec);
+        }
+        assert_eq!("incest_prevention(0.5, 3)".parse::<MateKind>().unwrap().to_string(), "incest_prevention(0.5, 3)");
+        assert_eq!("replace_worst(4)".parse::<ReplaceKind>().unwrap(), ReplaceKind::Worst { count: 4 });
+    }
+
+    #[test]
+    fn malformed_specs_are_errors() {
+        assert!("tournament".parse::<SelectionKind>().is_err());
+        assert!("swap_indexes(1, 2)".parse::<OperatorKind>().is_err());
+        assert!("insertion(x)".parse::<OperatorKind>().is_err());
+        assert!("two_opt(".parse::<OperatorKind>().is_err());
+        assert!("unknown".parse::<MateKind>().is_err());
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn configs_round_trip_through_json() {
+        let config = SolverConfig {
+            rules: vec!["8 follows 0 : -300".to_string()],
+            rules_language: Some(Language::English),
+            constructions: vec![Construction::NearestNeighbour, Construction::Christofides],
+            constructions_part: 0.2,
+            ..config(AlgorithmConfig::Genetic {
+                mutation: "insertion(2)".parse().unwrap(),
+                selection: "boltzmann(10, 0.9, 1, 0.5)".parse().unwrap(),
+                p_mutation: Some(0.1),
+                mate: Some(MateKind::Assortative { candidates: 3 }),
+                replace: Some(ReplaceKind::Generational { elitism: 2 }),
+                eliminate_duplicates: true,
+                fitness_sharing: Some((0.3, 1.)),
+                crowding: false,
+            })
+        };
+
+        let json = serde_json::to_string(&config).unwrap();
+        let parsed: SolverConfig = serde_json::from_str(&json).unwrap();
+
+        assert!(json.contains(r#""type":"genetic""#));
+        assert!(json.contains(r#""selection":"boltzmann(10, 0.9, 1, 0.5)""#));
+        assert!(json.contains(r#""constructions":["nearest_neighbour","christofides"]"#));
+        assert_eq!(format!("{:?}", parsed), format!("{:?}", config));
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn missing_config_values_keep_the_defaults() {
+        let config: SolverConfig = serde_json::from_str(
+            r#"{"algorithm": {"type": "simulated_annealing", "neighbour": "two_opt", "cooling": {"geometric": 0.95}}}"#,
+        ).unwrap();
+
+        assert!(config.rules.is_empty() && config.constructions.is_empty());
+        assert_eq!(config.actors_count, None);
+        assert!(matches!(
+            config.algorithm,
+            AlgorithmConfig::SimulatedAnnealing { cooling: Some(Cooling::Geometric(alpha)), temperature: None, .. } if alpha == 0.95
+        ));
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn malformed_config_specs_are_errors() {
+        let result = serde_json::from_str::<SolverConfig>(
+            r#"{"algorithm": {"type": "bee_colony", "research": "swap_indexes(x)"}}"#,
+        );
+
+        assert!(result.unwrap_err().to_string().contains("Invalid argument `x`"));
+    }
+
+    #[test]
+    fn every_algorithm_is_built() {
+        let matrix = fixtures::matrix();
+        for algorithm in algorithms() {
+            let solver = make_solver(&config(algorithm), matrix.clone(), None, &[]).unwrap();
+
+            let solutions = solver.run().unwrap();
+
+            assert!(!solutions.is_empty(), "{}", solver.name());
+            assert!(is_permutation(&solutions[0].path, matrix.len()), "{}", solver.name());
+        }
+    }
+
+    #[test]
+    fn invalid_values_are_errors() {
+        let mut genetic = config(algorithms().remove(0));
+        if let AlgorithmConfig::Genetic { p_mutation, .. } = &mut genetic.algorithm {
+            *p_mutation = Some(1.);
+        }
+        assert!(error(&genetic).contains("p_mutation"));
+
+        let annealing = config(AlgorithmConfig::SimulatedAnnealing {
+            neighbour: OperatorKind::TwoOpt,
+            moves_count: None,
+            cooling: Some(Cooling::Geometric(1.5)),
+            temperature: None,
+            temperature_min: None,
+            initial_acceptance: None,
+        });
+        assert!(error(&annealing).contains("Cooling"));
+
+        let tabu = config(AlgorithmConfig::TabuSearch {
+            neighbourhood: None,
+            tenure: None,
+            candidates_count: Some(0),
+            diversification: None,
+        });
+        assert!(error(&tabu).contains("candidates_count"));
+
+        let swarm = config(AlgorithmConfig::ParticleSwarm { inertia: Some(1.), cognitive: None, social: None });
+        assert!(error(&swarm).contains("inertia"));
+
+        let bees = SolverConfig { constructions_part: 2., ..config(algorithms().remove(1)) };
+        assert!(error(&bees).contains("constructions_part"));
+    }
+
+    #[test]
+    fn invalid_selection_parameters_are_errors() {
+        let cases = [
+            ("tournament(0)", "tournament size"),
+            ("linear_ranking(2.5)", "linear_ranking pressure"),
+            ("linear_ranking(0.5)", "linear_ranking pressure"),
+            ("exponential_ranking(1)", "exponential_ranking base"),
+            ("truncation(0, 1)", "truncation threshold"),
+            ("truncation(1.5, 1)", "truncation threshold"),
+            ("boltzmann(0, 0.9, 1)", "boltzmann temperature"),
+            ("boltzmann(10, 0.9, -1)", "boltzmann temperature_min"),
+            ("boltzmann(10, 0, 1)", "boltzmann cooling"),
+        ];
+        for (spec, message) in cases {
+            let mut genetic = config(algorithms().remove(0));
+            if let AlgorithmConfig::Genetic { selection, .. } = &mut genetic.algorithm {
+                *selection = spec.parse().unwrap();
+            }
+            assert!(error(&genetic).contains(message), "{}", spec);
+        }
+
+        for spec in ["tournament(2)", "linear_ranking(1.5)", "truncation(1, 1)", "boltzmann(10, 1, 1)"] {
+            assert_eq!(spec.parse::<SelectionKind>().unwrap().check(), Ok(()), "{}", spec);
+        }
+    }
+
+    #[test]
+    fn invalid_instances_are_errors() {
+        let config = config(AlgorithmConfig::AntColony { alpha: None, beta: None, p: None, q: None });
+        let names: Vec<String> = (0..10).map(|city| format!("city {}", city % 9)).collect();
+
+        assert!(make_solver(&config, Vec::new(), None, &[]).is_err());
+        assert!(make_solver(&config, vec![vec![0., 1.], vec![1.]], None, &[]).is_err());
+        assert!(make_solver(&config, fixtures::matrix(), Some(vec![vec![0; 2]; 2]), &[]).is_err());
+        assert!(make_solver(&config, fixtures::matrix(), None, &names[..3]).is_err());
+        assert!(make_solver(&config, fixtures::matrix(), None, &names).is_err());
+    }
+
+    #[test]
+    fn invalid_rules_are_errors() {
+        let config = SolverConfig {
+            rules: vec!["Rome follows 0 : 10".to_string()],
+            ..config(AlgorithmConfig::AntColony { alpha: None, beta: None, p: None, q: None })
+        };
+
+        assert!(error(&config).contains("Rome"));
+    }
+}
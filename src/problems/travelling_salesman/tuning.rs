@@ -0,0 +1,703 @@
+use std::fmt::{Display, Formatter};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use crate::algorithms::helpers::set_seed;
+use crate::algorithms::statistics::{friedman, rank_sums, wilcoxon_signed_rank};
+use crate::algorithms::tabu_search::types::Neighbourhood;
+use super::benchmark::Instance;
+use super::solver::{make_solver, AlgorithmConfig, SelectionKind, SolverConfig};
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(tag = "type", rename_all = "snake_case"))]
+pub enum ParameterRange {
+    Integer { min: i64, max: i64 },
+    Real { min: f64, max: f64 },
+    // Sampled uniformly on the log scale, for values like q or temperature
+    LogReal { min: f64, max: f64 },
+    Categorical { values: Vec<String> },
+}
+
+impl ParameterRange {
+    fn sample(&self, rng: &mut StdRng) -> ParameterValue {
+        match self {
+            ParameterRange::Integer { min, max } => ParameterValue::Integer(rng.gen_range(*min..=*max)),
+            ParameterRange::Real { min, max } => ParameterValue::Real(rng.gen_range(*min..=*max)),
+            ParameterRange::LogReal { min, max } => {
+                ParameterValue::Real(rng.gen_range(min.ln()..=max.ln()).exp().clamp(*min, *max))
+            }
+            ParameterRange::Categorical { values } => ParameterValue::Categorical(values.choose(rng).unwrap().clone()),
+        }
+    }
+
+    // Bounds of the numeric ranges and every categorical value
+    fn extremes(&self) -> Vec<ParameterValue> {
+        match self {
+            ParameterRange::Integer { min, max } => vec![ParameterValue::Integer(*min), ParameterValue::Integer(*max)],
+            ParameterRange::Real { min, max } | ParameterRange::LogReal { min, max } => {
+                vec![ParameterValue::Real(*min), ParameterValue::Real(*max)]
+            }
+            ParameterRange::Categorical { values } => values.iter().cloned().map(ParameterValue::Categorical).collect(),
+        }
+    }
+
+    // Normal perturbation of the value, spread is the standard deviation as a part of the range.
+    // Categorical values are kept with 1 - spread probability
+    fn sample_around(&self, value: &ParameterValue, spread: f64, rng: &mut StdRng) -> ParameterValue {
+        match (self, value) {
+            (ParameterRange::Integer { min, max }, ParameterValue::Integer(value)) => {
+                let sampled = *value as f64 + normal(rng) * spread * (max - min) as f64;
+                ParameterValue::Integer((sampled.round() as i64).clamp(*min, *max))
+            }
+            (ParameterRange::Real { min, max }, ParameterValue::Real(value)) => {
+                ParameterValue::Real((value + normal(rng) * spread * (max - min)).clamp(*min, *max))
+            }
+            (ParameterRange::LogReal { min, max }, ParameterValue::Real(value)) => {
+                let sampled = value.ln() + normal(rng) * spread * (max.ln() - min.ln());
+                ParameterValue::Real(sampled.exp().clamp(*min, *max))
+            }
+            (ParameterRange::Categorical { .. }, value) if rng.gen::<f64>() >= spread => value.clone(),
+            _ => self.sample(rng),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(untagged))]
+pub enum ParameterValue {
+    Integer(i64),
+    Real(f64),
+    Categorical(String),
+}
+
+impl ParameterValue {
+    fn as_real(&self) -> Result<f64, String> {
+        match self {
+            ParameterValue::Integer(value) => Ok(*value as f64),
+            ParameterValue::Real(value) => Ok(*value),
+            ParameterValue::Categorical(value) => Err(format!("Number is expected, got `{}`", value)),
+        }
+    }
+
+    fn as_count(&self) -> Result<usize, String> {
+        match self {
+            ParameterValue::Integer(value) => usize::try_from(*value).map_err(|_| format!("Count is expected, got {}", value)),
+            _ => Err(format!("Integer is expected, got `{}`", self)),
+        }
+    }
+
+    fn as_str(&self) -> Result<&str, String> {
+        match self {
+            ParameterValue::Categorical(value) => Ok(value),
+            _ => Err(format!("Categorical value is expected, got {}", self)),
+        }
+    }
+}
+
+impl Display for ParameterValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParameterValue::Integer(value) => write!(f, "{}", value),
+            ParameterValue::Real(value) => write!(f, "{}", value),
+            ParameterValue::Categorical(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Parameter {
+    pub name: String,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub range: ParameterRange,
+}
+
+impl Parameter {
+    pub fn integer(name: &str, min: i64, max: i64) -> Self {
+        if min > max {
+            panic!("Value must be min <= max")
+        }
+        Self { name: name.to_string(), range: ParameterRange::Integer { min, max } }
+    }
+
+    pub fn real(name: &str, min: f64, max: f64) -> Self {
+        if min > max {
+            panic!("Value must be min <= max")
+        }
+        Self { name: name.to_string(), range: ParameterRange::Real { min, max } }
+    }
+
+    pub fn log_real(name: &str, min: f64, max: f64) -> Self {
+        if min <= 0. || min > max {
+            panic!("Value must be 0 < min <= max")
+        }
+        Self { name: name.to_string(), range: ParameterRange::LogReal { min, max } }
+    }
+
+    pub fn categorical(name: &str, values: &[&str]) -> Self {
+        if values.is_empty() {
+            panic!("Values must not be empty")
+        }
+        Self {
+            name: name.to_string(),
+            range: ParameterRange::Categorical { values: values.iter().map(|value| value.to_string()).collect() },
+        }
+    }
+}
+
+// Sets the parameter of the config by its name. Besides the counts and constructions_part, names are the fields
+// of the algorithm config, operators take call strings, e.g. "tournament(5, 0.7)". tournament_size changes
+// the size of the tournament selection only
+pub fn set_parameter(config: &mut SolverConfig, name: &str, value: &ParameterValue) -> Result<(), String> {
+    match name {
+        "actors_count" => config.actors_count = Some(value.as_count()?),
+        "iters_count" => config.iters_count = Some(value.as_count()?),
+        "constructions_part" => config.constructions_part = value.as_real()? as f32,
+        _ => set_algorithm_parameter(&mut config.algorithm, name, value)?,
+    }
+    Ok(())
+}
+
+fn set_algorithm_parameter(algorithm: &mut AlgorithmConfig, name: &str, value: &ParameterValue) -> Result<(), String> {
+    match (algorithm, name) {
+        (AlgorithmConfig::Genetic { mutation, .. }, "mutation") => *mutation = value.as_str()?.parse()?,
+        (AlgorithmConfig::Genetic { selection, .. }, "selection") => *selection = value.as_str()?.parse()?,
+        (AlgorithmConfig::Genetic { selection: SelectionKind::Tournament { size, .. }, .. }, "tournament_size") => {
+            *size = value.as_count()?
+        }
+        (AlgorithmConfig::Genetic { p_mutation, .. }, "p_mutation") => *p_mutation = Some(value.as_real()? as f32),
+        (AlgorithmConfig::Genetic { mate, .. }, "mate") => *mate = Some(value.as_str()?.parse()?),
+        (AlgorithmConfig::Genetic { replace, .. }, "replace") => *replace = Some(value.as_str()?.parse()?),
+        (AlgorithmConfig::BeeColony { research, .. }, "research") => *research = value.as_str()?.parse()?,
+        (AlgorithmConfig::BeeColony { workers_part, .. }, "workers_part") => *workers_part = Some(value.as_real()? as f32),
+        (AlgorithmConfig::AntColony { alpha, .. }, "alpha") => *alpha = Some(value.as_real()?),
+        (AlgorithmConfig::AntColony { beta, .. }, "beta") => *beta = Some(value.as_real()?),
+        (AlgorithmConfig::AntColony { p, .. }, "p") => *p = Some(value.as_real()?),
+        (AlgorithmConfig::AntColony { q, .. }, "q") => *q = Some(value.as_real()?),
+        (AlgorithmConfig::SimulatedAnnealing { neighbour, .. }, "neighbour") => *neighbour = value.as_str()?.parse()?,
+        (AlgorithmConfig::SimulatedAnnealing { moves_count, .. }, "moves_count") => *moves_count = Some(value.as_count()?),
+        (AlgorithmConfig::SimulatedAnnealing { temperature, .. }, "temperature") => *temperature = Some(value.as_real()?),
+        (AlgorithmConfig::SimulatedAnnealing { temperature_min, .. }, "temperature_min") => {
+            *temperature_min = Some(value.as_real()?)
+        }
+        (AlgorithmConfig::SimulatedAnnealing { initial_acceptance, .. }, "initial_acceptance") => {
+            *initial_acceptance = Some(value.as_real()?)
+        }
+        (AlgorithmConfig::TabuSearch { neighbourhood, .. }, "neighbourhood") => {
+            *neighbourhood = Some(match value.as_str()? {
+                "swap" => Neighbourhood::Swap,
+                "two_opt" => Neighbourhood::TwoOpt,
+                "insertion" => Neighbourhood::Insertion,
+                other => return Err(format!("Unknown neighbourhood `{}`", other)),
+            })
+        }
+        (AlgorithmConfig::TabuSearch { tenure, .. }, "tenure") => *tenure = Some(value.as_count()?),
+        (AlgorithmConfig::TabuSearch { candidates_count, .. }, "candidates_count") => {
+            *candidates_count = Some(value.as_count()?)
+        }
+        (AlgorithmConfig::TabuSearch { diversification, .. }, "diversification") => {
+            *diversification = Some(value.as_real()?)
+        }
+        (AlgorithmConfig::ParticleSwarm { inertia, .. }, "inertia") => *inertia = Some(value.as_real()?),
+        (AlgorithmConfig::ParticleSwarm { cognitive, .. }, "cognitive") => *cognitive = Some(value.as_real()?),
+        (AlgorithmConfig::ParticleSwarm { social, .. }, "social") => *social = Some(value.as_real()?),
+        _ => return Err(format!("Unknown parameter `{}` for the algorithm", name)),
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(tag = "type", rename_all = "snake_case"))]
+pub enum TuningMethod {
+    // Iterated F-race: candidates are raced over the runs, after first_test runs the Friedman test with
+    // Wilcoxon post-tests drops the worse ones. Next candidates are sampled around the survivors
+    IteratedRace { first_test: usize, confidence: f64 },
+    // Random candidates, after every round the best 1/eta of them go on with eta times more runs
+    SuccessiveHalving { eta: usize },
+}
+
+pub struct Tuned {
+    pub config: SolverConfig,
+    pub values: Vec<(String, ParameterValue)>,
+    // Runs of the algorithm spent
+    pub evaluations: usize,
+    // Values of the candidates dropped because a run failed, with the error of the run
+    pub failed: Vec<(Vec<(String, ParameterValue)>, String)>,
+}
+
+#[derive(Clone)]
+struct Candidate {
+    values: Vec<ParameterValue>,
+    config: SolverConfig,
+    // Best distance of every run, the run number is the index
+    distances: Vec<f64>,
+    // Error of the failed run, such candidates are dropped instead of ranked
+    error: Option<String>,
+}
+
+// Run i is made on the instance i % instances count with the seed seed + i, so every candidate is
+// evaluated on the same runs and the distances can be compared by ranks
+pub struct Tuner {
+    base: SolverConfig,
+    parameters: Vec<Parameter>,
+    instances: Vec<Instance>,
+    method: TuningMethod,
+    budget: usize,
+    seed: u64,
+}
+
+impl Tuner {
+    pub fn new(base: SolverConfig, parameters: Vec<Parameter>, instances: Vec<Instance>) -> Result<Self, String> {
+        if instances.is_empty() {
+            return Err("Instances must not be empty".to_string());
+        }
+
+        // Unknown names and values that cannot be set are found before the tuning. Values out of the ranges
+        // of the algorithms are left to the runs, which fail and drop their candidates
+        let mut config = base.clone();
+        for parameter in parameters.iter() {
+            for value in parameter.range.extremes() {
+                set_parameter(&mut config, &parameter.name, &value).map_err(|error| format!("{}: {}", parameter.name, error))?;
+            }
+        }
+
+        Ok(Self {
+            base,
+            parameters,
+            instances,
+            method: TuningMethod::IteratedRace { first_test: 5, confidence: 0.95 },
+            budget: 500,
+            seed: 0,
+        })
+    }
+
+    pub fn method(mut self, method: TuningMethod) -> Self {
+        match method {
+            TuningMethod::IteratedRace { first_test, confidence } if first_test < 2 || !(0. ..1.).contains(&confidence) => {
+                panic!("Value must be first_test >= 2 and 0 <= confidence < 1")
+            }
+            TuningMethod::SuccessiveHalving { eta } if eta < 2 => panic!("Value must be eta >= 2"),
+            _ => {}
+        }
+        self.method = method;
+        self
+    }
+
+    // Maximum number of algorithm runs
+    pub fn budget(mut self, budget: usize) -> Self {
+        if budget == 0 {
+            panic!("Value must be greater than 0")
+        }
+        self.budget = budget;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    // Fails when every candidate fails, e.g. on a config error of the base
+    pub fn run(&self) -> Result<Tuned, String> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut failed = Vec::new();
+        let (best, evaluations) = match self.method {
+            TuningMethod::IteratedRace { first_test, confidence } => {
+                self.iterated_race(first_test, 1. - confidence, &mut failed, &mut rng)
+            }
+            TuningMethod::SuccessiveHalving { eta } => self.successive_halving(eta, &mut failed, &mut rng),
+        };
+
+        let named = |values: Vec<ParameterValue>| -> Vec<(String, ParameterValue)> {
+            self.parameters.iter().map(|parameter| parameter.name.clone()).zip(values).collect()
+        };
+        let best = match best {
+            Some(best) => best,
+            None => {
+                let error = failed.first().and_then(|candidate| candidate.error.clone()).unwrap_or_default();
+                return Err(format!("Every candidate failed: {}", error));
+            }
+        };
+
+        Ok(Tuned {
+            values: named(best.values),
+            config: best.config,
+            evaluations,
+            failed: failed
+                .into_iter()
+                .map(|candidate| (named(candidate.values), candidate.error.unwrap_or_default()))
+                .collect(),
+        })
+    }
+
+    fn iterated_race(&self, first_test: usize, alpha: f64, failed: &mut Vec<Candidate>, rng: &mut StdRng) -> (Option<Candidate>, usize) {
+        let dimensions = self.parameters.len().max(1);
+        let iterations = 2 + dimensions.ilog2() as usize;
+        let survivors_min = 2 + dimensions.ilog2() as usize;
+        let mut elites: Vec<Candidate> = Vec::new();
+        let mut used = 0;
+
+        for iteration in 1..=iterations {
+            let budget = (self.budget - used) / (iterations - iteration + 1);
+            let candidates_count = (budget / (first_test + iteration.min(5))).max(2);
+            let spread = (1. / candidates_count as f64).powf(iteration as f64 / dimensions as f64).max(0.05);
+
+            let mut candidates = elites.clone();
+            let mut attempts = 0;
+            while candidates.len() < candidates_count && attempts < 100 * candidates_count {
+                attempts += 1;
+                let values = match self.choose_elite(&elites, rng) {
+                    Some(elite) => self.parameters
+                        .iter()
+                        .zip(&elite.values)
+                        .map(|(parameter, value)| parameter.range.sample_around(value, spread, rng))
+                        .collect(),
+                    None => self.sample(rng),
+                };
+                if !candidates.iter().any(|candidate| candidate.values == values) {
+                    candidates.push(self.make_candidate(values));
+                }
+            }
+
+            let (survivors, spent) = self.race(candidates, budget, first_test, alpha, survivors_min, failed);
+            used += spent;
+            elites = survivors;
+            elites.truncate((candidates_count / 2).max(1));
+
+            if spent == 0 || used >= self.budget {
+                break;
+            }
+        }
+
+        (elites.into_iter().next(), used)
+    }
+
+    // Elites are sorted from the best, better ones are chosen more often
+    fn choose_elite<'a>(&self, elites: &'a [Candidate], rng: &mut StdRng) -> Option<&'a Candidate> {
+        let weights: Vec<usize> = (1..=elites.len()).rev().collect();
+        elites.iter().zip(weights).collect::<Vec<_>>().choose_weighted(rng, |(_, weight)| *weight).ok().map(|(elite, _)| *elite)
+    }
+
+    // Returns the survivors from the best and the number of runs made, the failed candidates are moved to failed
+    fn race(
+        &self,
+        mut alive: Vec<Candidate>,
+        budget: usize,
+        first_test: usize,
+        alpha: f64,
+        survivors_min: usize,
+        failed: &mut Vec<Candidate>,
+    ) -> (Vec<Candidate>, usize) {
+        let mut used = 0;
+        let mut runs_count = 0;
+
+        loop {
+            let missing = alive.iter().filter(|candidate| candidate.distances.len() <= runs_count).count();
+            if alive.is_empty() || used + missing > budget {
+                break;
+            }
+            for candidate in alive.iter_mut() {
+                if candidate.distances.len() <= runs_count {
+                    self.evaluate(candidate, runs_count);
+                    used += 1;
+                }
+            }
+            alive = Self::drop_failed(alive, failed);
+            runs_count += 1;
+
+            if runs_count >= first_test && alive.len() > survivors_min {
+                alive = Self::drop_worse(alive, runs_count, alpha);
+            }
+            if runs_count >= first_test && alive.len() <= survivors_min {
+                break;
+            }
+        }
+
+        (Self::sort_by_ranks(alive, runs_count), used)
+    }
+
+    fn drop_worse(alive: Vec<Candidate>, runs_count: usize, alpha: f64) -> Vec<Candidate> {
+        let blocks = Self::blocks(&alive, runs_count);
+        if alive.len() > 2 && friedman(&blocks).is_none_or(|test| test.p_value >= alpha) {
+            return alive;
+        }
+
+        let alive = Self::sort_by_ranks(alive, runs_count);
+        let best = alive[0].distances[..runs_count].to_vec();
+        alive
+            .into_iter()
+            .enumerate()
+            .filter(|(idx, candidate)| {
+                *idx == 0 || wilcoxon_signed_rank(&best, &candidate.distances[..runs_count]).is_none_or(|test| test.p_value >= alpha)
+            })
+            .map(|(_, candidate)| candidate)
+            .collect()
+    }
+
+    fn successive_halving(&self, eta: usize, failed: &mut Vec<Candidate>, rng: &mut StdRng) -> (Option<Candidate>, usize) {
+        let initial_runs = self.instances.len();
+        let cost = |candidates_count: usize| -> usize {
+            let (mut count, mut runs, mut total) = (candidates_count, initial_runs, 0);
+            loop {
+                total += count * runs;
+                if count == 1 {
+                    return total;
+                }
+                count = count.div_ceil(eta);
+                runs *= eta;
+            }
+        };
+
+        let mut candidates_count = eta;
+        while cost(candidates_count + 1) <= self.budget {
+            candidates_count += 1;
+        }
+
+        let mut alive: Vec<Candidate> = Vec::with_capacity(candidates_count);
+        let mut attempts = 0;
+        while alive.len() < candidates_count && attempts < 100 * candidates_count {
+            attempts += 1;
+            let values = self.sample(rng);
+            if !alive.iter().any(|candidate| candidate.values == values) {
+                alive.push(self.make_candidate(values));
+            }
+        }
+
+        let mut used = 0;
+        let mut runs_count = initial_runs;
+        loop {
+            for candidate in alive.iter_mut() {
+                while candidate.error.is_none() && candidate.distances.len() < runs_count {
+                    self.evaluate(candidate, candidate.distances.len());
+                    used += 1;
+                }
+            }
+
+            alive = Self::sort_by_ranks(Self::drop_failed(alive, failed), runs_count);
+            if alive.len() <= 1 || used >= self.budget {
+                break;
+            }
+            alive.truncate(alive.len().div_ceil(eta));
+            runs_count *= eta;
+        }
+
+        (alive.into_iter().next(), used)
+    }
+
+    fn drop_failed(alive: Vec<Candidate>, failed: &mut Vec<Candidate>) -> Vec<Candidate> {
+        let (failing, alive): (Vec<Candidate>, Vec<Candidate>) = alive.into_iter().partition(|candidate| candidate.error.is_some());
+        failed.extend(failing);
+        alive
+    }
+
+    fn sample(&self, rng: &mut StdRng) -> Vec<ParameterValue> {
+        self.parameters.iter().map(|parameter| parameter.range.sample(rng)).collect()
+    }
+
+    fn make_candidate(&self, values: Vec<ParameterValue>) -> Candidate {
+        let mut config = self.base.clone();
+        // The values are checked in new
+        for (parameter, value) in self.parameters.iter().zip(&values) {
+            set_parameter(&mut config, &parameter.name, value).unwrap();
+        }
+        Candidate { values, config, distances: Vec::new(), error: None }
+    }
+
+    // Adds the distance of the run, a failed run sets the error of the candidate instead
+    fn evaluate(&self, candidate: &mut Candidate, run: usize) {
+        let instance = &self.instances[run % self.instances.len()];
+        set_seed(Some(self.seed.wrapping_add(run as u64)));
+        let solutions = make_solver(&candidate.config, instance.matrix.clone(), instance.time_matrix.clone(), &instance.city_names)
+            .and_then(|solver| solver.run().map_err(|error| error.to_string()));
+        set_seed(None);
+
+        match solutions.map(|solutions| solutions.first().map(|solution| solution.cost())) {
+            Ok(Some(distance)) => candidate.distances.push(distance),
+            Ok(None) => candidate.error = Some(format!("{}: no tour was found", instance.name)),
+            Err(error) => candidate.error = Some(format!("{}: {}", instance.name, error)),
+        }
+    }
+
+    fn blocks(candidates: &[Candidate], runs_count: usize) -> Vec<Vec<f64>> {
+        (0..runs_count)
+            .map(|run| candidates.iter().map(|candidate| candidate.distances[run]).collect())
+            .collect()
+    }
+
+    fn sort_by_ranks(candidates: Vec<Candidate>, runs_count: usize) -> Vec<Candidate> {
+        if runs_count == 0 {
+            return candidates;
+        }
+
+        let (sums, _) = rank_sums(&Self::blocks(&candidates, runs_count));
+        let mut ranked: Vec<(f64, Candidate)> = sums.into_iter().zip(candidates).collect();
+        ranked.sort_by(|a, b| a.0.total_cmp(&b.0));
+        ranked.into_iter().map(|(_, candidate)| candidate).collect()
+    }
+}
+
+// Box-Muller transform
+fn normal(rng: &mut StdRng) -> f64 {
+    let u: f64 = 1. - rng.gen::<f64>();
+    let v: f64 = rng.gen();
+    (-2. * u.ln()).sqrt() * (2. * std::f64::consts::PI * v).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::travelling_salesman::fixtures;
+    use crate::problems::travelling_salesman::solver::OperatorKind;
+
+    fn genetic() -> SolverConfig {
+        SolverConfig::new(AlgorithmConfig::Genetic {
+            mutation: OperatorKind::SwapIndexes { offset: None },
+            selection: SelectionKind::Tournament { size: 3, rate: None },
+            p_mutation: None,
+            mate: None,
+            replace: None,
+            eliminate_duplicates: false,
+            fitness_sharing: None,
+            crowding: false,
+        })
+    }
+
+    fn swarm() -> SolverConfig {
+        SolverConfig {
+            actors_count: Some(10),
+            iters_count: Some(10),
+            ..SolverConfig::new(AlgorithmConfig::ParticleSwarm { inertia: None, cognitive: None, social: None })
+        }
+    }
+
+    fn instances() -> Vec<Instance> {
+        vec![Instance::new("ten_cities", fixtures::matrix()), Instance::new("circle", fixtures::circle(8))]
+    }
+
+    #[test]
+    fn parameters_are_set_by_name() {
+        let mut config = genetic();
+
+        set_parameter(&mut config, "actors_count", &ParameterValue::Integer(30)).unwrap();
+        set_parameter(&mut config, "tournament_size", &ParameterValue::Integer(7)).unwrap();
+        set_parameter(&mut config, "mutation", &ParameterValue::Categorical("insertion(2)".to_string())).unwrap();
+
+        assert_eq!(config.actors_count, Some(30));
+        assert!(matches!(
+            config.algorithm,
+            AlgorithmConfig::Genetic {
+                selection: SelectionKind::Tournament { size: 7, .. },
+                mutation: OperatorKind::Insertion { offset: Some(2) },
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn wrong_parameters_are_errors() {
+        let mut config = genetic();
+
+        assert!(set_parameter(&mut config, "alpha", &ParameterValue::Real(1.)).is_err());
+        assert!(set_parameter(&mut config, "actors_count", &ParameterValue::Integer(-1)).is_err());
+        assert!(set_parameter(&mut config, "p_mutation", &ParameterValue::Categorical("high".to_string())).is_err());
+        assert!(set_parameter(&mut config, "selection", &ParameterValue::Categorical("best".to_string())).is_err());
+    }
+
+    #[test]
+    fn tuners_check_the_parameters() {
+        let error = |parameters: Vec<Parameter>| Tuner::new(genetic(), parameters, instances()).err().unwrap();
+
+        assert!(Tuner::new(genetic(), Vec::new(), Vec::new()).is_err());
+        assert!(error(vec![Parameter::real("alpha", 0., 1.)]).starts_with("alpha"));
+        assert!(error(vec![Parameter::integer("actors_count", -5, 10)]).starts_with("actors_count"));
+        assert!(error(vec![Parameter::categorical("mutation", &["swap_indexes", "three_opt"])]).contains("three_opt"));
+        assert!(Tuner::new(genetic(), vec![Parameter::integer("actors_count", 5, 10)], instances()).is_ok());
+    }
+
+    #[test]
+    fn samples_stay_in_the_ranges() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let integer = ParameterRange::Integer { min: 2, max: 4 };
+        let log_real = ParameterRange::LogReal { min: 0.1, max: 10. };
+
+        for _ in 0..100 {
+            let value = integer.sample_around(&ParameterValue::Integer(4), 1., &mut rng);
+            assert!(matches!(value, ParameterValue::Integer(2..=4)));
+            let value = log_real.sample_around(&ParameterValue::Real(0.1), 1., &mut rng).as_real().unwrap();
+            assert!((0.1..=10.).contains(&value));
+        }
+    }
+
+    #[test]
+    fn failed_runs_set_the_error() {
+        let tuner = Tuner::new(swarm(), vec![Parameter::real("inertia", 0.5, 1.5)], instances()).unwrap();
+
+        let mut failing = tuner.make_candidate(vec![ParameterValue::Real(1.5)]);
+        tuner.evaluate(&mut failing, 0);
+        assert!(failing.distances.is_empty());
+        assert!(failing.error.as_deref().is_some_and(|error| error.starts_with("ten_cities: ") && error.contains("inertia")));
+
+        let mut running = tuner.make_candidate(vec![ParameterValue::Real(0.5)]);
+        tuner.evaluate(&mut running, 1);
+        assert_eq!(running.distances.len(), 1);
+        assert_eq!(running.error, None);
+    }
+
+    #[test]
+    fn config_errors_are_not_ranked() {
+        let base = SolverConfig { actors_count: Some(0), ..swarm() };
+        for method in [
+            TuningMethod::IteratedRace { first_test: 2, confidence: 0.9 },
+            TuningMethod::SuccessiveHalving { eta: 2 },
+        ] {
+            let error = Tuner::new(base.clone(), vec![Parameter::real("inertia", 0., 0.9)], instances())
+                .unwrap()
+                .method(method)
+                .budget(20)
+                .run()
+                .err()
+                .unwrap();
+
+            assert!(error.starts_with("Every candidate failed") && error.contains("actors_count"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn tuning_keeps_to_the_budget() {
+        for method in [
+            TuningMethod::IteratedRace { first_test: 2, confidence: 0.9 },
+            TuningMethod::SuccessiveHalving { eta: 2 },
+        ] {
+            let tuned = Tuner::new(swarm(), vec![Parameter::real("inertia", 0., 0.9)], instances())
+                .unwrap()
+                .method(method)
+                .budget(20)
+                .seed(3)
+                .run()
+                .unwrap();
+
+            assert!(tuned.evaluations <= 20);
+            assert_eq!(tuned.values[0].0, "inertia");
+            let inertia = tuned.values[0].1.as_real().unwrap();
+            assert!(matches!(tuned.config.algorithm, AlgorithmConfig::ParticleSwarm { inertia: Some(value), .. } if value == inertia));
+        }
+    }
+
+    #[test]
+    fn failing_values_are_not_chosen() {
+        let tuned = Tuner::new(swarm(), vec![Parameter::real("inertia", 0.5, 1.5)], instances())
+            .unwrap()
+            .method(TuningMethod::SuccessiveHalving { eta: 2 })
+            .budget(30)
+            .seed(1)
+            .run()
+            .unwrap();
+
+        assert!(tuned.values[0].1.as_real().unwrap() < 1.);
+        assert!(!tuned.failed.is_empty());
+        for (values, error) in tuned.failed {
+            assert!(values[0].1.as_real().unwrap() >= 1., "{:?}", values);
+            assert!(error.contains("inertia"));
+        }
+    }
+}
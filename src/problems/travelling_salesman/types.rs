@@ -1,6 +1,10 @@
 pub type City = usize;
+pub type CityNames = Vec<String>;
 pub type Matrix = Vec<Vec<f64>>;
 pub type TimeMatrix = Vec<Vec<usize>>;
 
 pub type RuleStr = String;
-pub type RuleFn = Box<dyn Fn(&Vec<City>) -> Option<i64>>;
\ No newline at end of file
+pub type RuleFn = Box<dyn Fn(&Vec<City>) -> Option<i64>>;
+pub type ConditionFn = Box<dyn Fn(&Vec<City>) -> Option<bool>>;
+// Length of the tour and the sum of the rule penalties, the penalty is None when a rule excludes the tour
+pub type RuleFitness = std::rc::Rc<dyn Fn(&Vec<City>) -> (f64, Option<f64>)>;
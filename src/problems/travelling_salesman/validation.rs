@@ -0,0 +1,846 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range as Span;
+use regex::{Captures, Regex};
+use crate::problems::travelling_salesman::rules::{
+    parse_city, parse_range, split_group, Keywords, Language, Predicates, Range,
+};
+use crate::problems::travelling_salesman::types::{City, RuleStr};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    // The rule panics or is never applied
+    Error,
+    // The rule is applied, but probably not as intended
+    Warning,
+}
+
+// Span is a byte range in the rule text, rule is the index of the rule in the set
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub rule: usize,
+    pub span: Span<usize>,
+    pub severity: Severity,
+    pub message: String,
+    pub suggestion: Option<String>,
+}
+
+impl Diagnostic {
+    fn error(rule: usize, span: Span<usize>, message: String) -> Self {
+        Self { rule, span, severity: Severity::Error, message, suggestion: None }
+    }
+
+    fn warning(rule: usize, span: Span<usize>, message: String) -> Self {
+        Self { rule, span, severity: Severity::Warning, message, suggestion: None }
+    }
+
+    fn suggest(mut self, suggestion: impl Into<String>) -> Self {
+        self.suggestion = Some(suggestion.into());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    // The diagnostic with the rule text and the span underlined
+    pub fn render(&self, rule: &str) -> String {
+        let offset = rule[..self.span.start].chars().count();
+        let width = rule[self.span.clone()].chars().count().max(1);
+        let mut rendered = format!(
+            "{}\n  | {}\n  | {}{}",
+            self, rule, " ".repeat(offset), "^".repeat(width)
+        );
+        if let Some(suggestion) = &self.suggestion {
+            rendered += &format!("\n  = {}", suggestion);
+        }
+
+        rendered
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{} in rule {} at {}..{}: {}", severity, self.rule, self.span.start, self.span.end, self.message)
+    }
+}
+
+// The language is detected for every rule, like in parse_rule
+pub fn validate_rules(
+    rules: &[RuleStr],
+    city_names: &[String],
+    cities_count: usize,
+    has_time_matrix: bool,
+) -> Vec<Diagnostic> {
+    validate(rules, None, city_names, cities_count, has_time_matrix)
+}
+
+pub fn validate_rules_in(
+    rules: &[RuleStr],
+    language: Language,
+    city_names: &[String],
+    cities_count: usize,
+    has_time_matrix: bool,
+) -> Vec<Diagnostic> {
+    validate(rules, Some(language), city_names, cities_count, has_time_matrix)
+}
+
+fn validate(
+    rules: &[RuleStr],
+    language: Option<Language>,
+    city_names: &[String],
+    cities_count: usize,
+    has_time_matrix: bool,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut constraints = Vec::new();
+
+    for (idx, rule) in rules.iter().enumerate() {
+        let language = language.unwrap_or_else(|| Language::detect(rule));
+        let validator = RuleValidator {
+            rule: idx,
+            text: rule,
+            language,
+            keywords: language.keywords(),
+            predicates: Predicates::new(&language.keywords(), true),
+            city_names,
+            cities_count,
+            has_time_matrix,
+        };
+        validator.validate(&mut diagnostics, &mut constraints);
+    }
+
+    // Later rules are reported, the earlier one is named in the message
+    for (idx, constraint) in constraints.iter().enumerate() {
+        for other in &constraints[..idx] {
+            let conflict = match (constraint.required, other.required) {
+                (true, true) => constraint.fact.conflicts(&other.fact, cities_count),
+                (true, false) | (false, true) => constraint.fact == other.fact,
+                (false, false) => false,
+            };
+            if conflict {
+                diagnostics.push(
+                    Diagnostic::error(
+                        constraint.rule,
+                        constraint.span.clone(),
+                        format!(
+                            "Rule contradicts rule {}: {} and {}",
+                            other.rule,
+                            constraint.describe(city_names),
+                            other.describe(city_names),
+                        ),
+                    ).suggest("no tour satisfies both rules, so every tour is excluded")
+                );
+            }
+        }
+    }
+
+    diagnostics.sort_by_key(|diagnostic| diagnostic.rule);
+    diagnostics
+}
+
+// Structural facts the contradictions are searched among
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Fact {
+    // City, position from 1
+    Position(City, usize),
+    // The first city goes right after the second one
+    Follows(City, City),
+    // The first city goes anywhere before the second one
+    Before(City, City),
+}
+
+impl Fact {
+    fn conflicts(&self, other: &Fact, cities_count: usize) -> bool {
+        match (*self, *other) {
+            (Fact::Position(city_a, order_a), Fact::Position(city_b, order_b)) => {
+                (city_a == city_b) != (order_a == order_b)
+            }
+            (Fact::Follows(next_a, prev_a), Fact::Follows(next_b, prev_b)) => {
+                (next_a == next_b) != (prev_a == prev_b)
+            }
+            (Fact::Position(city, order), Fact::Follows(next, prev))
+            | (Fact::Follows(next, prev), Fact::Position(city, order)) => {
+                (city == next && order == 1) || (city == prev && order == cities_count)
+            }
+            (Fact::Position(city, order), Fact::Before(first, second))
+            | (Fact::Before(first, second), Fact::Position(city, order)) => {
+                (city == second && order == 1) || (city == first && order == cities_count)
+            }
+            (Fact::Follows(next, prev), Fact::Before(first, second))
+            | (Fact::Before(first, second), Fact::Follows(next, prev)) => {
+                next == first && prev == second
+            }
+            (Fact::Before(first_a, second_a), Fact::Before(first_b, second_b)) => {
+                first_a == second_b && second_a == first_b
+            }
+        }
+    }
+
+    fn describe(&self, city_names: &[String]) -> String {
+        let name = |city: City| city_names.get(city).cloned().unwrap_or_else(|| city.to_string());
+        match *self {
+            Fact::Position(city, order) => format!("`{}` at position {}", name(city), order),
+            Fact::Follows(next, prev) => format!("`{}` right after `{}`", name(next), name(prev)),
+            Fact::Before(first, second) => format!("`{}` before `{}`", name(first), name(second)),
+        }
+    }
+}
+
+// A rule of a single predicate that excludes tours, so the fact is required or forbidden
+struct Constraint {
+    rule: usize,
+    span: Span<usize>,
+    fact: Fact,
+    required: bool,
+}
+
+impl Constraint {
+    fn describe(&self, city_names: &[String]) -> String {
+        if self.required {
+            format!("{} is required", self.fact.describe(city_names))
+        } else {
+            format!("{} is forbidden", self.fact.describe(city_names))
+        }
+    }
+}
+
+struct RuleValidator<'a> {
+    rule: usize,
+    text: &'a str,
+    language: Language,
+    keywords: Keywords,
+    predicates: Predicates,
+    city_names: &'a [String],
+    cities_count: usize,
+    has_time_matrix: bool,
+}
+
+impl RuleValidator<'_> {
+    fn validate(&self, diagnostics: &mut Vec<Diagnostic>, constraints: &mut Vec<Constraint>) {
+        let text = self.text;
+        let mut colons = text.match_indices(':').map(|(idx, _)| idx);
+
+        let colon = match colons.next() {
+            Some(colon) => colon,
+            None => {
+                diagnostics.push(
+                    self.error(text.len()..text.len(), "Rule has no action".to_string())
+                        .suggest(format!("add `: <penalty>` or `: {}` after the condition", self.keywords.exclude))
+                );
+                return;
+            }
+        };
+        if let Some(extra) = colons.next() {
+            diagnostics.push(
+                self.error(extra..extra + 1, "Rule has more than one `:`".to_string())
+                    .suggest("a rule is a single `condition : action`")
+            );
+            return;
+        }
+
+        let excludes = self.validate_action(colon + 1, diagnostics);
+
+        let condition = trim(text, 0..colon);
+        if condition.is_empty() {
+            diagnostics.push(self.error(condition, "Rule has no condition".to_string()));
+            return;
+        }
+        if !self.validate_parentheses(condition.clone(), diagnostics) {
+            return;
+        }
+
+        // Parts of the condition between the operators
+        let operators = Regex::new(&format!(r"\s+({}|{})\s+", self.keywords.and, self.keywords.or)).unwrap();
+        let mut parts = Vec::new();
+        let mut start = condition.start;
+        let mut has_or = false;
+        for operator in operators.captures_iter(&text[condition.clone()]) {
+            let found = operator.get(0).unwrap();
+            has_or |= &operator[1] == self.keywords.or;
+            parts.push(start..condition.start + found.start());
+            start = condition.start + found.end();
+        }
+        parts.push(start..condition.end);
+
+        let not = Regex::new(&format!(r"^{}\s+", self.keywords.not)).unwrap();
+        let mut facts: Vec<(Span<usize>, Fact, bool)> = Vec::new();
+        for part in parts {
+            let mut part = trim_parentheses(text, part);
+            let mut negated = false;
+            while let Some(found) = not.find(&text[part.clone()]) {
+                negated = !negated;
+                part = trim_parentheses(text, part.start + found.end()..part.end);
+            }
+
+            if part.is_empty() {
+                diagnostics.push(
+                    self.error(part, "Condition is missing".to_string())
+                        .suggest(format!("`{}` and `{}` join two conditions", self.keywords.and, self.keywords.or))
+                );
+                continue;
+            }
+
+            if let Some(fact) = self.validate_predicate(part.clone(), diagnostics) {
+                facts.push((part, fact, negated));
+            }
+        }
+
+        if has_or {
+            return;
+        }
+
+        // A conjunction of conflicting facts never holds
+        let positive: Vec<&(Span<usize>, Fact, bool)> = facts.iter().filter(|(_, _, negated)| !negated).collect();
+        for (idx, (span, fact, _)) in positive.iter().enumerate() {
+            if let Some((_, other, _)) = positive[..idx].iter().find(|(_, other, _)| fact.conflicts(other, self.cities_count)) {
+                diagnostics.push(self.warning(
+                    span.clone(),
+                    format!(
+                        "Condition never holds: {} conflicts with {}",
+                        fact.describe(self.city_names),
+                        other.describe(self.city_names),
+                    ),
+                ));
+            }
+        }
+
+        if let ([(span, fact, negated)], true) = (facts.as_slice(), excludes) {
+            constraints.push(Constraint { rule: self.rule, span: span.clone(), fact: *fact, required: *negated });
+        }
+    }
+
+    // Whether the rule excludes tours
+    fn validate_action(&self, start: usize, diagnostics: &mut Vec<Diagnostic>) -> bool {
+        let action = trim(self.text, start..self.text.len());
+        let value = &self.text[action.clone()];
+
+        if value == self.keywords.exclude {
+            return true;
+        }
+        if value.is_empty() {
+            diagnostics.push(
+                self.error(start - 1..start, "Rule has no action".to_string())
+                    .suggest(format!("add a penalty or `{}` after `:`", self.keywords.exclude))
+            );
+        } else if value == self.other_language().keywords().exclude {
+            diagnostics.push(
+                self.error(action, format!("`{}` is not a {:?} keyword", value, self.language))
+                    .suggest(format!("use `{}`, keywords of the languages cannot be mixed", self.keywords.exclude))
+            );
+        } else if value.parse::<i64>().is_err() {
+            diagnostics.push(
+                self.error(action, format!("Penalty `{}` is not an integer", value))
+                    .suggest(format!("use an integer penalty, negative for a bonus, or `{}`", self.keywords.exclude))
+            );
+        }
+
+        false
+    }
+
+    fn validate_parentheses(&self, condition: Span<usize>, diagnostics: &mut Vec<Diagnostic>) -> bool {
+        let mut opened = Vec::new();
+        for (idx, char) in self.text[condition.clone()].char_indices() {
+            let idx = condition.start + idx;
+            match char {
+                '(' => opened.push(idx),
+                ')' if opened.pop().is_none() => {
+                    diagnostics.push(self.error(idx..idx + 1, "Unmatched `)`".to_string()));
+                    return false;
+                }
+                _ => {}
+            }
+        }
+        if let Some(idx) = opened.pop() {
+            diagnostics.push(self.error(idx..idx + 1, "Unclosed `(`".to_string()));
+            return false;
+        }
+
+        true
+    }
+
+    // The fact is returned for the predicates the contradictions are searched among
+    fn validate_predicate(&self, part: Span<usize>, diagnostics: &mut Vec<Diagnostic>) -> Option<Fact> {
+        let predicates = &self.predicates;
+        let source = &self.text[part.clone()];
+        let span = |cap: &Captures, group: usize| {
+            let found = cap.get(group).unwrap();
+            part.start + found.start()..part.start + found.end()
+        };
+
+        if let Some(cap) = predicates.follows.captures(source) {
+            let next = self.validate_city(span(&cap, 1), diagnostics);
+            let prev = self.validate_city(span(&cap, 2), diagnostics);
+            let (next, prev) = (next?, prev?);
+            if next == prev {
+                diagnostics.push(self.warning(part, "A city cannot follow itself, the condition never holds".to_string()));
+                return None;
+            }
+            Some(Fact::Follows(next, prev))
+        } else if let Some(cap) = predicates.in_order.captures(source) {
+            let city = self.validate_city(span(&cap, 1), diagnostics);
+            if cap[2].starts_with('[') {
+                self.validate_range(span(&cap, 2), diagnostics);
+                return None;
+            }
+            let order = match cap[2].parse::<usize>() {
+                Ok(order) if (1..=self.cities_count).contains(&order) => order,
+                _ => {
+                    diagnostics.push(
+                        self.error(span(&cap, 2), format!("Position {} is out of range", &cap[2]))
+                            .suggest(format!("positions are counted from 1 to {}", self.cities_count))
+                    );
+                    return None;
+                }
+            };
+            Some(Fact::Position(city?, order))
+        } else if let Some(cap) = predicates.before.captures(source) {
+            let first = self.validate_city(span(&cap, 1), diagnostics);
+            let second = self.validate_city(span(&cap, 2), diagnostics);
+            let (first, second) = (first?, second?);
+            if first == second {
+                diagnostics.push(self.warning(part, "A city cannot be before itself, the condition never holds".to_string()));
+                return None;
+            }
+            Some(Fact::Before(first, second))
+        } else if let Some(cap) = predicates.adjacent.captures(source) {
+            let city_a = self.validate_city(span(&cap, 1), diagnostics);
+            let city_b = self.validate_city(span(&cap, 2), diagnostics);
+            if city_a.is_some() && city_a == city_b {
+                diagnostics.push(self.warning(part, "A city cannot be adjacent to itself, the condition never holds".to_string()));
+            }
+            None
+        } else if let Some(cap) = predicates.edge.captures(source) {
+            let city_from = self.validate_city(span(&cap, 1), diagnostics);
+            let city_to = self.validate_city(span(&cap, 2), diagnostics);
+            if city_from.is_some() && city_from == city_to {
+                diagnostics.push(self.warning(part, "A tour has no edge from a city to itself, the condition never holds".to_string()));
+            }
+            None
+        } else if let Some(cap) = predicates.consecutive.captures(source) {
+            self.validate_group(span(&cap, 1), diagnostics);
+            None
+        } else if let Some(cap) = predicates.on_distance_from_city.captures(source) {
+            self.validate_city(span(&cap, 1), diagnostics);
+            self.validate_city(span(&cap, 2), diagnostics);
+            self.validate_range(span(&cap, 3), diagnostics);
+            None
+        } else if let Some(cap) = predicates.on_distance.captures(source) {
+            self.validate_city(span(&cap, 1), diagnostics);
+            self.validate_range(span(&cap, 2), diagnostics);
+            None
+        } else if let Some(cap) = predicates.on_time_from_city.captures(source) {
+            self.validate_city(span(&cap, 1), diagnostics);
+            self.validate_city(span(&cap, 2), diagnostics);
+            self.validate_range(span(&cap, 3), diagnostics);
+            self.validate_time_matrix(part, diagnostics);
+            None
+        } else if let Some(cap) = predicates.on_time.captures(source) {
+            self.validate_city(span(&cap, 1), diagnostics);
+            self.validate_range(span(&cap, 2), diagnostics);
+            self.validate_time_matrix(part, diagnostics);
+            None
+        } else {
+            let diagnostic = self.error(part, format!("Unknown condition `{}`", source));
+            diagnostics.push(match self.suggest_predicate(source) {
+                Some(suggestion) => diagnostic.suggest(suggestion),
+                None => diagnostic,
+            });
+            None
+        }
+    }
+
+    fn validate_city(&self, span: Span<usize>, diagnostics: &mut Vec<Diagnostic>) -> Option<City> {
+        let token = &self.text[span.clone()];
+        match parse_city(token, self.city_names, self.cities_count) {
+            Ok(city) => Some(city),
+            Err(message) => {
+                let diagnostic = self.error(span, message);
+                let closest = closest(token, self.city_names.iter().map(String::as_str));
+                diagnostics.push(match closest {
+                    Some(name) => diagnostic.suggest(format!("did you mean `{}`?", name)),
+                    None if token.parse::<City>().is_ok() => {
+                        diagnostic.suggest(format!("cities are numbered from 0 to {}", self.cities_count.saturating_sub(1)))
+                    }
+                    None => diagnostic,
+                });
+                None
+            }
+        }
+    }
+
+    fn validate_group(&self, span: Span<usize>, diagnostics: &mut Vec<Diagnostic>) {
+        let mut group: Vec<City> = Vec::new();
+        let mut start = span.start;
+        for token in self.text[span.clone()].split(',') {
+            let token_span = trim(self.text, start..start + token.len());
+            start += token.len() + 1;
+
+            if token_span.is_empty() {
+                diagnostics.push(
+                    self.error(token_span, "City is missing in the group".to_string())
+                        .suggest("cities of a group are separated with commas, like `{A, B, C}`")
+                );
+                continue;
+            }
+            if let Some(city) = self.validate_city(token_span.clone(), diagnostics) {
+                if group.contains(&city) {
+                    let message = format!("City `{}` is repeated in the group, the condition never holds", &self.text[token_span.clone()]);
+                    diagnostics.push(self.warning(token_span, message));
+                }
+                group.push(city);
+            }
+        }
+
+        if split_group(&self.text[span.clone()]).count() < 2 {
+            diagnostics.push(self.warning(span, "A group of a single city always holds".to_string()));
+        }
+    }
+
+    fn validate_range(&self, span: Span<usize>, diagnostics: &mut Vec<Diagnostic>) {
+        let value = &self.text[span.clone()];
+        match parse_range(value.to_string()) {
+            Ok(Range::FromTo(from, to)) if from > to => {
+                diagnostics.push(
+                    self.warning(span, format!("Range `{}` is empty, the condition never holds", value))
+                        .suggest(format!("did you mean `[{},{}]`?", to, from))
+                );
+            }
+            Ok(_) => {}
+            Err(_) => {
+                diagnostics.push(
+                    self.error(span, format!("Malformed range `{}`", value))
+                        .suggest("write a number or a range without spaces, like `[10,20]`, `[10,]` or `[,20]`")
+                );
+            }
+        }
+    }
+
+    fn validate_time_matrix(&self, part: Span<usize>, diagnostics: &mut Vec<Diagnostic>) {
+        if !self.has_time_matrix {
+            diagnostics.push(
+                self.error(part, "Time condition without a time matrix, the rule is never applied".to_string())
+                    .suggest("set a time matrix before the rules")
+            );
+        }
+    }
+
+    // Keywords of both languages close to the words of the condition
+    fn suggest_predicate(&self, source: &str) -> Option<String> {
+        let other = self.other_language();
+        let other_predicates = Predicates::new(&other.keywords(), true);
+        let matches_other = [
+            &other_predicates.follows,
+            &other_predicates.in_order,
+            &other_predicates.on_distance,
+            &other_predicates.on_distance_from_city,
+            &other_predicates.on_time,
+            &other_predicates.on_time_from_city,
+            &other_predicates.before,
+            &other_predicates.adjacent,
+            &other_predicates.edge,
+            &other_predicates.consecutive,
+        ].iter().any(|re| re.is_match(source));
+        let negated_other = source
+            .strip_prefix(other.keywords().not)
+            .is_some_and(|rest| rest.starts_with(char::is_whitespace));
+        if matches_other || negated_other {
+            return Some(format!(
+                "this is the {:?} syntax, but the rule is read as {:?}, keywords of the languages cannot be mixed",
+                other, self.language,
+            ));
+        }
+
+        let words: Vec<&str> = source.split_whitespace().collect();
+        let keywords = [self.language.keywords(), other.keywords()];
+        let candidates = keywords.iter().flat_map(|keywords| {
+            [
+                keywords.follows,
+                keywords.in_order,
+                keywords.on_distance,
+                keywords.on_time,
+                keywords.before,
+                keywords.adjacent,
+                keywords.edge,
+                keywords.consecutive,
+            ]
+        });
+        let phrases = candidates.flat_map(|keyword| {
+            let count = keyword.split_whitespace().count();
+            words
+                .windows(count)
+                .map(|window| (keyword, edit_distance(&window.join(" "), keyword)))
+                .collect::<Vec<(&str, usize)>>()
+        });
+        let closest = phrases
+            .filter(|(_, distance)| *distance > 0 && *distance <= 2)
+            .min_by_key(|(_, distance)| *distance);
+
+        match closest {
+            Some((keyword, _)) => Some(format!("did you mean `{}`?", keyword)),
+            None => {
+                let k = &self.keywords;
+                Some(format!(
+                    "expected `A {} B`, `A {} N`, `A {} D`, `A {} T`, `A {} {} B D`, `A {} {} B T`, \
+                    `A {} B`, `A {} B`, `{} A {} B` or `{{A, B, C}} {}`",
+                    k.follows, k.in_order, k.on_distance, k.on_time, k.on_distance, k.from, k.on_time, k.from,
+                    k.before, k.adjacent, k.edge, k.edge_to, k.consecutive,
+                ))
+            }
+        }
+    }
+
+    fn other_language(&self) -> Language {
+        match self.language {
+            Language::Russian => Language::English,
+            Language::English => Language::Russian,
+        }
+    }
+
+    fn error(&self, span: Span<usize>, message: String) -> Diagnostic {
+        Diagnostic::error(self.rule, span, message)
+    }
+
+    fn warning(&self, span: Span<usize>, message: String) -> Diagnostic {
+        Diagnostic::warning(self.rule, span, message)
+    }
+}
+
+fn trim(text: &str, span: Span<usize>) -> Span<usize> {
+    let source = &text[span.clone()];
+    let start = span.start + (source.len() - source.trim_start().len());
+    let end = span.end - (source.len() - source.trim_end().len());
+    start..end.max(start)
+}
+
+// Parentheses are balanced in the whole condition, so they are dropped from the parts
+fn trim_parentheses(text: &str, span: Span<usize>) -> Span<usize> {
+    let source = &text[span.clone()];
+    let trimmed_start = source.trim_start_matches(|char: char| char == '(' || char.is_whitespace());
+    let trimmed = trimmed_start.trim_end_matches(|char: char| char == ')' || char.is_whitespace());
+    let start = span.start + (source.len() - trimmed_start.len());
+    start..start + trimmed.len()
+}
+
+fn closest<'a>(token: &str, names: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    names
+        .map(|name| (name, edit_distance(token, name)))
+        .filter(|(_, distance)| *distance <= 2)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(name, _)| name)
+}
+
+// Levenshtein distance over chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, char_a) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, char_b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(char_a != *char_b);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::travelling_salesman::fixtures;
+    use crate::problems::travelling_salesman::rules::parse_rule;
+
+    fn validate_one(rule: &str, has_time_matrix: bool) -> Vec<Diagnostic> {
+        validate_rules(&[rule.to_string()], &[], 10, has_time_matrix)
+    }
+
+    // The message and the text under the span of the only diagnostic
+    fn only(rule: &str) -> (String, String, Option<String>) {
+        let diagnostics = validate_one(rule, false);
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        let diagnostic = &diagnostics[0];
+
+        (diagnostic.message.clone(), rule[diagnostic.span.clone()].to_string(), diagnostic.suggestion.clone())
+    }
+
+    #[test]
+    fn valid_rules_have_no_diagnostics() {
+        let rules: Vec<RuleStr> = [
+            "1 следует за 0 : -300",
+            "5 по порядку [2,6] : 10",
+            "5 на времени от 2 [,150] : 10",
+            "(1 follows 0 or 0 follows 1) and not 3 before 8 : 10",
+            "edge from 7 to 0 : exclude",
+            "{1, 2, 3} consecutive : 10",
+        ].iter().map(|rule| rule.to_string()).collect();
+
+        assert_eq!(validate_rules(&rules, &[], 10, true), vec![]);
+    }
+
+    #[test]
+    fn syntax_errors() {
+        assert_eq!(only("1 follows 0").0, "Rule has no action");
+        assert_eq!(only("1 follows 0 : 5 : 6").1, ":");
+        assert_eq!(only(" : 5").0, "Rule has no condition");
+        assert_eq!(only("(1 follows 0 : 5").1, "(");
+        assert_eq!(only("1 follows 0) : 5").0, "Unmatched `)`");
+        assert_eq!(only("1 follows 0 and () : 5").0, "Condition is missing");
+
+        let (message, span, _) = only("1 follows 0 : 1O");
+        assert_eq!((message.as_str(), span.as_str()), ("Penalty `1O` is not an integer", "1O"));
+
+        let diagnostics = validate_rules_in(&["1 follows 0 : исключить".to_string()], Language::English, &[], 10, false);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "`исключить` is not a English keyword");
+        assert!(diagnostics[0].suggestion.as_ref().unwrap().contains("`exclude`"));
+    }
+
+    #[test]
+    fn unknown_predicates_are_explained() {
+        let (message, span, suggestion) = only("1 folows 0 : 5");
+        assert_eq!((message.as_str(), span.as_str()), ("Unknown condition `1 folows 0`", "1 folows 0"));
+        assert_eq!(suggestion.unwrap(), "did you mean `follows`?");
+
+        let (_, span, suggestion) = only("1 следует за 0 и 2 follows 1 : 5");
+        assert_eq!(span, "2 follows 1");
+        assert!(suggestion.unwrap().starts_with("this is the English syntax, but the rule is read as Russian"));
+    }
+
+    #[test]
+    fn cities_are_checked() {
+        let (message, span, suggestion) = only("12 follows 0 : 5");
+        assert_eq!(span, "12");
+        assert!(message.starts_with("City index 12 is out of range"));
+        assert_eq!(suggestion.unwrap(), "cities are numbered from 0 to 9");
+
+        let names: Vec<String> = ["Paris", "Lyon", "Nice"].iter().map(|name| name.to_string()).collect();
+        let rule = "Lion follows Paris : 5";
+        let diagnostics = validate_rules(&[rule.to_string()], &names, 3, false);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(&rule[diagnostics[0].span.clone()], "Lion");
+        assert_eq!(diagnostics[0].suggestion.as_deref(), Some("did you mean `Lyon`?"));
+
+        assert_eq!(only("{1, , 3} consecutive : 5").0, "City is missing in the group");
+        assert_eq!(only("{1, 2, 1} consecutive : 5").1, "1");
+    }
+
+    #[test]
+    fn time_rules_need_the_time_matrix() {
+        let rule = "5 at time [100,] : 10";
+        let diagnostics = validate_one(rule, false);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].is_error());
+        assert_eq!(&rule[diagnostics[0].span.clone()], "5 at time [100,]");
+        assert_eq!(validate_one(rule, true), vec![]);
+    }
+
+    #[test]
+    fn ranges_are_checked() {
+        let diagnostics = validate_one("5 at distance [100,50] : 10", false);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].suggestion.as_deref(), Some("did you mean `[50,100]`?"));
+
+        assert_eq!(only("5 at position [a,b] : 10").0, "Malformed range `[a,b]`");
+        assert_eq!(only("5 at position 11 : 10").0, "Position 11 is out of range");
+    }
+
+    #[test]
+    fn overflowing_bounds_are_malformed_ranges() {
+        let diagnostics = validate_rules(&["0 на дистанции [99999999999,] : 5".to_string()], &[], 10, false);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "Malformed range `[99999999999,]`");
+
+        assert_eq!(only("5 at distance [10,99999999999] : 10").0, "Malformed range `[10,99999999999]`");
+    }
+
+    #[test]
+    fn rules_that_fail_validation_are_not_parsed() {
+        let malformed = [
+            "junk 1 следует за 0 : 5",
+            "1 follows 0 junk : 5",
+            "1 follows 0 : 5 : 6",
+            "(1 follows 0 : 5",
+            "1 follows 0) : 5",
+            ": 5",
+            "1 follows 0 and : 5",
+            "1 at distance [10,99999999999] : 5",
+        ];
+        for rule in malformed {
+            let diagnostics = validate_rules(&[rule.to_string()], &[], 10, false);
+            assert!(diagnostics.iter().any(Diagnostic::is_error), "{}: {:?}", rule, diagnostics);
+            assert!(parse_rule(rule.to_string(), &[], fixtures::matrix(), None).is_err(), "{}", rule);
+        }
+
+        for rule in ["(1 follows 0 or not (2 follows 1)) and 3 follows 2 : 5", "не 1 следует за 0 : исключить"] {
+            assert_eq!(validate_rules(&[rule.to_string()], &[], 10, false), vec![], "{}", rule);
+            assert!(parse_rule(rule.to_string(), &[], fixtures::matrix(), None).is_ok(), "{}", rule);
+        }
+    }
+
+    #[test]
+    fn contradictory_rules_are_errors() {
+        let rules: Vec<RuleStr> = ["not 1 at position 1 : exclude", "3 follows 2 : 5", "not 2 at position 1 : exclude"]
+            .iter()
+            .map(|rule| rule.to_string())
+            .collect();
+        let diagnostics = validate_rules(&rules, &[], 10, false);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule, 2);
+        assert_eq!(
+            diagnostics[0].message,
+            "Rule contradicts rule 0: `2` at position 1 is required and `1` at position 1 is required"
+        );
+
+        // Requiring and forbidding the same edge
+        let rules: Vec<RuleStr> = vec!["not 1 follows 0 : exclude".to_string(), "1 follows 0 : exclude".to_string()];
+        assert_eq!(validate_rules(&rules, &[], 10, false).len(), 1);
+
+        // Penalties do not exclude tours, so they never contradict
+        let rules: Vec<RuleStr> = vec!["1 at position 1 : 5".to_string(), "2 at position 1 : 5".to_string()];
+        assert_eq!(validate_rules(&rules, &[], 10, false), vec![]);
+    }
+
+    #[test]
+    fn conditions_that_never_hold_are_warnings() {
+        let diagnostics = validate_one("1 at position 1 and 2 at position 1 : 5", false);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+
+        assert_eq!(validate_one("1 at position 1 or 2 at position 1 : 5", false), vec![]);
+        assert_eq!(validate_one("1 follows 1 : 5", false)[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn language_may_be_given() {
+        let rules = vec!["1 follows 0 : 5".to_string()];
+
+        assert_eq!(validate_rules_in(&rules, Language::English, &[], 10, false), vec![]);
+        assert_eq!(validate_rules_in(&rules, Language::Russian, &[], 10, false).len(), 1);
+    }
+
+    #[test]
+    fn diagnostics_are_rendered_with_the_span() {
+        let rule = "1 follows Х : 5";
+        let diagnostics = validate_one(rule, false);
+
+        assert_eq!(
+            diagnostics[0].render(rule),
+            "error in rule 0 at 10..12: Unknown city `Х`\n  | 1 follows Х : 5\n  |           ^"
+        );
+    }
+
+    #[test]
+    fn edit_distance_counts_chars() {
+        assert_eq!(edit_distance("follows", "folows"), 1);
+        assert_eq!(edit_distance("раньше", "раньшe"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+}